@@ -0,0 +1,102 @@
+use bibcitex_core::{
+    SearchIndex, bib::Reference, rank_references, search_references, utils::read_bibliography,
+};
+use biblatex::Bibliography;
+use criterion::{Criterion, criterion_group, criterion_main};
+use std::{fmt::Write, hint::black_box};
+
+const SYLLABLES: &[&str] = &[
+    "ka", "lo", "mi", "ne", "ru", "sa", "te", "vi", "zo", "gal", "ber", "kin", "tor", "len", "mar",
+    "dis", "con", "pro", "ex", "ti", "ma", "no", "pa", "qua", "ri", "sto", "un", "ver", "hy", "el",
+    "op", "ad", "spec", "tral", "grid", "mul", "form", "lin", "ear", "ton",
+];
+
+/// A deterministic pseudo-random number generator.
+struct Lcg(u64);
+
+impl Lcg {
+    fn next(&mut self, len: usize) -> usize {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (self.0 >> 33) as usize % len
+    }
+
+    fn word(&mut self, syllables: usize) -> String {
+        (0..syllables)
+            .map(|_| SYLLABLES[self.next(SYLLABLES.len())])
+            .collect()
+    }
+}
+
+/// Generate a library of `n` articles with a vocabulary of a few thousand words.
+fn library(n: usize) -> Vec<Reference> {
+    let mut rng = Lcg(42);
+    let words = (0..5000).map(|_| rng.word(3)).collect::<Vec<_>>();
+    let names = (0..2000).map(|_| rng.word(2)).collect::<Vec<_>>();
+    let mut src = String::new();
+    for i in 0..n {
+        let title = (0..8)
+            .map(|_| words[rng.next(words.len())].as_str())
+            .collect::<Vec<_>>();
+        let authors = (0..3)
+            .map(|_| names[rng.next(names.len())].as_str())
+            .collect::<Vec<_>>();
+        let _ = writeln!(
+            src,
+            "@article{{{}{i}, author = {{{}}}, title = {{{}}}, journal = {{J. {}}}, year = {{{}}}}}",
+            authors[0],
+            authors.join(" and "),
+            title.join(" "),
+            words[rng.next(100)],
+            1950 + rng.next(75),
+        );
+    }
+    read_bibliography(Bibliography::parse(&src).unwrap())
+}
+
+fn bench_search(c: &mut Criterion) {
+    let references = library(20_000);
+    let sample = &references[1234];
+    let author = sample.author.as_ref().unwrap()[0].trim().to_lowercase();
+    let title = sample.title.as_ref().unwrap()[0].get().to_lowercase();
+    let title = title.split(' ').take(2).collect::<Vec<_>>().join(" ");
+    let key = sample.cite_key[..sample.cite_key.len() - 1].to_string();
+    let queries = [
+        author.clone(),
+        title,
+        format!("author:{author} year:1960..2020"),
+        key,
+    ];
+
+    c.bench_function("index build 20k", |b| {
+        b.iter(|| SearchIndex::new(black_box(references.clone())))
+    });
+    let index = SearchIndex::new(references.clone());
+    for query in &queries {
+        c.bench_function(&format!("index search 20k `{query}`"), |b| {
+            b.iter(|| index.search(black_box(query)))
+        });
+        c.bench_function(&format!("index rank 20k `{query}`"), |b| {
+            b.iter(|| index.rank(black_box(query)))
+        });
+        c.bench_function(&format!("scan search 20k `{query}`"), |b| {
+            b.iter(|| search_references(black_box(&references), black_box(query)))
+        });
+        c.bench_function(&format!("scan rank 20k `{query}`"), |b| {
+            b.iter(|| rank_references(black_box(&references), black_box(query)))
+        });
+    }
+    // A typo in the author name
+    let typo = format!("{}{}", &author[1..2], &author[..1]) + &author[2..];
+    c.bench_function(&format!("index rank 20k `{typo}`"), |b| {
+        b.iter(|| index.rank(black_box(&typo)))
+    });
+    c.bench_function(&format!("scan rank 20k `{typo}`"), |b| {
+        b.iter(|| rank_references(black_box(&references), black_box(&typo)))
+    });
+}
+
+criterion_group!(benches, bench_search);
+criterion_main!(benches);
@@ -1,13 +1,23 @@
-use crate::{Error, Result, utils::merge_chunks};
-use biblatex::{Bibliography, Chunk, EntryType, PermissiveType, Person, Spanned};
+use crate::{
+    Annotation, BibSource, DEFAULT_TAG_SEPARATORS, Encoding, Error, JABREF_GROUP_SEPARATOR, Result,
+    tag::split_tags, utils::merge_chunks,
+};
+use biblatex::{Bibliography, Chunk, ChunksExt, EntryType, PermissiveType, Person, Spanned};
 use dioxus::prelude::Props;
-use fs_err as fs;
 use std::{ops::Range, path::Path};
 
-/// Parse BibTeX database `.bib` file
+/// Parse BibTeX database `.bib` file, detecting its encoding
 pub fn parse(file_path: impl AsRef<Path>) -> Result<Bibliography> {
-    let src = fs::read_to_string(file_path)?;
-    Ok(Bibliography::parse(&src)?)
+    parse_with_encoding(file_path, None)
+}
+
+/// Parse BibTeX database `.bib` file in the given encoding, detecting it if `None`
+pub fn parse_with_encoding(
+    file_path: impl AsRef<Path>,
+    encoding: Option<&'static Encoding>,
+) -> Result<Bibliography> {
+    let source = BibSource::read(file_path, encoding)?;
+    Ok(Bibliography::parse(&source.text)?)
 }
 
 /// Wrap a `biblatex::Entry` into a `Reference`, with detailed fields.
@@ -81,10 +91,17 @@ pub struct Reference {
     pub arxiv_primary_class: Option<String>,
     /// how published
     pub how_published: Option<String>,
+    /// keywords
+    pub keywords: Option<Vec<String>>,
+    /// JabRef static groups
+    pub groups: Option<Vec<String>>,
+    /// private notes, reading status and rating, see [`crate::NoteStore::annotate`]
+    pub annotation: Option<Annotation>,
 }
 
-impl From<&biblatex::Entry> for Reference {
-    fn from(entry: &biblatex::Entry) -> Self {
+impl Reference {
+    /// Read an entry, splitting the `keywords` field into tags on any of `tag_separators`.
+    pub fn from_entry(entry: &biblatex::Entry, tag_separators: &str) -> Self {
         let key = entry.key.clone();
         let source = entry
             .to_bibtex_string()
@@ -225,6 +242,12 @@ impl From<&biblatex::Entry> for Reference {
                 .first()
                 .map(|chunk| chunk.get().to_string())
         });
+        let keywords = entry
+            .get("keywords")
+            .map(|chunks| split_tags(&chunks.format_verbatim(), tag_separators));
+        let groups = entry
+            .get("groups")
+            .map(|chunks| split_tags(&chunks.format_verbatim(), JABREF_GROUP_SEPARATOR));
         Self {
             cite_key: key,
             source,
@@ -260,10 +283,19 @@ impl From<&biblatex::Entry> for Reference {
             archive_prefix,
             arxiv_primary_class,
             how_published,
+            keywords,
+            groups,
+            annotation: None,
         }
     }
 }
 
+impl From<&biblatex::Entry> for Reference {
+    fn from(entry: &biblatex::Entry) -> Self {
+        Self::from_entry(entry, DEFAULT_TAG_SEPARATORS)
+    }
+}
+
 impl Reference {
     pub fn key(&self) -> String {
         self.cite_key.clone()
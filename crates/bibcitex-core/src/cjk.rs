@@ -0,0 +1,249 @@
+//! CJK-aware text folding for search.
+//!
+//! Text is folded to half-width forms and simplified characters before matching, so that
+//! `ＡＢＣ` matches `abc` and `數學` matches `数学`. Text with CJK ideographs can also be matched by
+//! its full pinyin or pinyin initials, e.g., `zhongguokexue` or `zgkx` for `中国科学`.
+use once_cell::sync::Lazy;
+use pinyin::ToPinyin;
+use std::{collections::HashMap, sync::RwLock};
+
+/// Traditional characters, sorted, paired with [`SIMPLIFIED`] by position
+const TRADITIONAL: &str = "\
+並亂亞佈佔併來侖侶俁係俠倉個們倫偉側偵偽傑傘備傢傭傳債傷傾僂僅僉僑僕僥僨價儀儂億\
+儈儉儕償優儲儺儻兇兌兒內兩冊冪凍凜凱別刪剄則剗剛剝剮剴創劃劇劉劊劌劍劑勁動務勝勞\
+勢勱勳勵勸勻匭匯區協卻厙厭厲厴參叢吳呂咼員唄問啞啟啢喚喪喫喬單喲嗆嗇嗊嗎嗚嗩嗶嘆\
+嘍嘔嘖嘜嘩嘮嘯嘰嘵嘸噁噓噝噠噥噦噯噲噴噸嚀嚇嚌嚕嚦嚨嚮嚳嚴嚶囀囁囂囈囑圇國圍園圓\
+圖團埡執堅堊堖堝堯報場塊塋塏塒塢塤塵塹墊墜墮墳墾壇壓壘壙壚壞壟壠壢壩壯壺壼壽夠夢\
+夾奐奧奩奪奮妝姍娛婁婦婭媧媯媼媽嫋嫗嫵嫻嫿嬈嬋嬌嬙嬡嬤嬪嬰嬸孌孫學孿宮寢實寧審寫\
+寬寵寶將專尋對導尷屆屍屜屢層屨屬岡峴島峽崍崗崢崬嵐嶁嶄嶇嶔嶗嶠嶢嶧嶸嶺嶼嶽巋巒巔\
+巰帥師帳帶幀幃幗幘幟幣幫幬幹幾庫廁廂廄廈廚廝廟廠廡廢廩廬廳廻弳張強彆彈彌彎彙彥後\
+徑從徠復徵徹恥悅悵惡惱惲惻愛愜愴愷愾態慍慘慚慟慣慪慫慮慳慶憂憊憐憑憒憚憤憫憮憲憶\
+懇應懌懟懣懨懲懶懷懸懺懼懾戀戇戔戧戩戰戲戶拋挾捨捫掃掄掙掛採揀揚換揮損搖搗搵搶摑\
+摜摟摯摳摶摻撈撏撐撓撟撣撥撫撲撳撻撾撿擁擄擇擊擋擔據擠擬擯擰擱擲擴擷擺擻擼擾攄攆\
+攏攔攖攙攛攜攝攢攣攤攪攬敗敘敵數斂斃斕斬斷於時晉晝暈暉暘暢暫曄曆曇曉曖曠曨曬書會\
+朧東柵梔梘條梟棄棖棗棟棧棲椏楊楓楨業極榪榮榿構槍槧槨槳樁樂樅樓標樞樣樸樹樺橈橋機\
+橢橫檁檉檔檜檟檢檣檮檯檳檸檻櫃櫓櫚櫛櫝櫞櫟櫥櫧櫨櫪櫬櫳櫸櫻欄權欏欒欖欞欽歐歟歡歲\
+歷歸歿殘殞殤殫殮殯殲殺殼毀毆氈氣氫氬氳決沒沖況洶浹涇涼淚淨淪淵淶淺渙減渦測渾湊湞\
+湯準溝溫滄滅滌滎滬滯滲滸滿漁漚漢漣漬漲漵漸漿潁潑潔潛潤潯潰潿澀澆澇澗澠澤澦澩澮濁\
+濃濕濘濛濟濤濫濰濱濺濼濾瀅瀆瀉瀋瀏瀘瀝瀟瀠瀧瀨瀲瀾灃灄灑灘灝灣灤灩災為烏烴無煉煒\
+煙煢煥煩煬熒熗熱熾燁燈燉燒燙燜營燦燭燴燼燾爍爐爛爭爺爾牆牘牽犖犛犢犧狀狹狽猙猶猻\
+獁獄獅獎獨獪獫獮獰獲獵獷獸獺獻獼玀現琺琿瑋瑣瑤瑩瑪瑲璉璣璫環璽瓊瓏瓔甌甕產畝畢畫\
+異當疇疊痙痺瘂瘋瘍瘞瘡瘧瘻療癒癘癟癤癭癮癰癱癲發皚皰皺盜盞盡監盤盧眥眾睜瞼矇矚矯\
+硜硤硨硯碩碭確碼磚磣磧磯磽礎礙礦礪礫礬礱祕祿禍禎禕禦禪禮禰禿稈種稱穀積穎穠穡穢穩\
+窩窪窮窯窺竄竅竇竊競筆筍箋箏節範築簡簽簾籃籌籠籬粵糝糞糧糲糴糶糾紀紂約紅紆紇紈紉\
+紋納紐紓純紕紗紙級紛紜紡細紱紲紳紹紺紼紿絀終組絆絎結絕絛絝絞絡絢給絨統絲絳絹綁綃\
+綆綈綉綏經綜綞綢綬維綱網綴綵綸綹綺綻綽綾綿緄緇緊緋緒緗緘緙線緝緞締緡緣緦編緩緬緯\
+緱緲練緶緹縈縉縊縋縐縑縕縗縛縝縞縟縣縫縭縮縱縲縵縷縹總績繃繅繆繒織繕繚繞繢繩繪繫\
+繭繮繯繰繹繼繽繾纈續纍纏纓纘纜罌罰罷羅羆羈羋羥義習翹耬聖聞聯聰聲聳聵聶職聹聽聾肅\
+脅脈脛脫脹腎腡腦腫腳腸膕膚膠膩膽膾膿臉臍臏臘臚臠臨臺與興舉舊艙艦艫艱芻苧茲莊莖莧\
+華萇萊萬萵葉葒葦葷蒐蒔蒞蒼蓀蓆蓋蓮蓯蓴蓽蔔蔞蔣蔥蔦蔭蕁蕆蕎蕒蕕蕘蕢蕩蕪蕭蕷薈薊薌\
+薑薔薟薦薩薺藍藎藝藥藪藶藹藺蘄蘆蘇蘊蘋蘚蘞蘢蘭蘺蘿處虛虜號虧虯蛺蛻蜆蝕蝟蝦蝸螄螞\
+螢螻蟄蟈蟎蟣蟬蟯蟲蟶蟻蠅蠆蠍蠐蠑蠔蠟蠣蠱蠶蠻術衛衝袞裏補裝裡製複褲褳褸襇襖襝襠襤\
+襪襯襲見規覓視覘覡覦親覬覯覲覷覺覽覿觀觴觶觸訂訃計訊訌討訐訓訕訖記訛訝訟訣訥訪設\
+許訴訶診詁詆詎詐詒詔評詘詛詞詠詡詢詣試詩詫詬詭詮詰話該詳詵詼詿誄誅誆誇誌認誑誒誕\
+誘誚語誠誡誣誤誥誦誨說誰課誶誹誼調諂諄談諉請諍諏諑諒論諗諛諜諞諢諤諦諧諫諭諮諱諳\
+諶諷諸諺諼諾謀謁謂謄謅謊謎謐謔謖謗謙謚講謝謠謨謫謬謳謹謾證譎譏譖識譙譚譜譫譯議譴\
+護譽讀變讎讒讓讕讖讜讞豈豎豐豔豬貓貝貞負財貢貧貨販貪貫責貯貰貲貳貴貶買貸貺費貼貽\
+貿賀賁賂賃賄賅資賈賊賑賒賓賕賚賜賞賠賡賢賣賤賦質賬賭賴賺賻購賽賾贄贅贇贈贊贍贏贐\
+贓贖贗贛趕趙趨趲跡踐踴蹌蹕蹣蹤蹺躂躉躊躋躍躑躒躓躕躚躡躥躪軀車軋軌軍軒軔軛軟軤軫\
+軲軸軹軺軻軼軾較輅輇載輊輒輔輕輛輜輝輞輟輥輦輩輪輬輯輳輸輻輾轀轂轄轅轆轉轍轎轔轟\
+轢轤辦辭辯農迴逕這連週進運過達違遙遜遞遠適遲遷選遺遼邁還邇邊邏邐郟郵鄆鄉鄒鄔鄖鄧\
+鄭鄰鄲鄴鄶鄺酈醃醜醞醫醬釀釁釃釅釋釓釔釕釗釘釙針釣釧釩釷釹鈉鈍鈐鈑鈔鈕鈞鈣鈦鈮鈴\
+鈹鈾鈿鉀鉑鉛鉤鉬鉻銀銅銑銜銻鋁鋅鋇鋒鋤鋪鋰鋸鋼錄錢錦錫錯錳鍋鍍鍵鍾鎂鎖鎢鎣鎮鏈鏡\
+鏨鏽鐘鐲鐳鐵鑄鑑鑒鑰鑼鑽鑾鑿長門閂閃閆閉開閏閑間閔閘閡閣閥閨閩閭閻闆闈闊闋闌闐闔\
+闕闖關闞闡闢闥陘陝陣陰陳陸陽隊階隕際隨險隱隴隸隻雖雙雛雜雞離難雲電霧霽靂靄靈靚靜\
+靦鞏韁韃韉韋韌韓韙韜韻響頁頂頃項順須頊頌頎頏預頑頒頓頗領頜頡頦頭頰頷頸頹頻顆題額\
+顎顏顓願顙顛類顢顥顧顫顬顯顰顱顴風颱颳颶颼飄飆飛飢飩飪飭飯飲飼飽飾餃餅餉養餌餓餘\
+館餵饅饋饑饒饞馬馭馮馱馳馴駁駐駒駕駘駙駛駝駟駢駭駱駿騁騎騙騰騷騾驀驃驅驍驕驗驚驛\
+驟驢驤驥驪骯髏髒體髕髖髮鬆鬍鬚鬢鬥鬧鬨鬩鬮鬱魎魘魚魯鮑鮮鯉鯨鰓鱗鳥鳧鳩鳳鳴鴉鴛鴦\
+鴨鴻鵝鵬鶯鶴鷹鸚鹵鹹鹼鹽麗麥麩麵麼黃點黨黴黷鼇鼉齊齋齎齏齒齔齙齜齟齠齡齦齧齪齬齲\
+齶齷龍龐龔龕龜";
+
+/// Simplified forms of [`TRADITIONAL`]
+const SIMPLIFIED: &str = "\
+并乱亚布占并来仑侣俣系侠仓个们伦伟侧侦伪杰伞备家佣传债伤倾偻仅佥侨仆侥偾价仪侬亿\
+侩俭侪偿优储傩傥凶兑儿内两册幂冻凛凯别删刭则刬刚剥剐剀创划剧刘刽刿剑剂劲动务胜劳\
+势劢勋励劝匀匦汇区协却厍厌厉厣参丛吴吕呙员呗问哑启唡唤丧吃乔单哟呛啬唝吗呜唢哔叹\
+喽呕啧唛哗唠啸叽哓呒恶嘘咝哒哝哕嗳哙喷吨咛吓哜噜呖咙向喾严嘤啭嗫嚣呓嘱囵国围园圆\
+图团垭执坚垩垴埚尧报场块茔垲埘坞埙尘堑垫坠堕坟垦坛压垒圹垆坏垄垅坜坝壮壶壸寿够梦\
+夹奂奥奁夺奋妆姗娱娄妇娅娲妫媪妈袅妪妩娴婳娆婵娇嫱嫒嬷嫔婴婶娈孙学孪宫寝实宁审写\
+宽宠宝将专寻对导尴届尸屉屡层屦属冈岘岛峡崃岗峥岽岚嵝崭岖嵚崂峤峣峄嵘岭屿岳岿峦巅\
+巯帅师帐带帧帏帼帻帜币帮帱干几库厕厢厩厦厨厮庙厂庑废廪庐厅回弪张强别弹弥弯汇彦后\
+径从徕复征彻耻悦怅恶恼恽恻爱惬怆恺忾态愠惨惭恸惯怄怂虑悭庆忧惫怜凭愦惮愤悯怃宪忆\
+恳应怿怼懑恹惩懒怀悬忏惧慑恋戆戋戗戬战戏户抛挟舍扪扫抡挣挂采拣扬换挥损摇捣揾抢掴\
+掼搂挚抠抟掺捞挦撑挠挢掸拨抚扑揿挞挝捡拥掳择击挡担据挤拟摈拧搁掷扩撷摆擞撸扰摅撵\
+拢拦撄搀撺携摄攒挛摊搅揽败叙敌数敛毙斓斩断于时晋昼晕晖旸畅暂晔历昙晓暧旷昽晒书会\
+胧东栅栀枧条枭弃枨枣栋栈栖桠杨枫桢业极杩荣桤构枪椠椁桨桩乐枞楼标枢样朴树桦桡桥机\
+椭横檩柽档桧槚检樯梼台槟柠槛柜橹榈栉椟橼栎橱槠栌枥榇栊榉樱栏权椤栾榄棂钦欧欤欢岁\
+历归殁残殒殇殚殓殡歼杀壳毁殴毡气氢氩氲决没冲况汹浃泾凉泪净沦渊涞浅涣减涡测浑凑浈\
+汤准沟温沧灭涤荥沪滞渗浒满渔沤汉涟渍涨溆渐浆颍泼洁潜润浔溃涠涩浇涝涧渑泽滪泶浍浊\
+浓湿泞蒙济涛滥潍滨溅泺滤滢渎泻渖浏泸沥潇潆泷濑潋澜沣滠洒滩灏湾滦滟灾为乌烃无炼炜\
+烟茕焕烦炀荧炝热炽烨灯炖烧烫焖营灿烛烩烬焘烁炉烂争爷尔墙牍牵荦牦犊牺状狭狈狰犹狲\
+犸狱狮奖独狯猃狝狞获猎犷兽獭献猕猡现珐珲玮琐瑶莹玛玱琏玑珰环玺琼珑璎瓯瓮产亩毕画\
+异当畴叠痉痹痖疯疡瘗疮疟瘘疗愈疠瘪疖瘿瘾痈瘫癫发皑疱皱盗盏尽监盘卢眦众睁睑蒙瞩矫\
+硁硖砗砚硕砀确码砖碜碛矶硗础碍矿砺砾矾砻秘禄祸祯祎御禅礼祢秃秆种称谷积颖秾穑秽稳\
+窝洼穷窑窥窜窍窦窃竞笔笋笺筝节范筑简签帘篮筹笼篱粤糁粪粮粝籴粜纠纪纣约红纡纥纨纫\
+纹纳纽纾纯纰纱纸级纷纭纺细绂绁绅绍绀绋绐绌终组绊绗结绝绦绔绞络绚给绒统丝绛绢绑绡\
+绠绨绣绥经综缍绸绶维纲网缀彩纶绺绮绽绰绫绵绲缁紧绯绪缃缄缂线缉缎缔缗缘缌编缓缅纬\
+缑缈练缏缇萦缙缢缒绉缣缊缞缚缜缟缛县缝缡缩纵缧缦缕缥总绩绷缫缪缯织缮缭绕缋绳绘系\
+茧缰缳缲绎继缤缱缬续累缠缨缵缆罂罚罢罗罴羁芈羟义习翘耧圣闻联聪声耸聩聂职聍听聋肃\
+胁脉胫脱胀肾脶脑肿脚肠腘肤胶腻胆脍脓脸脐膑腊胪脔临台与兴举旧舱舰舻艰刍苎兹庄茎苋\
+华苌莱万莴叶荭苇荤搜莳莅苍荪席盖莲苁莼荜卜蒌蒋葱茑荫荨蒇荞荬莸荛蒉荡芜萧蓣荟蓟芗\
+姜蔷莶荐萨荠蓝荩艺药薮苈蔼蔺蕲芦苏蕴苹藓蔹茏兰蓠萝处虚虏号亏虬蛱蜕蚬蚀猬虾蜗蛳蚂\
+萤蝼蛰蝈螨虮蝉蛲虫蛏蚁蝇虿蝎蛴蝾蚝蜡蛎蛊蚕蛮术卫冲衮里补装里制复裤裢褛裥袄裣裆褴\
+袜衬袭见规觅视觇觋觎亲觊觏觐觑觉览觌观觞觯触订讣计讯讧讨讦训讪讫记讹讶讼诀讷访设\
+许诉诃诊诂诋讵诈诒诏评诎诅词咏诩询诣试诗诧诟诡诠诘话该详诜诙诖诔诛诓夸志认诳诶诞\
+诱诮语诚诫诬误诰诵诲说谁课谇诽谊调谄谆谈诿请诤诹诼谅论谂谀谍谝诨谔谛谐谏谕谘讳谙\
+谌讽诸谚谖诺谋谒谓誊诌谎谜谧谑谡谤谦谥讲谢谣谟谪谬讴谨谩证谲讥谮识谯谭谱谵译议谴\
+护誉读变雠谗让谰谶谠谳岂竖丰艳猪猫贝贞负财贡贫货贩贪贯责贮贳赀贰贵贬买贷贶费贴贻\
+贸贺贲赂赁贿赅资贾贼赈赊宾赇赉赐赏赔赓贤卖贱赋质账赌赖赚赙购赛赜贽赘赟赠赞赡赢赆\
+赃赎赝赣赶赵趋趱迹践踊跄跸蹒踪跷跶趸踌跻跃踯跞踬蹰跹蹑蹿躏躯车轧轨军轩轫轭软轷轸\
+轱轴轵轺轲轶轼较辂辁载轾辄辅轻辆辎辉辋辍辊辇辈轮辌辑辏输辐辗辒毂辖辕辘转辙轿辚轰\
+轹轳办辞辩农回迳这连周进运过达违遥逊递远适迟迁选遗辽迈还迩边逻逦郏邮郓乡邹邬郧邓\
+郑邻郸邺郐邝郦腌丑酝医酱酿衅酾酽释钆钇钌钊钉钋针钓钏钒钍钕钠钝钤钣钞钮钧钙钛铌铃\
+铍铀钿钾铂铅钩钼铬银铜铣衔锑铝锌钡锋锄铺锂锯钢录钱锦锡错锰锅镀键钟镁锁钨蓥镇链镜\
+錾锈钟镯镭铁铸鉴鉴钥锣钻銮凿长门闩闪闫闭开闰闲间闵闸阂阁阀闺闽闾阎板闱阔阕阑阗阖\
+阙闯关阚阐辟闼陉陕阵阴陈陆阳队阶陨际随险隐陇隶只虽双雏杂鸡离难云电雾霁雳霭灵靓静\
+腼巩缰鞑鞯韦韧韩韪韬韵响页顶顷项顺须顼颂颀颃预顽颁顿颇领颌颉颏头颊颔颈颓频颗题额\
+颚颜颛愿颡颠类颟颢顾颤颥显颦颅颧风台刮飓飕飘飙飞饥饨饪饬饭饮饲饱饰饺饼饷养饵饿余\
+馆喂馒馈饥饶馋马驭冯驮驰驯驳驻驹驾骀驸驶驼驷骈骇骆骏骋骑骗腾骚骡蓦骠驱骁骄验惊驿\
+骤驴骧骥骊肮髅脏体髌髋发松胡须鬓斗闹哄阋阄郁魉魇鱼鲁鲍鲜鲤鲸鳃鳞鸟凫鸠凤鸣鸦鸳鸯\
+鸭鸿鹅鹏莺鹤鹰鹦卤咸碱盐丽麦麸面么黄点党霉黩鳌鼍齐斋赍齑齿龀龅龇龃龆龄龈啮龊龉龋\
+腭龌龙庞龚龛龟";
+
+static TO_SIMPLIFIED: Lazy<HashMap<char, char>> =
+    Lazy::new(|| TRADITIONAL.chars().zip(SIMPLIFIED.chars()).collect());
+
+/// Whether a character is a CJK ideograph or radical, kana or a hangul syllable.
+pub fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{2E80}'..='\u{2FDF}'
+        | '\u{3040}'..='\u{30FF}'
+        | '\u{3400}'..='\u{4DBF}'
+        | '\u{4E00}'..='\u{9FFF}'
+        | '\u{AC00}'..='\u{D7AF}'
+        | '\u{F900}'..='\u{FAFF}'
+        | '\u{20000}'..='\u{2FA1F}'
+    )
+}
+
+/// Fold a full-width character to its half-width form and a traditional character to its
+/// simplified form.
+pub fn fold_char(c: char) -> char {
+    match c {
+        '\u{3000}' => ' ',
+        '\u{3002}' => '.',
+        '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFEE0).unwrap_or(c),
+        _ => TO_SIMPLIFIED.get(&c).copied().unwrap_or(c),
+    }
+}
+
+/// Lowercase text with full-width and traditional characters folded, see [`fold_char`].
+pub fn normalize(text: &str) -> String {
+    if text.is_ascii() {
+        return text.to_lowercase();
+    }
+    text.chars()
+        .map(fold_char)
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// The full pinyin and the pinyin initials of text, or `None` if it has no character with a
+/// pinyin reading, e.g., only kana or hangul.
+///
+/// Other characters are kept as they are, so `中国科学 2020` gives `zhongguokexue 2020` and
+/// `zgkx 2020`. Heteronyms take their most common reading.
+pub fn to_pinyin(text: &str) -> Option<(String, String)> {
+    if !text.chars().any(is_cjk) {
+        return None;
+    }
+    let mut full = String::with_capacity(text.len() * 2);
+    let mut initials = String::with_capacity(text.len());
+    let mut read = false;
+    for c in text.chars() {
+        match pinyin_of(c) {
+            Some((plain, initial)) => {
+                full.push_str(plain);
+                initials.push_str(initial);
+                read = true;
+            }
+            None => {
+                full.push(c);
+                initials.push(c);
+            }
+        }
+    }
+    read.then_some((full, initials))
+}
+
+/// The full pinyin and the pinyin initial of a character, if it has a reading.
+pub(crate) fn pinyin_of(c: char) -> Option<(&'static str, &'static str)> {
+    c.to_pinyin()
+        .map(|pinyin| (pinyin.plain(), pinyin.first_letter()))
+}
+
+/// Search forms of normalized text with CJK ideographs, as converting to pinyin is slow
+static CJK_SEARCH_FORMS: Lazy<RwLock<HashMap<String, Vec<String>>>> = Lazy::new(Default::default);
+
+/// Most texts kept in [`CJK_SEARCH_FORMS`], which is cleared when full
+const SEARCH_FORMS_CACHE_SIZE: usize = 100_000;
+
+/// Forms of text to search in: the normalized text, followed by its full pinyin and pinyin
+/// initials if it has CJK ideographs.
+///
+/// The forms of text with CJK ideographs are cached.
+pub(crate) fn search_forms(text: &str) -> Vec<String> {
+    let normalized = normalize(text);
+    if !normalized.chars().any(is_cjk) {
+        return vec![normalized];
+    }
+    if let Some(forms) = CJK_SEARCH_FORMS.read().unwrap().get(&normalized) {
+        return forms.clone();
+    }
+    let forms = match to_pinyin(&normalized) {
+        Some((full, initials)) => vec![normalized.clone(), full, initials],
+        None => vec![normalized.clone()],
+    };
+    let mut cache = CJK_SEARCH_FORMS.write().unwrap();
+    if cache.len() >= SEARCH_FORMS_CACHE_SIZE {
+        cache.clear();
+    }
+    cache.insert(normalized, forms.clone());
+    forms
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("ＡＢＣ　１２３"), "abc 123");
+        assert_eq!(normalize("數學學報"), "数学学报");
+        assert_eq!(normalize("Galerkin"), "galerkin");
+    }
+
+    #[test]
+    fn test_pinyin() {
+        assert_eq!(
+            to_pinyin("中国科学 2020"),
+            Some(("zhongguokexue 2020".to_string(), "zgkx 2020".to_string()))
+        );
+        assert_eq!(to_pinyin("Galerkin"), None);
+        assert_eq!(to_pinyin("ガレルキン"), None);
+        assert_eq!(search_forms("ガレルキン"), ["ガレルキン"]);
+        assert_eq!(search_forms("張三"), ["张三", "zhangsan", "zs"]);
+    }
+
+    #[test]
+    fn test_table() {
+        assert_eq!(TRADITIONAL.chars().count(), SIMPLIFIED.chars().count());
+        assert!(
+            TRADITIONAL
+                .chars()
+                .zip(TRADITIONAL.chars().skip(1))
+                .all(|(a, b)| a < b)
+        );
+    }
+}
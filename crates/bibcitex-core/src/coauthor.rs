@@ -0,0 +1,303 @@
+//! The co-authorship network of a library.
+//!
+//! [`CoauthorGraph`] has an author per node, weighted by the number of papers, and an edge per
+//! pair of co-authors, weighted by the number of joint papers. Authors are matched by their
+//! normalized names, see [`normalize_author`]. The graph can be exported as GraphML, DOT or JSON,
+//! e.g., for Gephi.
+use crate::{Filter, Result, bib::Reference, cjk};
+use serde::Serialize;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Write,
+};
+
+/// Normalize an author name to match spellings of the same author.
+///
+/// Braces and periods are dropped, whitespace is collapsed, and the name is lowercased with
+/// full-width and traditional characters folded, so `J. {van} Dyke` matches `j van dyke`.
+pub fn normalize_author(name: &str) -> String {
+    let name = name
+        .chars()
+        .filter(|c| !matches!(c, '{' | '}'))
+        .map(|c| if c == '.' { ' ' } else { c })
+        .collect::<String>();
+    cjk::normalize(&name.split_whitespace().collect::<Vec<_>>().join(" "))
+}
+
+/// Filter of the references and edges of a [`CoauthorGraph`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GraphFilter {
+    /// References in the graph, e.g., [`Filter::years`]
+    pub references: Filter,
+    /// Least number of joint papers of an edge
+    pub min_weight: usize,
+}
+
+/// An author in a [`CoauthorGraph`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AuthorNode {
+    /// Normalized name
+    pub id: String,
+    /// Name as first written
+    pub label: String,
+    /// Number of papers
+    pub papers: usize,
+}
+
+/// A pair of co-authors in a [`CoauthorGraph`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CoauthorEdge {
+    /// Id of the first author, ordered before the target
+    pub source: String,
+    /// Id of the second author
+    pub target: String,
+    /// Number of joint papers
+    pub weight: usize,
+}
+
+/// The co-authorship network of references.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct CoauthorGraph {
+    /// Authors, most papers first
+    pub nodes: Vec<AuthorNode>,
+    /// Pairs of co-authors, most joint papers first
+    pub edges: Vec<CoauthorEdge>,
+}
+
+impl CoauthorGraph {
+    /// Build the graph of the references of a library matching the filter.
+    ///
+    /// `library` is the name of the library, used by [`Filter::Library`]. Edges with fewer joint
+    /// papers than [`GraphFilter::min_weight`] are dropped. If it is more than 1, so are the
+    /// authors left without co-authors.
+    pub fn new(references: &[Reference], filter: &GraphFilter, library: Option<&str>) -> Self {
+        let mut nodes: Vec<AuthorNode> = Vec::new();
+        let mut positions = HashMap::new();
+        let mut weights = HashMap::new();
+        for authors in references
+            .iter()
+            .filter(|r| filter.references.matches(r, library))
+            .filter_map(|r| r.author.as_ref())
+        {
+            let mut ids = Vec::new();
+            for name in authors {
+                let id = normalize_author(name);
+                if id.is_empty() || ids.contains(&id) {
+                    continue;
+                }
+                let position = *positions.entry(id.clone()).or_insert_with(|| {
+                    nodes.push(AuthorNode {
+                        id: id.clone(),
+                        label: name.split_whitespace().collect::<Vec<_>>().join(" "),
+                        papers: 0,
+                    });
+                    nodes.len() - 1
+                });
+                nodes[position].papers += 1;
+                ids.push(id);
+            }
+            ids.sort();
+            for (i, source) in ids.iter().enumerate() {
+                for target in &ids[i + 1..] {
+                    *weights.entry((source.clone(), target.clone())).or_insert(0) += 1;
+                }
+            }
+        }
+
+        let mut edges = weights
+            .into_iter()
+            .filter(|(_, weight)| *weight >= filter.min_weight)
+            .map(|((source, target), weight)| CoauthorEdge {
+                source,
+                target,
+                weight,
+            })
+            .collect::<Vec<_>>();
+        edges.sort_by(|a, b| {
+            b.weight
+                .cmp(&a.weight)
+                .then_with(|| (&a.source, &a.target).cmp(&(&b.source, &b.target)))
+        });
+        if filter.min_weight > 1 {
+            let linked = edges
+                .iter()
+                .flat_map(|edge| [edge.source.as_str(), edge.target.as_str()])
+                .collect::<HashSet<_>>();
+            nodes.retain(|node| linked.contains(node.id.as_str()));
+        }
+        nodes.sort_by(|a, b| b.papers.cmp(&a.papers).then_with(|| a.id.cmp(&b.id)));
+        Self { nodes, edges }
+    }
+
+    /// Export as GraphML, with the `label` and `papers` of nodes and the `weight` of edges.
+    pub fn to_graphml(&self) -> String {
+        let mut xml = String::from(concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+            "  <key id=\"label\" for=\"node\" attr.name=\"label\" attr.type=\"string\"/>\n",
+            "  <key id=\"papers\" for=\"node\" attr.name=\"papers\" attr.type=\"int\"/>\n",
+            "  <key id=\"weight\" for=\"edge\" attr.name=\"weight\" attr.type=\"double\"/>\n",
+            "  <graph id=\"coauthors\" edgedefault=\"undirected\">\n",
+        ));
+        for node in &self.nodes {
+            writeln!(
+                xml,
+                "    <node id=\"{}\"><data key=\"label\">{}</data><data key=\"papers\">{}</data></node>",
+                xml_escape(&node.id),
+                xml_escape(&node.label),
+                node.papers
+            )
+            .unwrap();
+        }
+        for edge in &self.edges {
+            writeln!(
+                xml,
+                "    <edge source=\"{}\" target=\"{}\"><data key=\"weight\">{}</data></edge>",
+                xml_escape(&edge.source),
+                xml_escape(&edge.target),
+                edge.weight
+            )
+            .unwrap();
+        }
+        xml.push_str("  </graph>\n</graphml>\n");
+        xml
+    }
+
+    /// Export as an undirected DOT graph, with the `papers` of nodes and the `weight` of edges.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("graph coauthors {\n");
+        for node in &self.nodes {
+            writeln!(
+                dot,
+                "  \"{}\" [label=\"{}\", papers={}];",
+                dot_escape(&node.id),
+                dot_escape(&node.label),
+                node.papers
+            )
+            .unwrap();
+        }
+        for edge in &self.edges {
+            writeln!(
+                dot,
+                "  \"{}\" -- \"{}\" [weight={}];",
+                dot_escape(&edge.source),
+                dot_escape(&edge.target),
+                edge.weight
+            )
+            .unwrap();
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Export as JSON with `nodes` and `edges` arrays.
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn dot_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::read_bibliography;
+    use biblatex::Bibliography;
+
+    const SRC: &str = r#"
+@article{a,
+  author = {Smith, John and Doe, Jane},
+  title = {First},
+  year = {2010},
+}
+@article{b,
+  author = {Doe, Jane and Smith, J{ohn} and Roe, Richard},
+  title = {Second},
+  year = {2012},
+}
+@book{c,
+  author = {O'Neil, Ann},
+  title = {Alone},
+  year = {2015},
+}
+"#;
+
+    fn refs() -> Vec<Reference> {
+        read_bibliography(Bibliography::parse(SRC).unwrap())
+    }
+
+    #[test]
+    fn test_graph() {
+        let graph = CoauthorGraph::new(&refs(), &GraphFilter::default(), None);
+        assert_eq!(graph.nodes.len(), 4);
+        assert_eq!(graph.nodes[0].id, "jane doe");
+        assert_eq!(graph.nodes[1].papers, 2);
+        assert_eq!(graph.edges.len(), 3);
+        assert_eq!(
+            graph.edges[0],
+            CoauthorEdge {
+                source: "jane doe".to_string(),
+                target: "john smith".to_string(),
+                weight: 2,
+            }
+        );
+
+        let filter = GraphFilter {
+            min_weight: 2,
+            ..Default::default()
+        };
+        let graph = CoauthorGraph::new(&refs(), &filter, None);
+        assert_eq!(graph.nodes.len(), 2);
+        assert_eq!(graph.edges.len(), 1);
+
+        let filter = GraphFilter {
+            references: Filter::years(Some(2011), Some(2014)),
+            ..Default::default()
+        };
+        let graph = CoauthorGraph::new(&refs(), &filter, None);
+        assert_eq!(graph.nodes.len(), 3);
+        assert!(graph.nodes.iter().all(|node| node.papers == 1));
+
+        let filter = GraphFilter {
+            references: Filter::Library("papers".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            CoauthorGraph::new(&refs(), &filter, Some("papers"))
+                .nodes
+                .len(),
+            4
+        );
+        assert!(
+            CoauthorGraph::new(&refs(), &filter, Some("thesis"))
+                .nodes
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_export() {
+        let graph = CoauthorGraph::new(&refs(), &GraphFilter::default(), None);
+        let graphml = graph.to_graphml();
+        assert!(graphml.contains("<node id=\"ann o&apos;neil\"><data key=\"label\">Ann O&apos;Neil</data><data key=\"papers\">1</data></node>"));
+        assert!(graphml.contains(
+            "<edge source=\"jane doe\" target=\"john smith\"><data key=\"weight\">2</data></edge>"
+        ));
+        let dot = graph.to_dot();
+        assert!(dot.starts_with("graph coauthors {\n"));
+        assert!(dot.contains("  \"jane doe\" -- \"john smith\" [weight=2];\n"));
+        let json: serde_json::Value = serde_json::from_str(&graph.to_json().unwrap()).unwrap();
+        assert_eq!(json["nodes"].as_array().unwrap().len(), 4);
+        assert_eq!(json["edges"][0]["weight"], 2);
+    }
+}
@@ -0,0 +1,307 @@
+use crate::{Error, Result, bib::Reference};
+use fs_err as fs;
+use hayagriva::{
+    BibliographyDriver, BibliographyRequest, BufWriteFormat, CitationItem, CitationRequest,
+    ElemChild, ElemChildren, Entry, Formatted,
+    archive::{ArchivedStyle, locales},
+    citationberg::{FontStyle, FontWeight, IndependentStyle, Locale, Style},
+    io::from_biblatex_str,
+};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::{fmt::Write, path::Path};
+
+/// CSL locale files used to look up terms missing in a style.
+static LOCALES: Lazy<Vec<Locale>> = Lazy::new(locales);
+
+/// AMS style, not shipped with the `hayagriva` archive.
+static AMS_STYLE: &str = include_str!("../styles/american-mathematical-society.csl");
+
+/// CSL styles bundled with BibCiTeX
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum BuiltinStyle {
+    /// American Psychological Association 7th edition
+    Apa,
+    /// IEEE
+    Ieee,
+    /// Chicago Manual of Style (author-date)
+    ChicagoAuthorDate,
+    /// American Mathematical Society
+    Ams,
+    /// Nature
+    Nature,
+}
+
+impl BuiltinStyle {
+    /// All bundled styles.
+    pub fn all() -> &'static [Self] {
+        &[
+            Self::Apa,
+            Self::Ieee,
+            Self::ChicagoAuthorDate,
+            Self::Ams,
+            Self::Nature,
+        ]
+    }
+
+    /// Load the bundled CSL style.
+    pub fn load(self) -> IndependentStyle {
+        let archived = match self {
+            Self::Apa => ArchivedStyle::AmericanPsychologicalAssociation,
+            Self::Ieee => ArchivedStyle::InstituteOfElectricalAndElectronicsEngineers,
+            Self::ChicagoAuthorDate => ArchivedStyle::ChicagoAuthorDate,
+            Self::Nature => ArchivedStyle::Nature,
+            Self::Ams => {
+                return IndependentStyle::from_xml(AMS_STYLE).expect("bundled AMS style is valid");
+            }
+        };
+        match archived.get() {
+            Style::Independent(style) => style,
+            Style::Dependent(_) => unreachable!("bundled styles are independent"),
+        }
+    }
+}
+
+impl std::fmt::Display for BuiltinStyle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BuiltinStyle::Apa => write!(f, "APA"),
+            BuiltinStyle::Ieee => write!(f, "IEEE"),
+            BuiltinStyle::ChicagoAuthorDate => write!(f, "Chicago"),
+            BuiltinStyle::Ams => write!(f, "AMS"),
+            BuiltinStyle::Nature => write!(f, "Nature"),
+        }
+    }
+}
+
+/// Output format of formatted references
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum OutputFormat {
+    /// Plain text without any markup
+    #[default]
+    Text,
+    /// HTML with inline styles
+    Html,
+    /// Markdown, using `*` for italic and `**` for bold
+    Markdown,
+}
+
+impl std::fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OutputFormat::Text => write!(f, "Text"),
+            OutputFormat::Html => write!(f, "HTML"),
+            OutputFormat::Markdown => write!(f, "Markdown"),
+        }
+    }
+}
+
+/// A loaded CSL style
+#[derive(Debug, Clone, PartialEq)]
+pub struct CslStyle {
+    name: String,
+    style: IndependentStyle,
+}
+
+impl CslStyle {
+    /// Load a bundled style.
+    pub fn builtin(style: BuiltinStyle) -> Self {
+        Self {
+            name: style.to_string(),
+            style: style.load(),
+        }
+    }
+
+    /// Parse an independent CSL style from XML.
+    pub fn from_xml(xml: &str) -> Result<Self> {
+        let style = IndependentStyle::from_xml(xml).map_err(|e| Error::CSLError(e.to_string()))?;
+        Ok(Self {
+            name: style.info.title.value.clone(),
+            style,
+        })
+    }
+
+    /// Load an independent CSL style from a `.csl` file.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let xml = fs::read_to_string(path)?;
+        Self::from_xml(&xml)
+    }
+
+    /// Title of the style
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Format references as a bibliography with the given CSL style.
+///
+/// The results are in the order of the rendered bibliography, which follows the sorting rules of the style.
+pub fn format_references(
+    references: &[Reference],
+    style: &CslStyle,
+    format: OutputFormat,
+) -> Result<Vec<String>> {
+    let entries = references
+        .iter()
+        .map(to_entry)
+        .collect::<Result<Vec<_>>>()?;
+    let mut driver = BibliographyDriver::new();
+    for entry in &entries {
+        driver.citation(CitationRequest::from_items(
+            vec![CitationItem::with_entry(entry)],
+            &style.style,
+            &LOCALES,
+        ));
+    }
+    let rendered = driver.finish(BibliographyRequest::new(&style.style, None, &LOCALES));
+    let bibliography = rendered.bibliography.ok_or(Error::CSLError(format!(
+        "Style {} has no bibliography",
+        style.name
+    )))?;
+    bibliography
+        .items
+        .into_iter()
+        .map(|item| {
+            let mut buf = String::new();
+            if let Some(first_field) = item.first_field {
+                write_child(&mut buf, &first_field, format)?;
+                buf.push(' ');
+            }
+            write_children(&mut buf, &item.content, format)?;
+            Ok(buf)
+        })
+        .collect()
+}
+
+/// Format a single reference with the given CSL style.
+pub fn format_reference(
+    reference: &Reference,
+    style: &CslStyle,
+    format: OutputFormat,
+) -> Result<String> {
+    format_references(std::slice::from_ref(reference), style, format)
+        .map(|mut items| items.pop().unwrap_or_default())
+}
+
+/// Convert a reference to a `hayagriva` entry through its BibTeX source.
+fn to_entry(reference: &Reference) -> Result<Entry> {
+    let key = &reference.cite_key;
+    let library = from_biblatex_str(&reference.source).map_err(|errors| {
+        let reasons = errors
+            .iter()
+            .map(|e| e.to_string())
+            .collect::<Vec<_>>()
+            .join("; ");
+        Error::CSLError(format!("Cannot convert {key}: {reasons}"))
+    })?;
+    library.get(key).cloned().ok_or_else(|| {
+        Error::CSLError(format!(
+            "Cannot convert {key}: no entry with this key in its source"
+        ))
+    })
+}
+
+fn write_children(buf: &mut String, children: &ElemChildren, format: OutputFormat) -> Result<()> {
+    for child in &children.0 {
+        write_child(buf, child, format)?;
+    }
+    Ok(())
+}
+
+fn write_child(buf: &mut String, child: &ElemChild, format: OutputFormat) -> Result<()> {
+    let result = match format {
+        OutputFormat::Text => child.write_buf(buf, BufWriteFormat::Plain),
+        OutputFormat::Html => child.write_buf(buf, BufWriteFormat::Html),
+        OutputFormat::Markdown => {
+            match child {
+                ElemChild::Text(text) => write_markdown(buf, text),
+                ElemChild::Elem(elem) => write_children(buf, &elem.children, format)?,
+                ElemChild::Markup(markup) => buf.push_str(markup),
+                ElemChild::Link { text, url } => {
+                    buf.push('[');
+                    write_markdown(buf, text);
+                    let _ = write!(buf, "]({url})");
+                }
+                ElemChild::Transparent { .. } => {}
+            }
+            Ok(())
+        }
+    };
+    result.map_err(|e| Error::CSLError(e.to_string()))
+}
+
+fn write_markdown(buf: &mut String, text: &Formatted) {
+    let marker = match (
+        text.formatting.font_style == FontStyle::Italic,
+        text.formatting.font_weight == FontWeight::Bold,
+    ) {
+        (true, true) => "***",
+        (true, false) => "*",
+        (false, true) => "**",
+        (false, false) => "",
+    };
+    // Markdown emphasis must not start or end with whitespace.
+    let trimmed = text.text.trim();
+    if marker.is_empty() || trimmed.is_empty() {
+        buf.push_str(&text.text);
+        return;
+    }
+    let start = text.text.len() - text.text.trim_start().len();
+    let end = start + trimmed.len();
+    buf.push_str(&text.text[..start]);
+    buf.push_str(marker);
+    buf.push_str(trimmed);
+    buf.push_str(marker);
+    buf.push_str(&text.text[end..]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::read_bibliography;
+    use biblatex::Bibliography;
+
+    const SRC: &str = r#"
+@article{knuth1984,
+    author = {Donald E. Knuth},
+    title = {Literate Programming},
+    journal = {The Computer Journal},
+    volume = {27},
+    number = {2},
+    pages = {97--111},
+    year = {1984},
+}
+"#;
+
+    fn references() -> Vec<Reference> {
+        read_bibliography(Bibliography::parse(SRC).unwrap())
+    }
+
+    #[test]
+    fn test_builtin_styles() {
+        let refs = references();
+        for &builtin in BuiltinStyle::all() {
+            let style = CslStyle::builtin(builtin);
+            let text = format_reference(&refs[0], &style, OutputFormat::Text).unwrap();
+            assert!(text.contains("Knuth"), "{builtin}: {text}");
+            assert!(text.contains("1984"), "{builtin}: {text}");
+        }
+    }
+
+    #[test]
+    fn test_output_formats() {
+        let refs = references();
+        let style = CslStyle::builtin(BuiltinStyle::Apa);
+        let html = format_reference(&refs[0], &style, OutputFormat::Html).unwrap();
+        assert!(html.contains("<i>") || html.contains("font-style: italic"));
+        let markdown = format_reference(&refs[0], &style, OutputFormat::Markdown).unwrap();
+        assert!(markdown.contains("*The Computer Journal"));
+    }
+
+    #[test]
+    fn test_custom_style() {
+        let style = CslStyle::from_xml(AMS_STYLE).unwrap();
+        assert_eq!(style.name(), "American Mathematical Society");
+        assert!(CslStyle::from_xml("<style/>").is_err());
+    }
+}
@@ -0,0 +1,453 @@
+//! Edit fields of entries in the source of a `.bib` file.
+//!
+//! Edits are made on the text, so comments, `@string` macros and the layout of untouched
+//! entries are kept as they are.
+use crate::{BibSource, Encoding, Result};
+use std::{ops::Range, path::Path};
+
+/// An entry in the source of a `.bib` file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceEntry {
+    /// Entry type as written, e.g., `article`
+    pub entry_type: String,
+    /// Cite key
+    pub key: String,
+    /// Byte range of the entry, from `@` to the closing delimiter
+    pub range: Range<usize>,
+    /// Fields in source order
+    pub fields: Vec<SourceField>,
+}
+
+/// A field of an entry in the source of a `.bib` file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceField {
+    /// Field name as written
+    pub name: String,
+    /// Byte range from the name to the end of the value
+    pub range: Range<usize>,
+    /// Byte range of the value, including its braces or quotes
+    pub value: Range<usize>,
+}
+
+impl SourceField {
+    /// The value without its outer braces or quotes.
+    pub fn text<'a>(&self, source: &'a str) -> &'a str {
+        let value = &source[self.value.clone()];
+        if value.len() >= 2
+            && ((value.starts_with('{') && value.ends_with('}'))
+                || (value.starts_with('"') && value.ends_with('"')))
+        {
+            &value[1..value.len() - 1]
+        } else {
+            value
+        }
+    }
+}
+
+impl SourceEntry {
+    /// Find a field by name, ignoring case.
+    pub fn field(&self, name: &str) -> Option<&SourceField> {
+        self.fields
+            .iter()
+            .find(|field| field.name.eq_ignore_ascii_case(name))
+    }
+}
+
+/// Entries of a `.bib` source, skipping `@comment`, `@string` and `@preamble`.
+pub fn source_entries(source: &str) -> Vec<SourceEntry> {
+    let bytes = source.as_bytes();
+    blocks(source)
+        .into_iter()
+        .filter(|(entry_type, ..)| {
+            !["comment", "string", "preamble"]
+                .iter()
+                .any(|t| t.eq_ignore_ascii_case(entry_type))
+        })
+        .map(|(entry_type, range, open)| {
+            let end = range.end - 1;
+            let key_start = skip_whitespace(bytes, open + 1);
+            let key_end = scan(bytes, key_start, |b| b != b',' && b != bytes[end]);
+            SourceEntry {
+                entry_type: entry_type.to_string(),
+                key: source[key_start..key_end].trim().to_string(),
+                range,
+                fields: fields(source, key_end, end),
+            }
+        })
+        .collect()
+}
+
+/// `@string` macros and `@preamble`s of a `.bib` source, with the entry type in lowercase.
+///
+/// The field of a `@string` is the macro, a `@preamble` has no fields.
+pub fn source_macros(source: &str) -> Vec<SourceEntry> {
+    blocks(source)
+        .into_iter()
+        .filter_map(|(entry_type, range, open)| {
+            let entry_type = entry_type.to_ascii_lowercase();
+            let fields = match entry_type.as_str() {
+                "string" => fields(source, open + 1, range.end - 1),
+                "preamble" => Vec::new(),
+                _ => return None,
+            };
+            Some(SourceEntry {
+                entry_type,
+                key: String::new(),
+                range,
+                fields,
+            })
+        })
+        .collect()
+}
+
+/// The contents of the `@comment` blocks of a `.bib` source, without their delimiters.
+pub fn source_comments(source: &str) -> Vec<&str> {
+    blocks(source)
+        .into_iter()
+        .filter(|(entry_type, ..)| entry_type.eq_ignore_ascii_case("comment"))
+        .map(|(_, range, open)| &source[open + 1..range.end - 1])
+        .collect()
+}
+
+/// Blocks starting with `@type{` or `@type(`, with their type, range and opening delimiter.
+fn blocks(source: &str) -> Vec<(&str, Range<usize>, usize)> {
+    let bytes = source.as_bytes();
+    let mut blocks = Vec::new();
+    let mut pos = 0;
+    while let Some(offset) = source[pos..].find('@') {
+        let start = pos + offset;
+        pos = start + 1;
+        let type_end = scan(bytes, pos, |b| b.is_ascii_alphanumeric() || b == b'_');
+        let entry_type = &source[pos..type_end];
+        let open = skip_whitespace(bytes, type_end);
+        if entry_type.is_empty() || !matches!(bytes.get(open), Some(b'{' | b'(')) {
+            continue;
+        }
+        let Some(end) = closing(bytes, open) else {
+            break;
+        };
+        pos = end + 1;
+        blocks.push((entry_type, start..end + 1, open));
+    }
+    blocks
+}
+
+/// Find an entry by cite key.
+pub fn find_entry(source: &str, key: &str) -> Option<SourceEntry> {
+    source_entries(source)
+        .into_iter()
+        .find(|entry| entry.key == key)
+}
+
+/// Set a field of an entry to a braced value, or remove it with `None`.
+///
+/// A new field is added as the first field, indented like the existing ones. Returns `None` if
+/// the entry is not found.
+pub fn set_field(source: &str, key: &str, name: &str, value: Option<&str>) -> Option<String> {
+    let entry = find_entry(source, key)?;
+    let replacement = field_replacement(source, &entry, name, value);
+    Some(apply_replacements(source, replacement))
+}
+
+/// Rename the cite key of an entry.
+///
+/// `crossref` fields pointing to the entry are updated as well. Returns `None` if the entry is
+/// not found, or the new key is invalid or already taken.
+pub fn rename_cite_key(source: &str, old: &str, new: &str) -> Option<String> {
+    if new.is_empty() || new.contains(|c: char| c.is_whitespace() || matches!(c, ',' | '{' | '}')) {
+        return None;
+    }
+    let entries = source_entries(source);
+    if entries.iter().any(|entry| entry.key == new) {
+        return None;
+    }
+    let entry = entries.iter().find(|entry| entry.key == old)?;
+    let open = entry.range.start + source[entry.range.clone()].find(['{', '('])? + 1;
+    let key_start = open + source[open..].find(old)?;
+    let crossrefs = entries
+        .iter()
+        .filter_map(|entry| entry.field("crossref"))
+        .filter(|field| field.text(source).trim() == old)
+        .map(|field| (field.value.clone(), format!("{{{new}}}")));
+    let key = (key_start..key_start + old.len(), new.to_string());
+    Some(apply_replacements(source, crossrefs.chain([key])))
+}
+
+/// Rename the cite key of an entry in a `.bib` file, see [`rename_cite_key`].
+///
+/// Annotations and usage records are kept by cite key outside the file, so callers move them
+/// along with [`crate::NoteStore::rename_key`] and [`crate::UsageLog::rename_key`]. Returns
+/// whether the entry was renamed.
+pub fn rename_cite_key_in_file(
+    path: impl AsRef<Path>,
+    encoding: Option<&'static Encoding>,
+    old: &str,
+    new: &str,
+) -> Result<bool> {
+    let path = path.as_ref();
+    let source = BibSource::read(path, encoding)?;
+    let Some(output) = rename_cite_key(&source.text, old, new) else {
+        return Ok(false);
+    };
+    source.write(path, &output)?;
+    Ok(true)
+}
+
+/// The replacement of a byte range of the source setting a field of an entry, see
+/// [`set_field`], or `None` if there is nothing to change.
+///
+/// Replacements of different entries can be collected from a single parse of the source and
+/// applied together with [`apply_replacements`].
+pub fn field_replacement(
+    source: &str,
+    entry: &SourceEntry,
+    name: &str,
+    value: Option<&str>,
+) -> Option<(Range<usize>, String)> {
+    match (entry.field(name), value) {
+        (Some(field), Some(value)) => Some((field.value.clone(), format!("{{{value}}}"))),
+        (Some(field), None) => Some((removal(source, field), String::new())),
+        (None, Some(value)) => {
+            let bytes = source.as_bytes();
+            let after_key = entry
+                .fields
+                .first()
+                .map_or(entry.range.end - 1, |field| field.range.start);
+            let comma = source[entry.range.start..after_key]
+                .rfind(',')
+                .map(|offset| entry.range.start + offset);
+            let indent = entry.fields.first().map_or("  ", |field| {
+                let line_start = source[..field.range.start].rfind('\n').map_or(0, |i| i + 1);
+                let indent = &source[line_start..field.range.start];
+                if indent.trim().is_empty() {
+                    indent
+                } else {
+                    "  "
+                }
+            });
+            Some(match comma {
+                Some(comma) => (
+                    comma + 1..comma + 1,
+                    format!("\n{indent}{name} = {{{value}}},"),
+                ),
+                // `@article{key}` without fields
+                None => {
+                    let close = scan_back(bytes, entry.range.end - 1);
+                    (close..close, format!(",\n{indent}{name} = {{{value}}}\n"))
+                }
+            })
+        }
+        (None, None) => None,
+    }
+}
+
+/// Replace non-overlapping byte ranges of the source.
+///
+/// The replacements are applied from the end, so that the ranges of earlier ones stay valid.
+pub fn apply_replacements(
+    source: &str,
+    replacements: impl IntoIterator<Item = (Range<usize>, String)>,
+) -> String {
+    let mut replacements = replacements.into_iter().collect::<Vec<_>>();
+    replacements.sort_by_key(|(range, _)| std::cmp::Reverse(range.start));
+    let mut output = source.to_string();
+    for (range, text) in replacements {
+        output.replace_range(range, &text);
+    }
+    output
+}
+
+/// The range removed with a field: its line if it is alone on it, and its trailing comma.
+fn removal(source: &str, field: &SourceField) -> Range<usize> {
+    let bytes = source.as_bytes();
+    let mut end = skip_whitespace_inline(bytes, field.value.end);
+    if bytes.get(end) == Some(&b',') {
+        end += 1;
+    }
+    let line_start = source[..field.range.start].rfind('\n').map_or(0, |i| i + 1);
+    let mut rest = skip_whitespace_inline(bytes, end);
+    if !source[line_start..field.range.start].trim().is_empty() {
+        return field.range.start..end;
+    }
+    if bytes.get(rest) == Some(&b'\r') {
+        rest += 1;
+    }
+    match bytes.get(rest) {
+        Some(b'\n') => line_start..rest + 1,
+        None => line_start..rest,
+        _ => field.range.start..end,
+    }
+}
+
+/// Fields between the key and the closing delimiter at `end`.
+fn fields(source: &str, mut pos: usize, end: usize) -> Vec<SourceField> {
+    let bytes = source.as_bytes();
+    let mut fields = Vec::new();
+    loop {
+        pos = scan(bytes, pos, |b| b == b',' || b.is_ascii_whitespace());
+        if pos >= end {
+            break;
+        }
+        let name_start = pos;
+        pos = scan(bytes, pos, |b| {
+            !b.is_ascii_whitespace() && !matches!(b, b'=' | b',' | b'{' | b'}' | b'"')
+        });
+        let name = &source[name_start..pos];
+        pos = skip_whitespace(bytes, pos);
+        if name.is_empty() || bytes.get(pos) != Some(&b'=') {
+            // Malformed field, skip to the next comma
+            pos = scan(bytes, pos + 1, |b| b != b',').max(pos + 1);
+            continue;
+        }
+        let value_start = skip_whitespace(bytes, pos + 1);
+        pos = value_start;
+        loop {
+            pos = match bytes.get(pos) {
+                Some(b'{') => closing(bytes, pos).map_or(end, |close| close + 1),
+                Some(b'"') => quoted(bytes, pos).map_or(end, |close| close + 1),
+                _ => scan(bytes, pos, |b| {
+                    !b.is_ascii_whitespace() && !matches!(b, b',' | b'#' | b'}' | b')')
+                }),
+            }
+            .min(end);
+            let next = skip_whitespace(bytes, pos);
+            if bytes.get(next) == Some(&b'#') {
+                pos = skip_whitespace(bytes, next + 1);
+            } else {
+                break;
+            }
+        }
+        fields.push(SourceField {
+            name: name.to_string(),
+            range: name_start..pos,
+            value: value_start..pos,
+        });
+    }
+    fields
+}
+
+fn scan(bytes: &[u8], mut pos: usize, accept: impl Fn(u8) -> bool) -> usize {
+    while pos < bytes.len() && accept(bytes[pos]) {
+        pos += 1;
+    }
+    pos
+}
+
+fn scan_back(bytes: &[u8], mut pos: usize) -> usize {
+    while pos > 0 && bytes[pos - 1].is_ascii_whitespace() {
+        pos -= 1;
+    }
+    pos
+}
+
+fn skip_whitespace(bytes: &[u8], pos: usize) -> usize {
+    scan(bytes, pos, |b| b.is_ascii_whitespace())
+}
+
+fn skip_whitespace_inline(bytes: &[u8], pos: usize) -> usize {
+    scan(bytes, pos, |b| b == b' ' || b == b'\t')
+}
+
+/// The matching closing delimiter of the `{` or `(` at `open`, skipping nested braces.
+fn closing(bytes: &[u8], open: usize) -> Option<usize> {
+    let close = if bytes[open] == b'(' { b')' } else { b'}' };
+    let mut depth = 0usize;
+    for (i, &b) in bytes.iter().enumerate().skip(open + 1) {
+        match b {
+            b'{' => depth += 1,
+            b'}' if depth > 0 => depth -= 1,
+            _ if b == close && depth == 0 => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// The closing quote of the `"` at `open`, skipping quotes in braces.
+fn quoted(bytes: &[u8], open: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    for (i, &b) in bytes.iter().enumerate().skip(open + 1) {
+        match b {
+            b'{' => depth += 1,
+            b'}' => depth = depth.saturating_sub(1),
+            b'"' if depth == 0 => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SRC: &str = r#"@string{siam = "SIAM"}
+% a comment
+@article{smith2010,
+  author = {John {Smith}},
+  title = "Discontinuous {Galerkin} Methods",
+  journal = siam # " Review",
+  year = 2010
+}
+
+@book{knuth1984,
+    title = {The TeXbook},
+}
+"#;
+
+    #[test]
+    fn test_source_entries() {
+        let entries = source_entries(SRC);
+        assert_eq!(entries.len(), 2);
+        let smith = &entries[0];
+        assert_eq!(smith.key, "smith2010");
+        let names = smith
+            .fields
+            .iter()
+            .map(|f| f.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["author", "title", "journal", "year"]);
+        assert_eq!(smith.field("AUTHOR").unwrap().text(SRC), "John {Smith}");
+        assert_eq!(
+            smith.field("title").unwrap().text(SRC),
+            "Discontinuous {Galerkin} Methods"
+        );
+        assert_eq!(
+            smith.field("journal").unwrap().text(SRC),
+            r#"siam # " Review""#
+        );
+        assert_eq!(smith.field("year").unwrap().text(SRC), "2010");
+        assert!(SRC[entries[1].range.clone()].ends_with("},\n}"));
+        let macros = source_macros(SRC);
+        assert_eq!(macros.len(), 1);
+        assert_eq!(macros[0].fields[0].name, "siam");
+        assert_eq!(macros[0].fields[0].text(SRC), "SIAM");
+    }
+
+    #[test]
+    fn test_set_field() {
+        let updated = set_field(SRC, "smith2010", "title", Some("New")).unwrap();
+        assert!(updated.contains("  title = {New},\n"));
+        let added = set_field(SRC, "knuth1984", "keywords", Some("TeX")).unwrap();
+        assert!(added.contains("@book{knuth1984,\n    keywords = {TeX},\n    title"));
+        let removed = set_field(SRC, "smith2010", "journal", None).unwrap();
+        assert!(removed.contains("Methods\",\n  year = 2010\n}"));
+        let removed = set_field(SRC, "smith2010", "year", None).unwrap();
+        assert!(removed.contains("\" Review\",\n}"));
+        assert_eq!(set_field(SRC, "smith2010", "note", None).unwrap(), SRC);
+        assert!(set_field(SRC, "missing", "note", Some("x")).is_none());
+        let empty = set_field("@misc{key}", "key", "note", Some("x")).unwrap();
+        assert_eq!(empty, "@misc{key,\n  note = {x}\n}");
+    }
+
+    #[test]
+    fn test_rename_cite_key() {
+        let src = "@inproceedings{smith2010,\n  crossref = {proc2010},\n}\n\n@proceedings{proc2010,\n  year = {2010},\n}\n";
+        let renamed = rename_cite_key(src, "proc2010", "icm2010").unwrap();
+        assert!(renamed.contains("crossref = {icm2010},"));
+        assert!(renamed.contains("@proceedings{icm2010,"));
+        assert!(rename_cite_key(src, "proc2010", "smith2010").is_none());
+        assert!(rename_cite_key(src, "missing", "x").is_none());
+        assert!(rename_cite_key(src, "proc2010", "a b").is_none());
+    }
+}
@@ -0,0 +1,216 @@
+//! Read and write `.bib` files in encodings other than UTF-8.
+//!
+//! Older files are often written in Latin-1 or GBK. [`BibSource::read`] decodes a file to UTF-8,
+//! detecting its encoding from, in order, a byte order mark, a JabRef `% Encoding:` header, valid
+//! UTF-8, and a heuristic choosing between GBK and Windows-1252 (a superset of Latin-1).
+//! [`BibSource::write`] writes edited text back in the original encoding.
+use crate::{Error, Result};
+use encoding_rs::{GBK, UTF_8, UTF_16BE, UTF_16LE, WINDOWS_1252};
+use fs_err as fs;
+use regex::bytes::Regex;
+use std::{path::Path, sync::LazyLock};
+
+pub use encoding_rs::Encoding;
+
+static JABREF_ENCODING: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?m)^%\s*Encoding:\s*([\w.:-]+)").unwrap());
+
+/// Number of bytes searched for a JabRef `% Encoding:` header.
+const HEADER_LENGTH: usize = 1024;
+
+/// Find an encoding by a label such as `latin1`, `gbk` or `utf-8`, ignoring case.
+pub fn encoding_for_label(label: &str) -> Result<&'static Encoding> {
+    Encoding::for_label(label.trim().as_bytes())
+        .ok_or_else(|| Error::EncodingError(format!("Unknown encoding {label}")))
+}
+
+/// Detect the encoding of the bytes of a file without a byte order mark.
+pub fn detect_encoding(bytes: &[u8]) -> &'static Encoding {
+    let header = &bytes[..bytes.len().min(HEADER_LENGTH)];
+    if let Some(encoding) = JABREF_ENCODING
+        .captures(header)
+        .and_then(|captures| Encoding::for_label(&captures[1]))
+    {
+        return encoding;
+    }
+    if std::str::from_utf8(bytes).is_ok() {
+        UTF_8
+    } else if looks_like_gbk(bytes) {
+        GBK
+    } else {
+        WINDOWS_1252
+    }
+}
+
+/// Whether all non-ASCII bytes form GBK characters, at least half of them in the GB2312 range
+/// of common Chinese characters.
+///
+/// Accented Latin-1 letters are mostly followed by ASCII letters, which are not GB2312 trail
+/// bytes.
+fn looks_like_gbk(bytes: &[u8]) -> bool {
+    let (mut chars, mut gb2312) = (0, 0);
+    let mut i = 0;
+    while i < bytes.len() {
+        let lead = bytes[i];
+        if lead < 0x80 {
+            i += 1;
+            continue;
+        }
+        match bytes.get(i + 1) {
+            Some(&trail) if (0x81..=0xFE).contains(&lead) && (0x40..=0xFE).contains(&trail) => {
+                if trail == 0x7F {
+                    return false;
+                }
+                chars += 1;
+                if (0xA1..=0xF7).contains(&lead) && trail >= 0xA1 {
+                    gb2312 += 1;
+                }
+                i += 2;
+            }
+            _ => return false,
+        }
+    }
+    chars > 0 && gb2312 * 2 >= chars
+}
+
+/// The text of a `.bib` file decoded to UTF-8, with its original encoding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BibSource {
+    pub text: String,
+    pub encoding: &'static Encoding,
+    /// Whether the file starts with a byte order mark
+    pub bom: bool,
+}
+
+impl BibSource {
+    /// Decode the bytes of a file.
+    ///
+    /// A byte order mark takes precedence over the given encoding, which takes precedence over
+    /// detection. Bytes invalid in the encoding are an error rather than replaced, so that
+    /// writing the text back does not corrupt the file.
+    pub fn decode(bytes: &[u8], encoding: Option<&'static Encoding>) -> Result<Self> {
+        let (encoding, bom, bytes) = match Encoding::for_bom(bytes) {
+            Some((encoding, length)) => (encoding, true, &bytes[length..]),
+            None => (
+                encoding.unwrap_or_else(|| detect_encoding(bytes)),
+                false,
+                bytes,
+            ),
+        };
+        let text = encoding
+            .decode_without_bom_handling_and_without_replacement(bytes)
+            .ok_or_else(|| Error::EncodingError(format!("File is not valid {}", encoding.name())))?
+            .into_owned();
+        Ok(Self {
+            text,
+            encoding,
+            bom,
+        })
+    }
+
+    /// Read and decode a file, see [`Self::decode`].
+    pub fn read(path: impl AsRef<Path>, encoding: Option<&'static Encoding>) -> Result<Self> {
+        Self::decode(&fs::read(path)?, encoding)
+    }
+
+    /// Encode text in the encoding of the file, with its byte order mark if it had one.
+    ///
+    /// Characters that cannot be represented in the encoding are an error.
+    pub fn encode(&self, text: &str) -> Result<Vec<u8>> {
+        let mut bytes = Vec::with_capacity(text.len() + 3);
+        if self.encoding == UTF_16LE || self.encoding == UTF_16BE {
+            let little_endian = self.encoding == UTF_16LE;
+            let units = self.bom.then_some(0xFEFF).into_iter();
+            for unit in units.chain(text.encode_utf16()) {
+                if little_endian {
+                    bytes.extend_from_slice(&unit.to_le_bytes());
+                } else {
+                    bytes.extend_from_slice(&unit.to_be_bytes());
+                }
+            }
+            return Ok(bytes);
+        }
+        if self.bom && self.encoding == UTF_8 {
+            bytes.extend_from_slice(b"\xEF\xBB\xBF");
+        }
+        let (encoded, _, unmappable) = self.encoding.encode(text);
+        if unmappable {
+            let c = text
+                .chars()
+                .find(|c| self.encoding.encode(c.encode_utf8(&mut [0; 4])).2)
+                .unwrap_or_default();
+            return Err(Error::EncodingError(format!(
+                "Character {c} cannot be written in {}",
+                self.encoding.name()
+            )));
+        }
+        bytes.extend_from_slice(&encoded);
+        Ok(bytes)
+    }
+
+    /// Write text to a file in the encoding of this source, see [`Self::encode`].
+    pub fn write(&self, path: impl AsRef<Path>, text: &str) -> Result<()> {
+        fs::write(path, self.encode(text)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_encoding() {
+        let utf8 = "@book{a, author = {Müller}}";
+        assert_eq!(detect_encoding(utf8.as_bytes()), UTF_8);
+        let (latin1, ..) = WINDOWS_1252.encode(utf8);
+        assert_eq!(detect_encoding(&latin1), WINDOWS_1252);
+        let (gbk, ..) = GBK.encode("@book{a, author = {张三}, title = {数值分析}}");
+        assert_eq!(detect_encoding(&gbk), GBK);
+        let jabref = b"% Encoding: ISO-8859-1\n\n@book{a, title = {\xE9t\xE9}}";
+        assert_eq!(detect_encoding(jabref), WINDOWS_1252);
+        assert_eq!(encoding_for_label("Latin1").unwrap(), WINDOWS_1252);
+        assert!(encoding_for_label("klingon").is_err());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let text = "@book{a, author = {Müller}}";
+        let (latin1, ..) = WINDOWS_1252.encode(text);
+        let source = BibSource::decode(&latin1, None).unwrap();
+        assert_eq!(source.text, text);
+        assert_eq!(source.encode(&source.text).unwrap(), latin1.into_owned());
+        assert!(source.encode("@book{a, author = {张三}}").is_err());
+
+        let bom = b"\xEF\xBB\xBF@book{a}";
+        let source = BibSource::decode(bom, Some(GBK)).unwrap();
+        assert_eq!((source.encoding, source.bom), (UTF_8, true));
+        assert_eq!(source.text, "@book{a}");
+        assert_eq!(source.encode(&source.text).unwrap(), bom);
+
+        let utf16 = [0xFF, 0xFE, b'@', 0, b'a', 0];
+        let source = BibSource::decode(&utf16, None).unwrap();
+        assert_eq!(source.text, "@a");
+        assert_eq!(source.encode("@a").unwrap(), utf16);
+
+        assert!(BibSource::decode(b"\xFF\xFF", Some(UTF_8)).is_err());
+    }
+
+    #[test]
+    fn test_edit_in_file() {
+        let path =
+            std::env::temp_dir().join(format!("bibcitex-encoding-{}.bib", std::process::id()));
+        let (gbk, ..) = GBK.encode("@book{a, author = {张三}, title = {数值分析}}\n");
+        fs::write(&path, &gbk).unwrap();
+        assert!(crate::bib::parse(&path).is_ok());
+        let edit = crate::TagEdit::Add("教材".to_string());
+        assert_eq!(
+            crate::edit_tags_in_file(&path, None, &["a"], &edit, ",").unwrap(),
+            1
+        );
+        let source = BibSource::read(&path, None).unwrap();
+        assert_eq!(source.encoding, GBK);
+        assert!(source.text.contains("keywords = {教材}"));
+        fs::remove_file(&path).unwrap();
+    }
+}
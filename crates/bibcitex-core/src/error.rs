@@ -19,6 +19,18 @@ pub enum Error {
     /// Field Type Error
     #[error("{0}")]
     FieldType(String),
+    /// Citation Template Not Found Error
+    #[error("Citation template {0} not found")]
+    TemplateNotFound(String),
+    /// CSL Style or Rendering Error
+    #[error("{0}")]
+    CSLError(String),
+    /// File Encoding Error
+    #[error("{0}")]
+    EncodingError(String),
+    /// Search Query Syntax Error
+    #[error("Invalid query at position {position}: {message}")]
+    QueryError { position: usize, message: String },
 }
 
 impl From<biblatex::ParseError> for Error {
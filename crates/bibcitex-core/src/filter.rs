@@ -1,78 +1,298 @@
-use crate::bib::Reference;
+//! Composable filters on references.
+//!
+//! A [`Filter`] is a predicate built from entry types, year ranges, the presence of a DOI or an
+//! attached file, tags, the reading status and rating, and the library of a reference, combined
+//! with [`Filter::and`], [`Filter::or`] and [`Filter::not`]. Filters narrow the references of the
+//! search, of saved searches, and of the statistics and co-author graph with their exports:
+//!
+//! ```
+//! use bibcitex_core::Filter;
+//! use biblatex::EntryType;
+//!
+//! let filter = Filter::types([EntryType::Article, EntryType::Book])
+//!     .and(Filter::years(Some(2000), None))
+//!     .and(Filter::HasDoi.not());
+//! ```
+use crate::{ReadingStatus, bib::Reference, cjk, search::THRESHOLD_PARALLEL_SIZE};
 use biblatex::EntryType;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
 
-/// Filters references to articles.
-pub fn filter_article(references: Vec<Reference>) -> Vec<Reference> {
-    references
-        .into_iter()
-        .filter(|r| r.type_ == EntryType::Article)
-        .collect()
+/// A predicate on references.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Filter {
+    /// Matches every reference
+    #[default]
+    All,
+    /// Any of the entry types, `thesis` also matches `phdthesis` and `mastersthesis`
+    Types(#[serde(with = "entry_types")] Vec<EntryType>),
+    /// An inclusive year range, open if a bound is missing
+    YearRange {
+        start: Option<i32>,
+        end: Option<i32>,
+    },
+    /// References with a DOI
+    HasDoi,
+    /// References with an attached file, e.g., a PDF
+    HasPdf,
+    /// References with a keyword, compared case-insensitively
+    Tag(String),
+    /// References with a reading status, unannotated ones are unread
+    Status(ReadingStatus),
+    /// References rated at least `min`
+    Rating {
+        min: u8,
+    },
+    /// References from the named library
+    Library(String),
+    And(Box<Filter>, Box<Filter>),
+    Or(Box<Filter>, Box<Filter>),
+    Not(Box<Filter>),
 }
 
-/// Filters references to books.
-pub fn filter_book(references: Vec<Reference>) -> Vec<Reference> {
-    references
-        .into_iter()
-        .filter(|r| r.type_ == EntryType::Book)
-        .collect()
-}
+impl Filter {
+    /// Matches references of any of the entry types, or every reference if there are none.
+    pub fn types(types: impl IntoIterator<Item = EntryType>) -> Self {
+        let types = types.into_iter().collect::<Vec<_>>();
+        if types.is_empty() {
+            Self::All
+        } else {
+            Self::Types(types)
+        }
+    }
 
-/// Filters references to theses.
-pub fn filter_thesis(references: Vec<Reference>) -> Vec<Reference> {
-    references
-        .into_iter()
-        .filter(|r| {
-            r.type_ == EntryType::PhdThesis
-                || r.type_ == EntryType::MastersThesis
-                || r.type_ == EntryType::Thesis
-        })
-        .collect()
-}
+    /// Matches references published in an inclusive year range.
+    pub fn years(start: Option<i32>, end: Option<i32>) -> Self {
+        Self::YearRange { start, end }
+    }
 
-/// Filters references to booklet.
-pub fn filter_booklet(references: Vec<Reference>) -> Vec<Reference> {
-    references
-        .into_iter()
-        .filter(|r| r.type_ == EntryType::Booklet)
-        .collect()
-}
+    /// Matches references passing both filters.
+    pub fn and(self, other: Self) -> Self {
+        match (self, other) {
+            (Self::All, filter) | (filter, Self::All) => filter,
+            (lhs, rhs) => Self::And(Box::new(lhs), Box::new(rhs)),
+        }
+    }
+
+    /// Matches references passing either filter.
+    pub fn or(self, other: Self) -> Self {
+        match (self, other) {
+            (Self::All, _) | (_, Self::All) => Self::All,
+            (lhs, rhs) => Self::Or(Box::new(lhs), Box::new(rhs)),
+        }
+    }
 
-/// Filters references to inbook.
-pub fn filter_inbook(references: Vec<Reference>) -> Vec<Reference> {
-    references
-        .into_iter()
-        .filter(|r| r.type_ == EntryType::InBook)
-        .collect()
+    /// Matches references not passing the filter.
+    #[allow(clippy::should_implement_trait)]
+    pub fn not(self) -> Self {
+        match self {
+            Self::Not(filter) => *filter,
+            filter => Self::Not(Box::new(filter)),
+        }
+    }
+
+    /// Check whether a reference passes the filter.
+    ///
+    /// `library` is the name of the library the reference belongs to, if known. A
+    /// [`Filter::Library`] never matches a reference from an unknown library.
+    pub fn matches(&self, reference: &Reference, library: Option<&str>) -> bool {
+        match self {
+            Self::All => true,
+            Self::Types(types) => types.iter().any(|entry_type| match entry_type {
+                EntryType::Unknown(_) => reference.type_ == *entry_type,
+                _ => reference.type_.to_biblatex() == entry_type.to_biblatex(),
+            }),
+            Self::YearRange { start, end } => reference.year.is_some_and(|year| {
+                start.is_none_or(|start| year >= start) && end.is_none_or(|end| year <= end)
+            }),
+            Self::HasDoi => reference.doi.as_ref().is_some_and(|doi| !doi.is_empty()),
+            Self::HasPdf => reference.file.as_ref().is_some_and(|file| !file.is_empty()),
+            Self::Tag(tag) => {
+                let tag = cjk::normalize(tag.trim());
+                reference
+                    .keywords
+                    .iter()
+                    .flatten()
+                    .any(|keyword| cjk::normalize(keyword) == tag)
+            }
+            Self::Status(status) => {
+                reference
+                    .annotation
+                    .as_ref()
+                    .map(|annotation| annotation.status)
+                    .unwrap_or_default()
+                    == *status
+            }
+            Self::Rating { min } => reference
+                .annotation
+                .as_ref()
+                .and_then(|annotation| annotation.rating)
+                .is_some_and(|rating| rating >= *min),
+            Self::Library(name) => library == Some(name.as_str()),
+            Self::And(lhs, rhs) => {
+                lhs.matches(reference, library) && rhs.matches(reference, library)
+            }
+            Self::Or(lhs, rhs) => {
+                lhs.matches(reference, library) || rhs.matches(reference, library)
+            }
+            Self::Not(filter) => !filter.matches(reference, library),
+        }
+    }
+
+    /// Check whether the filter matches every reference.
+    pub fn is_all(&self) -> bool {
+        *self == Self::All
+    }
 }
 
-/// Filters references to incollection.
-pub fn filter_incollection(references: Vec<Reference>) -> Vec<Reference> {
-    references
-        .into_iter()
-        .filter(|r| r.type_ == EntryType::InCollection)
-        .collect()
+/// Filter references from a library sequentially or in parallel.
+pub fn filter_references(
+    references: &[Reference],
+    filter: &Filter,
+    library: Option<&str>,
+) -> Vec<Reference> {
+    if filter.is_all() {
+        return references.to_vec();
+    }
+    if references.len() > THRESHOLD_PARALLEL_SIZE {
+        references
+            .par_iter()
+            .filter(|&reference| filter.matches(reference, library))
+            .cloned()
+            .collect()
+    } else {
+        references
+            .iter()
+            .filter(|&reference| filter.matches(reference, library))
+            .cloned()
+            .collect()
+    }
 }
 
-/// Filters references to inproceedings.
-pub fn filter_inproceedings(references: Vec<Reference>) -> Vec<Reference> {
-    references
-        .into_iter()
-        .filter(|r| r.type_ == EntryType::InProceedings)
-        .collect()
+/// Entry types are stored by their BibTeX names.
+mod entry_types {
+    use biblatex::EntryType;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(types: &[EntryType], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(types.iter().map(|entry_type| entry_type.to_string()))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<EntryType>, D::Error> {
+        let names = Vec::<String>::deserialize(deserializer)?;
+        Ok(names.iter().map(|name| EntryType::new(name)).collect())
+    }
 }
 
-/// Filters references to misc.
-pub fn filter_misc(references: Vec<Reference>) -> Vec<Reference> {
-    references
-        .into_iter()
-        .filter(|r| r.type_ == EntryType::Misc)
-        .collect()
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Annotation, utils::read_bibliography};
+    use biblatex::Bibliography;
+
+    const SRC: &str = r#"
+@article{smith2010,
+    author = {John Smith},
+    title = {Discontinuous Galerkin Methods},
+    doi = {10.1137/1},
+    keywords = {Finite Elements; DG},
+    year = {2010},
+}
+@phdthesis{lee2015,
+    author = {Ann Lee},
+    title = {Spectral Methods},
+    file = {lee2015.pdf},
+    year = {2015},
 }
+@book{knuth1984,
+    author = {Donald Knuth},
+    title = {The TeXbook},
+    year = {1984},
+}
+"#;
+
+    fn filter(filter: &Filter, library: Option<&str>) -> Vec<String> {
+        let refs = read_bibliography(Bibliography::parse(SRC).unwrap());
+        filter_references(&refs, filter, library)
+            .into_iter()
+            .map(|r| r.cite_key)
+            .collect()
+    }
+
+    #[test]
+    fn test_filter() {
+        assert_eq!(filter(&Filter::All, None).len(), 3);
+        assert_eq!(filter(&Filter::types([]), None).len(), 3);
+        assert_eq!(
+            filter(&Filter::types([EntryType::Thesis]), None),
+            ["lee2015"]
+        );
+        assert_eq!(
+            filter(&Filter::types([EntryType::Article, EntryType::Book]), None),
+            ["smith2010", "knuth1984"]
+        );
+        assert_eq!(
+            filter(&Filter::years(Some(2000), None), None),
+            ["smith2010", "lee2015"]
+        );
+        assert_eq!(filter(&Filter::HasDoi, None), ["smith2010"]);
+        assert_eq!(filter(&Filter::HasPdf, None), ["lee2015"]);
+        assert_eq!(filter(&Filter::Tag("dg".into()), None), ["smith2010"]);
+        assert!(filter(&Filter::Tag("galerkin".into()), None).is_empty());
+    }
+
+    #[test]
+    fn test_combine() {
+        let recent = Filter::years(Some(2000), None);
+        assert_eq!(
+            filter(&recent.clone().and(Filter::HasDoi.not()), None),
+            ["lee2015"]
+        );
+        assert_eq!(
+            filter(&Filter::HasDoi.or(Filter::HasPdf), None),
+            ["smith2010", "lee2015"]
+        );
+        assert_eq!(Filter::All.and(recent.clone()), recent);
+        assert_eq!(recent.clone().or(Filter::All), Filter::All);
+        assert_eq!(recent.clone().not().not(), recent);
+        let library = Filter::Library("thesis".into());
+        assert!(filter(&library, None).is_empty());
+        assert!(filter(&library, Some("papers")).is_empty());
+        assert_eq!(filter(&library, Some("thesis")).len(), 3);
+    }
+
+    #[test]
+    fn test_annotation() {
+        let mut refs = read_bibliography(Bibliography::parse(SRC).unwrap());
+        refs[0].annotation = Some(Annotation {
+            status: ReadingStatus::Read,
+            rating: Some(4),
+            ..Default::default()
+        });
+        let keys = |filter: &Filter| {
+            filter_references(&refs, filter, None)
+                .into_iter()
+                .map(|r| r.cite_key)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(keys(&Filter::Status(ReadingStatus::Read)), ["smith2010"]);
+        assert_eq!(
+            keys(&Filter::Status(ReadingStatus::Unread)),
+            ["lee2015", "knuth1984"]
+        );
+        assert_eq!(keys(&Filter::Rating { min: 4 }), ["smith2010"]);
+        assert!(keys(&Filter::Rating { min: 5 }).is_empty());
+    }
 
-/// Filters references to techreport.
-pub fn filter_techreport(references: Vec<Reference>) -> Vec<Reference> {
-    references
-        .into_iter()
-        .filter(|r| r.type_ == EntryType::TechReport)
-        .collect()
+    #[test]
+    fn test_serde() {
+        let filter = Filter::types([EntryType::Article, EntryType::Thesis])
+            .and(Filter::Tag("DG".into()).or(Filter::Library("papers".into())));
+        let json = serde_json::to_string(&filter).unwrap();
+        assert!(json.contains(r#"["article","thesis"]"#));
+        assert_eq!(serde_json::from_str::<Filter>(&json).unwrap(), filter);
+    }
 }
@@ -0,0 +1,453 @@
+use crate::{
+    bib::Reference,
+    utils::{chunks_to_string, contains_cjk, merge_chunks},
+};
+use biblatex::{Bibliography, EntryType, Person};
+use serde::{Deserialize, Serialize};
+
+/// Maximum number of authors listed before truncating with "等" or "et al.".
+const MAX_AUTHORS: usize = 3;
+
+/// Variants of GB/T 7714-2015
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum GbtStyle {
+    /// 顺序编码制, `[1] Author. Title[J]. Journal, Year, ...`
+    #[default]
+    Numeric,
+    /// 著者-出版年制, `Author, Year. Title[J]. Journal, ...`
+    AuthorYear,
+}
+
+impl std::fmt::Display for GbtStyle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GbtStyle::Numeric => write!(f, "GB/T 7714 (顺序编码)"),
+            GbtStyle::AuthorYear => write!(f, "GB/T 7714 (著者-出版年)"),
+        }
+    }
+}
+
+/// Document type code of GB/T 7714-2015, e.g., `J` for journal articles.
+pub fn type_code(reference: &Reference) -> &'static str {
+    match reference.type_ {
+        EntryType::Article | EntryType::Periodical => "J",
+        EntryType::Book
+        | EntryType::MvBook
+        | EntryType::InBook
+        | EntryType::BookInBook
+        | EntryType::Booklet
+        | EntryType::InCollection
+        | EntryType::Collection
+        | EntryType::Manual
+        | EntryType::Reference
+        | EntryType::InReference => "M",
+        EntryType::InProceedings | EntryType::Proceedings | EntryType::MvProceedings => "C",
+        EntryType::Thesis | EntryType::PhdThesis | EntryType::MastersThesis => "D",
+        EntryType::TechReport | EntryType::Report => "R",
+        EntryType::Patent => "P",
+        EntryType::Dataset => "DS",
+        EntryType::Software => "CP",
+        EntryType::Online => "EB/OL",
+        _ if reference.url.is_some() || reference.eprint.is_some() => "EB/OL",
+        _ => "Z",
+    }
+}
+
+/// Format a reference in GB/T 7714-2015.
+///
+/// The numeric variant does not include the sequence number, see [`format_gbt7714_list`].
+pub fn format_gbt7714(reference: &Reference, style: GbtStyle) -> String {
+    let names = Names::from_reference(reference);
+    let cjk = names.is_cjk(reference);
+    let code = type_code(reference);
+    let title = reference
+        .title
+        .as_deref()
+        .map(chunks_to_string)
+        .unwrap_or_default();
+    let year = reference.year.map(|year| year.to_string());
+
+    let mut out = String::new();
+    // Responsible persons and title
+    let authors = format_names(&names.authors, cjk);
+    match style {
+        GbtStyle::Numeric => {
+            if !authors.is_empty() {
+                out.push_str(&authors);
+                out.push_str(". ");
+            }
+        }
+        GbtStyle::AuthorYear => {
+            if authors.is_empty() {
+                out.push_str(if cjk { "佚名" } else { "Anon" });
+            } else {
+                out.push_str(&authors);
+            }
+            out.push_str(", ");
+            out.push_str(
+                year.as_deref()
+                    .unwrap_or(if cjk { "出版年不详" } else { "n.d." }),
+            );
+            out.push_str(". ");
+        }
+    }
+    out.push_str(&format!("{title}[{code}]"));
+
+    // Year is printed in the publication part only for the numeric variant
+    let year = match style {
+        GbtStyle::Numeric => year,
+        GbtStyle::AuthorYear => None,
+    };
+    let pages = reference
+        .pages
+        .as_ref()
+        .map(|pages| {
+            if pages.start == pages.end {
+                pages.start.to_string()
+            } else {
+                format!("{}-{}", pages.start, pages.end)
+            }
+        })
+        .or_else(|| reference.book_pages.clone().map(|p| p.replace("--", "-")));
+
+    match code {
+        "J" => {
+            out.push_str(". ");
+            let journal = reference
+                .journal
+                .clone()
+                .or_else(|| reference.full_journal.clone())
+                .unwrap_or_default();
+            out.push_str(&journal);
+            let mut issue = String::new();
+            if let Some(volume) = reference.volume {
+                issue.push_str(&volume.to_string());
+            }
+            if let Some(number) = &reference.number {
+                issue.push_str(&format!("({number})"));
+            }
+            let serial = [year, (!issue.is_empty()).then_some(issue)]
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>()
+                .join(", ");
+            if !serial.is_empty() {
+                out.push_str(", ");
+                out.push_str(&serial);
+            }
+            if let Some(pages) = pages {
+                out.push_str(": ");
+                out.push_str(&pages);
+            }
+        }
+        "C" | "M" if reference.book_title.is_some() => {
+            // A part of a collection: `Title[C]//Editor. Book title. Place: Publisher, Year: Pages`
+            out.push_str("//");
+            let editors = format_names(&names.editors, cjk);
+            if !editors.is_empty() {
+                out.push_str(&editors);
+                out.push_str(". ");
+            }
+            out.push_str(&chunks_to_string(
+                reference.book_title.as_deref().unwrap_or_default(),
+            ));
+            out.push_str(". ");
+            out.push_str(&publication(
+                reference.address.as_deref(),
+                reference
+                    .publisher
+                    .as_ref()
+                    .map(|p| p.join(", "))
+                    .as_deref(),
+                year.as_deref(),
+                cjk,
+            ));
+            if let Some(pages) = pages {
+                out.push_str(": ");
+                out.push_str(&pages);
+            }
+        }
+        "EB/OL" => {
+            out.push_str(". ");
+            if let Some(year) = &year {
+                out.push_str(year);
+            }
+            if let Some(accessed) = &names.urldate {
+                out.push_str(&format!("[{accessed}]"));
+            }
+            let url = reference.url.clone().or_else(|| {
+                reference
+                    .eprint
+                    .as_ref()
+                    .map(|eprint| format!("https://arxiv.org/abs/{eprint}"))
+            });
+            if let Some(url) = url {
+                if year.is_some() || names.urldate.is_some() {
+                    out.push_str(". ");
+                }
+                out.push_str(&url);
+            }
+        }
+        _ => {
+            if let Some(edition) = reference.edition.filter(|&edition| edition > 1) {
+                out.push_str(". ");
+                out.push_str(&format_edition(edition, cjk));
+            }
+            out.push_str(". ");
+            let publisher = match code {
+                "D" => reference.school.clone(),
+                "R" => reference
+                    .institution
+                    .clone()
+                    .or_else(|| reference.publisher.as_ref().map(|p| p.join(", "))),
+                _ => reference.publisher.as_ref().map(|p| p.join(", ")),
+            };
+            out.push_str(&publication(
+                reference.address.as_deref(),
+                publisher.as_deref(),
+                year.as_deref(),
+                cjk,
+            ));
+            if code != "M"
+                && let Some(pages) = pages
+            {
+                out.push_str(": ");
+                out.push_str(&pages);
+            }
+        }
+    }
+    if let Some(doi) = &reference.doi {
+        out.push_str(". DOI:");
+        out.push_str(doi);
+    }
+    out.push('.');
+    out
+}
+
+/// Format references as a GB/T 7714-2015 bibliography.
+///
+/// The numeric variant keeps the given order and prefixes each item with its sequence number,
+/// while the author-year variant sorts items by authors and year.
+pub fn format_gbt7714_list(references: &[Reference], style: GbtStyle) -> Vec<String> {
+    match style {
+        GbtStyle::Numeric => references
+            .iter()
+            .enumerate()
+            .map(|(i, reference)| format!("[{}] {}", i + 1, format_gbt7714(reference, style)))
+            .collect(),
+        GbtStyle::AuthorYear => {
+            let mut items = references
+                .iter()
+                .map(|reference| format_gbt7714(reference, style))
+                .collect::<Vec<_>>();
+            items.sort();
+            items
+        }
+    }
+}
+
+/// Responsible persons and other fields not kept by [`Reference`].
+#[derive(Default)]
+struct Names {
+    authors: Vec<Person>,
+    editors: Vec<Person>,
+    urldate: Option<String>,
+}
+
+impl Names {
+    fn from_reference(reference: &Reference) -> Self {
+        let Ok(bibliography) = Bibliography::parse(&reference.source) else {
+            return Self::default();
+        };
+        let Some(entry) = bibliography.get(&reference.cite_key) else {
+            return Self::default();
+        };
+        Self {
+            authors: entry.author().unwrap_or_default(),
+            editors: entry
+                .editors()
+                .map(|editors| {
+                    editors
+                        .into_iter()
+                        .flat_map(|(persons, _)| persons)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            urldate: entry
+                .get("urldate")
+                .map(|chunks| chunks_to_string(&merge_chunks(chunks.to_owned()))),
+        }
+    }
+
+    /// Chinese references use "等" and Chinese placeholders, decided by the first author or the title.
+    fn is_cjk(&self, reference: &Reference) -> bool {
+        match self.authors.first() {
+            Some(person) => contains_cjk(&person.name) || contains_cjk(&person.given_name),
+            None => reference
+                .title
+                .as_deref()
+                .is_some_and(|title| contains_cjk(&chunks_to_string(title))),
+        }
+    }
+}
+
+/// Format a list of persons, keeping at most three names.
+fn format_names(persons: &[Person], cjk: bool) -> String {
+    let mut names = persons
+        .iter()
+        .take(MAX_AUTHORS)
+        .map(format_person)
+        .collect::<Vec<_>>();
+    if persons.len() > MAX_AUTHORS {
+        names.push(if cjk { "等" } else { "et al" }.to_string());
+    }
+    names.join(", ")
+}
+
+/// Chinese names are kept as written, Latin names become `SURNAME G N`.
+fn format_person(person: &Person) -> String {
+    if contains_cjk(&person.name) || contains_cjk(&person.given_name) {
+        return format!("{}{}", person.name, person.given_name);
+    }
+    let surname = [person.prefix.as_str(), person.name.as_str()]
+        .iter()
+        .filter(|part| !part.is_empty())
+        .map(|part| part.to_uppercase())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let initials = person
+        .given_name
+        .split_whitespace()
+        .map(|part| {
+            part.split('-')
+                .filter_map(|p| p.chars().find(|c| c.is_alphabetic()))
+                .map(|c| c.to_uppercase().to_string())
+                .collect::<Vec<_>>()
+                .join("-")
+        })
+        .filter(|initial| !initial.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+    if initials.is_empty() {
+        surname
+    } else {
+        format!("{surname} {initials}")
+    }
+}
+
+fn format_edition(edition: i64, cjk: bool) -> String {
+    if cjk {
+        return format!("{edition}版");
+    }
+    let suffix = match (edition % 10, edition % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{edition}{suffix} ed")
+}
+
+/// `Place: Publisher, Year`, with placeholders for a missing place or publisher.
+fn publication(
+    place: Option<&str>,
+    publisher: Option<&str>,
+    year: Option<&str>,
+    cjk: bool,
+) -> String {
+    let place = place.unwrap_or(if cjk { "[出版地不详]" } else { "[S.l.]" });
+    let publisher = publisher.unwrap_or(if cjk { "[出版者不详]" } else { "[s.n.]" });
+    match year {
+        Some(year) => format!("{place}: {publisher}, {year}"),
+        None => format!("{place}: {publisher}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::read_bibliography;
+
+    const SRC: &str = r#"
+@article{knuth1984,
+    author = {Donald E. Knuth},
+    title = {Literate Programming},
+    journal = {The Computer Journal},
+    volume = {27},
+    number = {2},
+    pages = {97--111},
+    year = {1984},
+}
+@phdthesis{zhang2020,
+    author = {张三},
+    title = {偏微分方程数值解},
+    school = {北京大学},
+    address = {北京},
+    year = {2020},
+}
+@book{many,
+    author = {Alice Smith and Bob Jones and Carol White and Dan Brown},
+    title = {Numerical Analysis},
+    publisher = {Springer},
+    address = {Berlin},
+    edition = {2},
+    year = {2010},
+}
+@misc{arxiv,
+    author = {李四 and 王五 and 赵六 and 钱七},
+    title = {深度学习},
+    eprint = {2101.00001},
+    year = {2021},
+    urldate = {2024-05-01},
+}
+"#;
+
+    fn references() -> Vec<Reference> {
+        crate::utils::serial_read_bibliography(Bibliography::parse(SRC).unwrap())
+    }
+
+    fn get(refs: &[Reference], key: &str) -> Reference {
+        refs.iter().find(|r| r.cite_key == key).unwrap().clone()
+    }
+
+    #[test]
+    fn test_numeric() {
+        let refs = references();
+        assert_eq!(
+            format_gbt7714(&get(&refs, "knuth1984"), GbtStyle::Numeric),
+            "KNUTH D E. Literate Programming[J]. The Computer Journal, 1984, 27(2): 97-111."
+        );
+        assert_eq!(
+            format_gbt7714(&get(&refs, "zhang2020"), GbtStyle::Numeric),
+            "张三. 偏微分方程数值解[D]. 北京: 北京大学, 2020."
+        );
+        assert_eq!(
+            format_gbt7714(&get(&refs, "many"), GbtStyle::Numeric),
+            "SMITH A, JONES B, WHITE C, et al. Numerical Analysis[M]. 2nd ed. Berlin: Springer, 2010."
+        );
+        assert_eq!(
+            format_gbt7714(&get(&refs, "arxiv"), GbtStyle::Numeric),
+            "李四, 王五, 赵六, 等. 深度学习[EB/OL]. 2021[2024-05-01]. https://arxiv.org/abs/2101.00001."
+        );
+    }
+
+    #[test]
+    fn test_author_year() {
+        let refs = references();
+        assert_eq!(
+            format_gbt7714(&get(&refs, "knuth1984"), GbtStyle::AuthorYear),
+            "KNUTH D E, 1984. Literate Programming[J]. The Computer Journal, 27(2): 97-111."
+        );
+        let list = format_gbt7714_list(&refs, GbtStyle::AuthorYear);
+        assert!(list[0].starts_with("KNUTH"));
+    }
+
+    #[test]
+    fn test_numeric_list() {
+        let refs = read_bibliography(Bibliography::parse(SRC).unwrap());
+        let list = format_gbt7714_list(&refs, GbtStyle::Numeric);
+        assert_eq!(list.len(), 4);
+        assert!(list[0].starts_with("[1] "));
+    }
+}
@@ -0,0 +1,281 @@
+//! Match spans for highlighting search results.
+//!
+//! Spans are byte ranges of the displayed text. Terms found in the folded or pinyin forms of a
+//! value, see [`crate::cjk`], are mapped back to the characters they came from, so `zgkx`
+//! highlights `中国科学`.
+use crate::{
+    bib::Reference,
+    cjk::{self, is_cjk, pinyin_of},
+    query::{Query, QueryField, glob_match},
+};
+use std::ops::Range;
+
+/// A value of a field matched by a query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldMatch {
+    /// The matched field
+    pub field: QueryField,
+    /// The value as displayed
+    pub value: String,
+    /// Sorted, disjoint byte ranges of the value matched by the query
+    pub spans: Vec<Range<usize>>,
+}
+
+impl Query {
+    /// Byte ranges of `text`, a value of `field`, matched by the terms of the query.
+    ///
+    /// Negated terms are not highlighted. The ranges are sorted and disjoint.
+    pub fn spans(&self, field: QueryField, text: &str) -> Vec<Range<usize>> {
+        let mut terms = Vec::new();
+        self.terms(field, false, &mut terms);
+        if terms.is_empty() || text.is_empty() {
+            return Vec::new();
+        }
+        let forms = Form::all(text);
+        let mut spans = Vec::new();
+        for term in terms {
+            if term.contains('*') || field == QueryField::Year {
+                let matched = forms.iter().any(|form| {
+                    if term.contains('*') {
+                        glob_match(term, &form.text)
+                    } else {
+                        form.text == term
+                    }
+                });
+                if matched {
+                    spans.push(0..text.len());
+                }
+                continue;
+            }
+            for form in &forms {
+                for (start, found) in form.text.match_indices(term) {
+                    spans.push(form.source(start..start + found.len()));
+                }
+            }
+        }
+        merge(spans)
+    }
+
+    /// Values of the fields of a reference matched by the query, with their spans.
+    ///
+    /// Fields are listed in the order of [`QueryField::all`].
+    pub fn match_spans(&self, reference: &Reference) -> Vec<FieldMatch> {
+        QueryField::all()
+            .iter()
+            .flat_map(|&field| {
+                field
+                    .raw_values(reference)
+                    .into_iter()
+                    .filter_map(move |value| {
+                        let spans = self.spans(field, &value);
+                        (!spans.is_empty()).then_some(FieldMatch {
+                            field,
+                            value,
+                            spans,
+                        })
+                    })
+            })
+            .collect()
+    }
+
+    /// Collect the lowercase texts of the terms searching `field`, skipping negated ones.
+    fn terms<'a>(&'a self, field: QueryField, negated: bool, terms: &mut Vec<&'a str>) {
+        match self {
+            Self::Term { fields, text } if !negated && fields.contains(&field) => {
+                terms.push(text);
+            }
+            Self::And(lhs, rhs) | Self::Or(lhs, rhs) => {
+                lhs.terms(field, negated, terms);
+                rhs.terms(field, negated, terms);
+            }
+            Self::Not(query) => query.terms(field, !negated, terms),
+            _ => {}
+        }
+    }
+}
+
+/// A searchable form of a text, with the byte range of the source character of each segment.
+struct Form {
+    text: String,
+    /// Start of the segment in `text` and the range of its source character
+    segments: Vec<(usize, Range<usize>)>,
+}
+
+impl Form {
+    /// The normalized form of a text, followed by its pinyin forms if it has CJK ideographs.
+    fn all(text: &str) -> Vec<Self> {
+        let folded = text
+            .char_indices()
+            .map(|(start, c)| {
+                (
+                    start..start + c.len_utf8(),
+                    cjk::normalize(c.encode_utf8(&mut [0; 4])),
+                )
+            })
+            .collect::<Vec<_>>();
+        let mut forms = vec![Self::new(
+            folded.iter().map(|(range, s)| (range, s.as_str())),
+        )];
+        if folded.iter().any(|(_, s)| s.chars().any(is_cjk)) {
+            let pinyin = folded
+                .iter()
+                .map(|(range, s)| {
+                    let reading = s.chars().next().and_then(pinyin_of);
+                    (range, s.as_str(), reading)
+                })
+                .collect::<Vec<_>>();
+            forms.push(Self::new(pinyin.iter().map(|(range, s, reading)| {
+                (*range, reading.map_or(*s, |(plain, _)| plain))
+            })));
+            forms.push(Self::new(pinyin.iter().map(|(range, s, reading)| {
+                (*range, reading.map_or(*s, |(_, initial)| initial))
+            })));
+        }
+        forms
+    }
+
+    fn new<'a>(segments: impl Iterator<Item = (&'a Range<usize>, &'a str)>) -> Self {
+        let mut text = String::new();
+        let mut starts = Vec::new();
+        for (range, segment) in segments {
+            starts.push((text.len(), range.clone()));
+            text.push_str(segment);
+        }
+        Self {
+            text,
+            segments: starts,
+        }
+    }
+
+    /// The range of the source text covering the characters of a range of the form.
+    fn source(&self, range: Range<usize>) -> Range<usize> {
+        let first = self
+            .segments
+            .partition_point(|(start, _)| *start <= range.start)
+            .saturating_sub(1);
+        let last = self
+            .segments
+            .partition_point(|(start, _)| *start < range.end)
+            .saturating_sub(1);
+        self.segments[first].1.start..self.segments[last].1.end
+    }
+}
+
+/// Sort ranges and merge overlapping or adjacent ones.
+fn merge(mut spans: Vec<Range<usize>>) -> Vec<Range<usize>> {
+    spans.sort_by_key(|span| (span.start, span.end));
+    let mut merged: Vec<Range<usize>> = Vec::with_capacity(spans.len());
+    for span in spans {
+        match merged.last_mut() {
+            Some(last) if span.start <= last.end => last.end = last.end.max(span.end),
+            _ => merged.push(span),
+        }
+    }
+    merged
+}
+
+/// A snippet of `text` around `span`, with about `context` characters on each side.
+///
+/// Returns the snippet and the span within it. An ellipsis marks text cut at either end.
+pub fn snippet(text: &str, span: Range<usize>, context: usize) -> (String, Range<usize>) {
+    let start = text[..span.start]
+        .char_indices()
+        .rev()
+        .take(context)
+        .last()
+        .map_or(span.start, |(index, _)| index);
+    let end = text[span.end..]
+        .char_indices()
+        .nth(context)
+        .map_or(text.len(), |(index, _)| span.end + index);
+    let (prefix, suffix) = (
+        if start > 0 { "…" } else { "" },
+        if end < text.len() { "…" } else { "" },
+    );
+    let offset = prefix.len() + span.start - start;
+    (
+        format!("{prefix}{}{suffix}", &text[start..end]),
+        offset..offset + span.len(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::read_bibliography;
+    use biblatex::Bibliography;
+
+    fn spans(query: &str, field: QueryField, text: &str) -> Vec<String> {
+        Query::parse(query)
+            .unwrap()
+            .spans(field, text)
+            .into_iter()
+            .map(|span| text[span].to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_spans() {
+        let title = "Discontinuous Galerkin Methods";
+        assert_eq!(spans("galerkin", QueryField::Title, title), ["Galerkin"]);
+        assert_eq!(
+            spans("galerkin meth", QueryField::Title, title),
+            ["Galerkin", "Meth"]
+        );
+        assert_eq!(spans("in", QueryField::Title, title), ["in", "in"]);
+        assert!(spans("author:galerkin", QueryField::Title, title).is_empty());
+        assert_eq!(
+            spans("methods -galerkin", QueryField::Title, title),
+            ["Methods"]
+        );
+        assert_eq!(
+            spans("doi:10.1137/*", QueryField::Doi, "10.1137/1"),
+            ["10.1137/1"]
+        );
+    }
+
+    #[test]
+    fn test_cjk_spans() {
+        let journal = "中國科學：數學";
+        assert_eq!(spans("zgkx", QueryField::Journal, journal), ["中國科學"]);
+        assert_eq!(spans("kexue", QueryField::Journal, journal), ["科學"]);
+        assert_eq!(spans("数学", QueryField::Journal, journal), ["數學"]);
+        assert_eq!(
+            spans("ｇａｌｅｒｋｉｎ", QueryField::Title, "Galerkin"),
+            ["Galerkin"]
+        );
+    }
+
+    #[test]
+    fn test_match_spans() {
+        let src = r#"
+@article{smith2010,
+    author = {John Smith},
+    title = {Discontinuous Galerkin Methods},
+    abstract = {We analyse a hybridizable scheme for elliptic problems.},
+    year = {2010},
+}
+"#;
+        let refs = read_bibliography(Bibliography::parse(src).unwrap());
+        let matches = Query::parse("hybridizable").unwrap().match_spans(&refs[0]);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].field, QueryField::Abstract);
+        assert_eq!(matches[0].spans, vec![13..25]);
+        let fields = Query::parse("smith2010 OR smith")
+            .unwrap()
+            .match_spans(&refs[0])
+            .into_iter()
+            .map(|m| m.field)
+            .collect::<Vec<_>>();
+        assert_eq!(fields, [QueryField::Key, QueryField::Author]);
+    }
+
+    #[test]
+    fn test_snippet() {
+        let text = "We analyse a hybridizable scheme for elliptic problems.";
+        let (cut, span) = snippet(text, 13..25, 6);
+        assert_eq!(cut, "…yse a hybridizable schem…");
+        assert_eq!(&cut[span], "hybridizable");
+        assert_eq!(snippet(text, 0..2, 100).0, text);
+    }
+}
@@ -0,0 +1,539 @@
+//! An inverted token index for searching large libraries.
+//!
+//! The index keeps the lowercase field values of every reference and maps each token to the
+//! references containing it. A query first collects candidates from the token map, then checks
+//! the candidates with [`Query::matches`] semantics, so results are the same as a full scan.
+//!
+//! Tokens containing a word are found by a range query for prefixes and a trigram map of the
+//! vocabulary for infixes. Ranked searches also look up tokens sharing a trigram with the word
+//! that are within a small edit distance or contain the word as a subsequence, and score the
+//! candidates like [`crate::rank_references`]. A subsequence without a shared trigram, e.g.
+//! `mthds` for `methods`, is only found by a full scan.
+use crate::{
+    bib::Reference,
+    query::{Query, QueryField},
+    rank::{subsequence, tolerance},
+};
+use std::{
+    borrow::Cow,
+    cmp::Reverse,
+    collections::{BTreeMap, HashMap, HashSet},
+    ops::Bound,
+};
+
+/// Identifier of a reference in a [`SearchIndex`], stable until it is removed.
+///
+/// A replaced reference keeps its id, and the ids of removed references are reused.
+pub type RefId = usize;
+
+/// An incrementally updatable search index of references.
+#[derive(Debug, Clone, Default)]
+pub struct SearchIndex {
+    /// References by id, `None` once removed
+    references: Vec<Option<Reference>>,
+    /// Ids of removed references, reused by later insertions
+    free: Vec<RefId>,
+    /// Lowercase values by id and field
+    values: Vec<Vec<Vec<String>>>,
+    /// Ids by cite key
+    keys: HashMap<String, RefId>,
+    /// Sorted ids by token, for any field
+    tokens: BTreeMap<String, Vec<RefId>>,
+    /// Tokens by trigram
+    trigrams: HashMap<[char; 3], HashSet<String>>,
+}
+
+impl SearchIndex {
+    /// Build an index of the given references.
+    pub fn new(references: Vec<Reference>) -> Self {
+        let mut index = Self::default();
+        for reference in references {
+            index.insert(reference);
+        }
+        index
+    }
+
+    /// Number of references in the index.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Whether the index has no references.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Get a reference by id.
+    pub fn get(&self, id: RefId) -> Option<&Reference> {
+        self.references.get(id).and_then(Option::as_ref)
+    }
+
+    /// Get the id of a reference by its cite key.
+    pub fn id(&self, cite_key: &str) -> Option<RefId> {
+        self.keys.get(cite_key).copied()
+    }
+
+    /// Iterate over the references in id order.
+    pub fn iter(&self) -> impl Iterator<Item = (RefId, &Reference)> {
+        self.references
+            .iter()
+            .enumerate()
+            .filter_map(|(id, reference)| Some((id, reference.as_ref()?)))
+    }
+
+    /// Add a reference, replacing the one with the same cite key if any.
+    ///
+    /// A replaced reference keeps its id, so its place among ties does not move, and a new one
+    /// takes the id of a removed reference if any, so the index does not grow with updates.
+    pub fn insert(&mut self, reference: Reference) -> RefId {
+        let id = match self.unindex(&reference.cite_key) {
+            Some((id, _)) => id,
+            None => self.free.pop().unwrap_or(self.references.len()),
+        };
+        let values = QueryField::all()
+            .iter()
+            .map(|field| field.values(&reference))
+            .collect::<Vec<_>>();
+        for token in values.iter().flatten().flat_map(|value| tokenize(value)) {
+            let ids = match self.tokens.get_mut(token) {
+                Some(ids) => ids,
+                None => {
+                    for trigram in trigrams(token) {
+                        self.trigrams
+                            .entry(trigram)
+                            .or_default()
+                            .insert(token.to_string());
+                    }
+                    self.tokens.entry(token.to_string()).or_default()
+                }
+            };
+            if let Err(pos) = ids.binary_search(&id) {
+                ids.insert(pos, id);
+            }
+        }
+        self.keys.insert(reference.cite_key.clone(), id);
+        if id == self.references.len() {
+            self.references.push(Some(reference));
+            self.values.push(values);
+        } else {
+            self.references[id] = Some(reference);
+            self.values[id] = values;
+        }
+        id
+    }
+
+    /// Remove a reference by its cite key.
+    pub fn remove(&mut self, cite_key: &str) -> Option<Reference> {
+        let (id, reference) = self.unindex(cite_key)?;
+        self.free.push(id);
+        Some(reference)
+    }
+
+    /// Drop a reference and its tokens, leaving its slot empty.
+    fn unindex(&mut self, cite_key: &str) -> Option<(RefId, Reference)> {
+        let id = self.keys.remove(cite_key)?;
+        let values = std::mem::take(&mut self.values[id]);
+        for token in values.iter().flatten().flat_map(|value| tokenize(value)) {
+            let Some(ids) = self.tokens.get_mut(token) else {
+                continue;
+            };
+            if let Ok(pos) = ids.binary_search(&id) {
+                ids.remove(pos);
+            }
+            if ids.is_empty() {
+                self.tokens.remove(token);
+                for trigram in trigrams(token) {
+                    if let Some(tokens) = self.trigrams.get_mut(&trigram) {
+                        tokens.remove(token);
+                        if tokens.is_empty() {
+                            self.trigrams.remove(&trigram);
+                        }
+                    }
+                }
+            }
+        }
+        Some((id, self.references[id].take()?))
+    }
+
+    /// Lowercase values of a field of a reference.
+    fn values(&self, id: RefId, field: QueryField) -> Cow<'_, [String]> {
+        Cow::Borrowed(&self.values[id][field as usize])
+    }
+
+    /// Search for references matching the query, in id order.
+    ///
+    /// See [`Query`] for the syntax, an invalid query is searched as plain text.
+    pub fn search(&self, query: &str) -> Vec<RefId> {
+        let query = Query::parse_or_plain(query, QueryField::defaults());
+        self.query(&query)
+    }
+
+    /// Search for references matching a parsed query, in id order.
+    pub fn query(&self, query: &Query) -> Vec<RefId> {
+        self.candidates(query, false)
+            .into_iter()
+            .filter(|&id| {
+                let reference = self.references[id].as_ref().expect("candidates exist");
+                query.matches_with(reference, &|field| self.values(id, field))
+            })
+            .collect()
+    }
+
+    /// Search for references matching the query, best match first.
+    ///
+    /// See [`crate::rank_references`] for the ranking. Exact hits and references with a word
+    /// within a small edit distance of a term, or containing it as a subsequence, are ranked
+    /// together.
+    pub fn rank(&self, query: &str) -> Vec<RefId> {
+        self.rank_boosted(query, |_| 0)
+    }
+
+    /// Search for references matching the query, best match first, looking up unqualified
+    /// terms in `fields`.
+    pub fn rank_in(&self, query: &str, fields: &[QueryField]) -> Vec<RefId> {
+        self.rank_by(query, fields, |_| 0)
+    }
+
+    /// Search for references matching the query, best match first, adding `boost` to the score
+    /// of each match, e.g., from [`crate::UsageLog::boosts`].
+    pub fn rank_boosted(&self, query: &str, boost: impl Fn(&Reference) -> u32) -> Vec<RefId> {
+        self.rank_by(query, QueryField::defaults(), boost)
+    }
+
+    fn rank_by(
+        &self,
+        query: &str,
+        fields: &[QueryField],
+        boost: impl Fn(&Reference) -> u32,
+    ) -> Vec<RefId> {
+        let query = Query::parse_or_plain(query, fields);
+        let mut scored = self
+            .candidates(&query, true)
+            .into_iter()
+            .filter_map(|id| {
+                let reference = self.references[id].as_ref()?;
+                let score = query.score_with(reference, &|field| self.values(id, field))?;
+                Some((score + boost(reference), id))
+            })
+            .collect::<Vec<_>>();
+        scored.sort_by_key(|&(score, id)| (Reverse(score), id));
+        scored.into_iter().map(|(_, id)| id).collect()
+    }
+
+    /// Search for references matching the query, in id order.
+    pub fn search_references(&self, query: &str) -> Vec<&Reference> {
+        self.search(query)
+            .into_iter()
+            .filter_map(|id| self.get(id))
+            .collect()
+    }
+
+    /// Search for references matching the query, best match first.
+    pub fn rank_references(&self, query: &str) -> Vec<&Reference> {
+        self.rank(query)
+            .into_iter()
+            .filter_map(|id| self.get(id))
+            .collect()
+    }
+
+    /// Search for references matching the query, best match first, with boosted scores.
+    pub fn rank_references_boosted(
+        &self,
+        query: &str,
+        boost: impl Fn(&Reference) -> u32,
+    ) -> Vec<&Reference> {
+        self.rank_boosted(query, boost)
+            .into_iter()
+            .filter_map(|id| self.get(id))
+            .collect()
+    }
+
+    /// All ids of references in the index, sorted.
+    fn all(&self) -> Vec<RefId> {
+        self.iter().map(|(id, _)| id).collect()
+    }
+
+    /// Sorted ids of references that may match the query, including typos if `fuzzy`.
+    fn candidates(&self, query: &Query, fuzzy: bool) -> Vec<RefId> {
+        match query {
+            Query::Term { text, .. } if !text.contains('*') => {
+                let mut candidates = None::<Vec<RefId>>;
+                for word in tokenize(text) {
+                    let mut ids = self.containing(word);
+                    if fuzzy {
+                        ids = union(&ids, &self.similar(word));
+                    }
+                    candidates = Some(match candidates {
+                        Some(candidates) => intersection(&candidates, &ids),
+                        None => ids,
+                    });
+                }
+                candidates.unwrap_or_else(|| self.all())
+            }
+            Query::And(lhs, rhs) => {
+                intersection(&self.candidates(lhs, fuzzy), &self.candidates(rhs, fuzzy))
+            }
+            Query::Or(lhs, rhs) => {
+                union(&self.candidates(lhs, fuzzy), &self.candidates(rhs, fuzzy))
+            }
+            _ => self.all(),
+        }
+    }
+
+    /// Sorted ids of references with a token containing the word.
+    fn containing(&self, word: &str) -> Vec<RefId> {
+        let prefixed = self
+            .tokens
+            .range::<str, _>((Bound::Included(word), Bound::Unbounded))
+            .take_while(|(token, _)| token.starts_with(word))
+            .map(|(token, _)| token.as_str());
+        let tokens = match rarest_trigram(self, word) {
+            Some(tokens) => prefixed
+                .chain(
+                    tokens
+                        .into_iter()
+                        .filter(|token| !token.starts_with(word) && token.contains(word)),
+                )
+                .collect::<Vec<_>>(),
+            // Words shorter than a trigram scan the vocabulary.
+            None => self
+                .tokens
+                .keys()
+                .map(String::as_str)
+                .filter(|token| token.contains(word))
+                .collect(),
+        };
+        self.ids_of(&tokens)
+    }
+
+    /// Sorted ids of references with a token, or a prefix of a token, within the edit distance
+    /// tolerance of the word, or a token containing the word as a subsequence.
+    ///
+    /// Only tokens sharing a trigram with the word are checked, so a keystroke does not scan the
+    /// whole vocabulary.
+    fn similar(&self, word: &str) -> Vec<RefId> {
+        let len = word.chars().count();
+        if len < 3 {
+            return Vec::new();
+        }
+        let tolerance = tolerance(len);
+        let tokens = trigrams(word)
+            .filter_map(|trigram| self.trigrams.get(&trigram))
+            .flatten()
+            .map(String::as_str)
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .filter(|token| {
+                let count = token.chars().count();
+                if count + tolerance < len {
+                    return false;
+                }
+                let prefix = token.chars().take(len).collect::<String>();
+                strsim::osa_distance(word, &prefix) <= tolerance
+                    || strsim::osa_distance(word, token) <= tolerance
+                    || (count >= len && subsequence(token, word).is_some())
+            })
+            .collect::<Vec<_>>();
+        self.ids_of(&tokens)
+    }
+
+    /// Sorted ids of references with any of the tokens.
+    fn ids_of(&self, tokens: &[&str]) -> Vec<RefId> {
+        let mut ids = tokens
+            .iter()
+            .filter_map(|token| self.tokens.get(*token))
+            .flatten()
+            .copied()
+            .collect::<Vec<_>>();
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+}
+
+/// Tokens with the least common trigram of the word, or `None` if the word is too short.
+fn rarest_trigram<'a>(index: &'a SearchIndex, word: &str) -> Option<Vec<&'a str>> {
+    let trigrams = trigrams(word).collect::<Vec<_>>();
+    if trigrams.is_empty() {
+        return None;
+    }
+    let tokens = trigrams
+        .iter()
+        .map(|trigram| index.trigrams.get(trigram))
+        .min_by_key(|tokens| tokens.map_or(0, HashSet::len))
+        .flatten();
+    Some(tokens.into_iter().flatten().map(String::as_str).collect())
+}
+
+/// Split a lowercase value into alphanumeric tokens.
+fn tokenize(value: &str) -> impl Iterator<Item = &str> {
+    value
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|token| !token.is_empty())
+}
+
+/// Trigrams of a token.
+fn trigrams(token: &str) -> impl Iterator<Item = [char; 3]> {
+    let chars = token.chars().collect::<Vec<_>>();
+    (0..chars.len().saturating_sub(2)).map(move |i| [chars[i], chars[i + 1], chars[i + 2]])
+}
+
+/// Intersection of sorted ids.
+fn intersection(a: &[RefId], b: &[RefId]) -> Vec<RefId> {
+    let (mut i, mut j) = (0, 0);
+    let mut out = Vec::with_capacity(a.len().min(b.len()));
+    while i < a.len() && j < b.len() {
+        match a[i].cmp(&b[j]) {
+            std::cmp::Ordering::Less => i += 1,
+            std::cmp::Ordering::Greater => j += 1,
+            std::cmp::Ordering::Equal => {
+                out.push(a[i]);
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    out
+}
+
+/// Union of sorted ids.
+fn union(a: &[RefId], b: &[RefId]) -> Vec<RefId> {
+    let mut out = a.iter().chain(b).copied().collect::<Vec<_>>();
+    out.sort_unstable();
+    out.dedup();
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{search_references, utils::read_bibliography};
+    use biblatex::Bibliography;
+
+    const SRC: &str = r#"
+@article{knuth1984,
+    author = {Donald E. Knuth},
+    title = {Literate Programming},
+    journal = {The Computer Journal},
+    year = {1984},
+}
+@book{knuth1997,
+    author = {Donald E. Knuth},
+    title = {The Art of Computer Programming},
+    publisher = {Addison-Wesley},
+    year = {1997},
+}
+@phdthesis{lamport1972,
+    author = {Leslie Lamport},
+    title = {The Analytic Cauchy Problem with Singular Data},
+    school = {Brandeis University},
+    year = {1972},
+}
+"#;
+
+    fn references() -> Vec<Reference> {
+        read_bibliography(Bibliography::parse(SRC).unwrap())
+    }
+
+    #[test]
+    fn test_same_as_scan() {
+        let refs = references();
+        let index = SearchIndex::new(refs.clone());
+        for query in [
+            "knuth",
+            "gram",
+            "\"computer programming\"",
+            "author:knuth year:1990..",
+            "knuth OR lamport",
+            "NOT knuth",
+            "type:thesis",
+            "",
+            "(unclosed",
+        ] {
+            let expected = search_references(&refs, query);
+            let found = index.search_references(query);
+            assert_eq!(found.len(), expected.len(), "{query}");
+            assert!(found.iter().zip(&expected).all(|(a, b)| *a == b), "{query}");
+        }
+    }
+
+    #[test]
+    fn test_update() {
+        let refs = references();
+        let mut index = SearchIndex::new(refs.clone());
+        assert_eq!(index.len(), 3);
+        let removed = index.remove("lamport1972").unwrap();
+        assert!(index.search("lamport").is_empty());
+        assert_eq!(index.len(), 2);
+        let id = index.insert(removed);
+        assert_eq!(index.search("lamport"), [id]);
+        // Replacing a reference drops its old tokens and keeps its id.
+        let mut changed = refs[0].clone();
+        changed.title = None;
+        assert_eq!(index.insert(changed), 0);
+        assert!(index.search("literate").is_empty());
+        assert_eq!(index.search("knuth"), [0, 1]);
+        assert_eq!(index.len(), 3);
+        // Updates reuse the slots of removed references.
+        for _ in 0..10 {
+            index.insert(refs[1].clone());
+            let removed = index.remove("knuth1984").unwrap();
+            index.insert(removed);
+        }
+        assert_eq!(index.references.len(), 3);
+        assert_eq!(index.search("knuth"), [0, 1]);
+    }
+
+    #[test]
+    fn test_rank() {
+        let index = SearchIndex::new(references());
+        let keys = |query: &str| {
+            index
+                .rank_references(query)
+                .into_iter()
+                .map(|r| r.cite_key.as_str())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(keys("lamprot"), ["lamport1972"]);
+        assert_eq!(keys("knuth1997")[0], "knuth1997");
+    }
+
+    #[test]
+    fn test_rank_same_as_scan() {
+        let refs = read_bibliography(
+            Bibliography::parse(
+                r#"
+@article{exact, title = {Galerkin Methods}, year = {2010}}
+@article{typo, title = {Galerkn Methods}, year = {2011}}
+@article{other, title = {Finite Element Methods}, year = {2012}}
+"#,
+            )
+            .unwrap(),
+        );
+        let index = SearchIndex::new(refs.clone());
+        for query in [
+            "galerkin",
+            "glrkin",
+            "mthods",
+            "finte",
+            "galerkin year:2011",
+            "element OR galerkn",
+        ] {
+            let expected = crate::rank_references(&refs, query)
+                .into_iter()
+                .map(|r| r.cite_key)
+                .collect::<Vec<_>>();
+            let found = index
+                .rank_references(query)
+                .into_iter()
+                .map(|r| r.cite_key.clone())
+                .collect::<Vec<_>>();
+            assert_eq!(found, expected, "{query}");
+        }
+        // Subsequences without a shared trigram are left to full scans.
+        assert!(index.rank("mthds").is_empty());
+        let title = index.rank_in("methods", &[QueryField::Title]);
+        assert_eq!(title.len(), 3);
+        assert!(index.rank_in("2010", &[QueryField::Title]).is_empty());
+    }
+}
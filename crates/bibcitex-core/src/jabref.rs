@@ -0,0 +1,608 @@
+//! JabRef groups.
+//!
+//! JabRef stores its group tree in a `@Comment{jabref-meta: grouping: …}` block, one group per
+//! line as `<level> <type>:<fields>;`, and the members of static groups in the `groups` field
+//! of entries. Membership edits only touch the `groups` field, so the file stays readable by
+//! JabRef.
+use crate::{
+    BibSource, Encoding, Result,
+    bib::Reference,
+    edit,
+    query::{Query, QueryField},
+    tag::{TagEdit, edit_list_field, same_tag},
+};
+use regex::{Regex, RegexBuilder};
+use std::{collections::HashMap, path::Path};
+
+/// Separator of group names in the `groups` field.
+pub const JABREF_GROUP_SEPARATOR: &str = ",";
+
+/// How a group relates to its parent.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum GroupContext {
+    /// Only the group's own entries
+    #[default]
+    Independent,
+    /// Entries of the group that are also in the parent
+    Refining,
+    /// Entries of the group and of its subgroups
+    Including,
+}
+
+/// The kind of a group and what it matches.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GroupKind {
+    /// Every entry
+    AllEntries,
+    /// Entries listing the group in their `groups` field, or listed in the group by older
+    /// JabRef versions
+    Explicit { keys: Vec<String> },
+    /// Entries with a field containing a word or pattern
+    Keyword {
+        field: String,
+        expression: String,
+        case_sensitive: bool,
+        regex: bool,
+    },
+    /// Entries matching a JabRef search expression
+    Search { expression: String, regex: bool },
+    /// A group type BibCiTeX does not evaluate, matching no entry
+    Other(String),
+}
+
+/// A JabRef group and its subgroups.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JabRefGroup {
+    pub name: String,
+    pub kind: GroupKind,
+    pub context: GroupContext,
+    /// Whether the group is expanded in JabRef
+    pub expanded: bool,
+    pub children: Vec<JabRefGroup>,
+}
+
+impl JabRefGroup {
+    /// Check whether the group is static, i.e., its members are edited by hand.
+    pub fn is_explicit(&self) -> bool {
+        matches!(self.kind, GroupKind::Explicit { .. })
+    }
+
+    /// Find a group in the tree by name.
+    pub fn find(&self, name: &str) -> Option<&Self> {
+        if self.name == name {
+            return Some(self);
+        }
+        self.children.iter().find_map(|child| child.find(name))
+    }
+
+    /// Number of groups in the tree, including this one.
+    pub fn count(&self) -> usize {
+        1 + self.children.iter().map(Self::count).sum::<usize>()
+    }
+
+    /// References in the group named `name`, following the contexts of the groups on its path.
+    pub fn members(&self, name: &str, references: &[Reference]) -> Vec<Reference> {
+        let matcher = Matcher::new(self);
+        let Some(path) = matcher.path(name) else {
+            return Vec::new();
+        };
+        references
+            .iter()
+            .filter(|reference| {
+                path.iter()
+                    .fold(true, |parent, group| group.contains(reference, parent))
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Number of members of every group in the tree by name, see [`Self::members`].
+    pub fn member_counts(&self, references: &[Reference]) -> HashMap<String, usize> {
+        let matcher = Matcher::new(self);
+        let mut counts = HashMap::new();
+        for reference in references {
+            matcher.count(reference, true, &mut counts);
+        }
+        counts
+    }
+}
+
+/// The expression of a group, compiled once to match many references.
+enum Pattern {
+    /// A regular expression, `None` if it is invalid and matches no entry
+    Regex(Option<Regex>),
+    /// A search expression
+    Query(Query),
+    /// A lowercase keyword matched ignoring case
+    Lowercase(String),
+    /// Nothing to compile
+    Plain,
+}
+
+/// A group tree with the expressions of the groups compiled.
+struct Matcher<'a> {
+    group: &'a JabRefGroup,
+    pattern: Pattern,
+    children: Vec<Matcher<'a>>,
+}
+
+impl<'a> Matcher<'a> {
+    fn new(group: &'a JabRefGroup) -> Self {
+        let regex = |expression: &str, case_sensitive: bool| {
+            Pattern::Regex(
+                RegexBuilder::new(expression)
+                    .case_insensitive(!case_sensitive)
+                    .build()
+                    .ok(),
+            )
+        };
+        let pattern = match &group.kind {
+            GroupKind::Keyword {
+                expression,
+                case_sensitive,
+                regex: true,
+                ..
+            } => regex(expression, *case_sensitive),
+            GroupKind::Keyword {
+                expression,
+                case_sensitive: false,
+                ..
+            } => Pattern::Lowercase(expression.to_lowercase()),
+            GroupKind::Search {
+                expression,
+                regex: true,
+            } => regex(expression, false),
+            GroupKind::Search { expression, .. } => Pattern::Query(search_query(expression)),
+            _ => Pattern::Plain,
+        };
+        Self {
+            group,
+            pattern,
+            children: group.children.iter().map(Self::new).collect(),
+        }
+    }
+
+    /// Groups from this one down to the group named `name`.
+    fn path(&self, name: &str) -> Option<Vec<&Self>> {
+        if self.group.name == name {
+            return Some(vec![self]);
+        }
+        self.children.iter().find_map(|child| {
+            let mut path = child.path(name)?;
+            path.insert(0, self);
+            Some(path)
+        })
+    }
+
+    /// Count a reference in the groups of the tree containing it, given whether it is in the
+    /// parent group.
+    fn count(&self, reference: &Reference, parent: bool, counts: &mut HashMap<String, usize>) {
+        let contained = self.contains(reference, parent);
+        let count = counts.entry(self.group.name.clone()).or_default();
+        *count += usize::from(contained);
+        for child in &self.children {
+            child.count(reference, contained, counts);
+        }
+    }
+
+    /// Check whether a reference is in the group, given whether it is in the parent group.
+    fn contains(&self, reference: &Reference, parent: bool) -> bool {
+        let own = self.matches(reference);
+        match self.group.context {
+            GroupContext::Independent => own,
+            GroupContext::Refining => parent && own,
+            GroupContext::Including => {
+                own || self
+                    .children
+                    .iter()
+                    .any(|child| child.contains(reference, own))
+            }
+        }
+    }
+
+    /// Check whether a reference matches the group itself, ignoring parents and subgroups.
+    fn matches(&self, reference: &Reference) -> bool {
+        match (&self.group.kind, &self.pattern) {
+            (GroupKind::AllEntries, _) => true,
+            (GroupKind::Explicit { keys }, _) => {
+                keys.contains(&reference.cite_key)
+                    || reference
+                        .groups
+                        .iter()
+                        .flatten()
+                        .any(|group| group == &self.group.name)
+            }
+            (GroupKind::Keyword { field, .. }, Pattern::Regex(re)) => {
+                re.as_ref().is_some_and(|re| {
+                    field_values(reference, field)
+                        .iter()
+                        .any(|value| re.is_match(value))
+                })
+            }
+            (
+                GroupKind::Keyword {
+                    field,
+                    expression,
+                    case_sensitive,
+                    ..
+                },
+                pattern,
+            ) => {
+                let values = field_values(reference, field);
+                if field.eq_ignore_ascii_case("keywords") || field.eq_ignore_ascii_case("groups") {
+                    values.iter().any(|value| {
+                        if *case_sensitive {
+                            value.trim() == expression.trim()
+                        } else {
+                            same_tag(value, expression)
+                        }
+                    })
+                } else if let Pattern::Lowercase(expression) = pattern {
+                    values
+                        .iter()
+                        .any(|value| value.to_lowercase().contains(expression))
+                } else {
+                    values
+                        .iter()
+                        .any(|value| value.contains(expression.as_str()))
+                }
+            }
+            (GroupKind::Search { .. }, Pattern::Regex(re)) => re.as_ref().is_some_and(|re| {
+                QueryField::defaults()
+                    .iter()
+                    .any(|&field| field.raw_values(reference).iter().any(|v| re.is_match(v)))
+            }),
+            (GroupKind::Search { .. }, Pattern::Query(query)) => query.matches(reference),
+            _ => false,
+        }
+    }
+}
+
+/// Values of a field by its BibTeX name.
+fn field_values(reference: &Reference, field: &str) -> Vec<String> {
+    match field.to_lowercase().as_str() {
+        "keywords" => reference.keywords.clone().unwrap_or_default(),
+        "groups" => reference.groups.clone().unwrap_or_default(),
+        field => field
+            .parse::<QueryField>()
+            .map(|field| field.raw_values(reference))
+            .unwrap_or_default(),
+    }
+}
+
+/// Translate a JabRef search expression, e.g., `author=smith and year>=2020`, to a [`Query`].
+///
+/// Expressions that do not translate are searched as plain text.
+fn search_query(expression: &str) -> Query {
+    let mut terms = Vec::new();
+    for token in tokens(expression) {
+        let term = match token.to_lowercase().as_str() {
+            "and" | "or" | "not" => token.to_uppercase(),
+            _ => translate_term(&token),
+        };
+        terms.push(term);
+    }
+    Query::parse_or_plain(&terms.join(" "), QueryField::defaults())
+}
+
+fn translate_term(token: &str) -> String {
+    for op in [">=", "<=", "!=", "==", "=~", "=", "~", ">", "<"] {
+        let Some((field, value)) = token.split_once(op) else {
+            continue;
+        };
+        if field.is_empty() || !field.chars().all(|c| c.is_ascii_alphanumeric()) {
+            break;
+        }
+        let field = if field.eq_ignore_ascii_case("any") || field.eq_ignore_ascii_case("anyfield") {
+            None
+        } else {
+            Some(field)
+        };
+        let value = value.trim_matches('"');
+        let quoted = if value.contains(char::is_whitespace) {
+            format!("\"{value}\"")
+        } else {
+            value.to_string()
+        };
+        let year = |value: &str| value.parse::<i32>().ok();
+        return match (field, op) {
+            (Some(field), ">=") => format!("{field}:{value}.."),
+            (Some(field), "<=") => format!("{field}:..{value}"),
+            (Some(field), ">") => {
+                year(value).map_or(token.into(), |y| format!("{field}:{}..", y + 1))
+            }
+            (Some(field), "<") => {
+                year(value).map_or(token.into(), |y| format!("{field}:..{}", y - 1))
+            }
+            (Some(field), "!=") => format!("NOT {field}:{quoted}"),
+            (None, "!=") => format!("NOT {quoted}"),
+            (Some(field), _) => format!("{field}:{quoted}"),
+            (None, _) => quoted,
+        };
+    }
+    token.to_string()
+}
+
+/// Split on whitespace outside double quotes, keeping the quotes.
+fn tokens(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for c in text.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                current.push(c);
+            }
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+/// Parse the JabRef group tree of a `.bib` source, `None` if it has no groups.
+///
+/// The tree ends at the brace closing its `@Comment` block, so groups may contain braces, e.g.,
+/// in search expressions.
+pub fn parse_jabref_groups(source: &str) -> Option<JabRefGroup> {
+    let marker = "jabref-meta: grouping:";
+    let content = edit::source_comments(source)
+        .into_iter()
+        .find_map(|comment| {
+            let comment = comment.trim_start();
+            comment
+                .get(..marker.len())
+                .is_some_and(|start| start.eq_ignore_ascii_case(marker))
+                .then(|| comment[marker.len()..].replace(['\r', '\n'], ""))
+        })?;
+    let mut stack: Vec<(usize, JabRefGroup)> = Vec::new();
+    let mut root = None;
+    for line in split_escaped(&content) {
+        let line = line.trim();
+        let Some((level, group)) = line.split_once(' ') else {
+            continue;
+        };
+        let (Ok(level), Some(group)) = (level.parse::<usize>(), parse_group(group)) else {
+            continue;
+        };
+        // Close the groups at the same or a deeper level
+        while stack.last().is_some_and(|(l, _)| *l >= level) {
+            let (_, done) = stack.pop().unwrap();
+            match stack.last_mut() {
+                Some((_, parent)) => parent.children.push(done),
+                None => root = Some(done),
+            }
+        }
+        stack.push((level, group));
+    }
+    while let Some((_, done)) = stack.pop() {
+        match stack.last_mut() {
+            Some((_, parent)) => parent.children.push(done),
+            None => root = Some(done),
+        }
+    }
+    root
+}
+
+/// Parse a group like `KeywordGroup:SPDE;0;keywords;spde;0;0;1;;;;`, with the escapes of the
+/// file removed.
+fn parse_group(text: &str) -> Option<JabRefGroup> {
+    let (kind, rest) = text.split_once(':')?;
+    let fields = split_escaped(rest);
+    let field = |i: usize| fields.get(i).map(String::as_str).unwrap_or_default();
+    let flag = |i: usize| field(i) == "1";
+    let context = match field(1) {
+        "1" => GroupContext::Refining,
+        "2" => GroupContext::Including,
+        _ => GroupContext::Independent,
+    };
+    let (kind, expanded) = match kind {
+        "AllEntriesGroup" => (GroupKind::AllEntries, true),
+        "StaticGroup" => (GroupKind::Explicit { keys: Vec::new() }, flag(2)),
+        "ExplicitGroup" => {
+            let keys = fields
+                .iter()
+                .skip(2)
+                .filter(|key| !key.is_empty())
+                .cloned()
+                .collect();
+            (GroupKind::Explicit { keys }, true)
+        }
+        "KeywordGroup" => (
+            GroupKind::Keyword {
+                field: field(2).to_string(),
+                expression: field(3).to_string(),
+                case_sensitive: flag(4),
+                regex: flag(5),
+            },
+            flag(6),
+        ),
+        "SearchGroup" => (
+            GroupKind::Search {
+                expression: field(2).to_string(),
+                regex: flag(4),
+            },
+            flag(5),
+        ),
+        other => (GroupKind::Other(other.to_string()), true),
+    };
+    let name = match kind {
+        GroupKind::AllEntries => "All Entries".to_string(),
+        _ => field(0).to_string(),
+    };
+    Some(JabRefGroup {
+        name,
+        kind,
+        context,
+        expanded,
+        children: Vec::new(),
+    })
+}
+
+/// Split on `;` not escaped by `\`, and remove the escapes.
+fn split_escaped(text: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => current.extend(chars.next()),
+            ';' => parts.push(std::mem::take(&mut current)),
+            c => current.push(c),
+        }
+    }
+    if !current.trim().is_empty() {
+        parts.push(current);
+    }
+    parts
+}
+
+/// Add entries to or remove them from a static group in the source of a `.bib` file.
+///
+/// Returns the edited source and the number of changed entries.
+pub fn edit_group_members(source: &str, keys: &[&str], group: &str, add: bool) -> (String, usize) {
+    let edit = if add {
+        TagEdit::Add(group.to_string())
+    } else {
+        TagEdit::Remove(group.to_string())
+    };
+    edit_list_field(source, keys, "groups", &edit, JABREF_GROUP_SEPARATOR)
+}
+
+/// Add entries to or remove them from a static group in a `.bib` file.
+///
+/// The file is only written if an entry changed. Returns the number of changed entries.
+pub fn edit_group_members_in_file(
+    path: impl AsRef<Path>,
+    encoding: Option<&'static Encoding>,
+    keys: &[&str],
+    group: &str,
+    add: bool,
+) -> Result<usize> {
+    let path = path.as_ref();
+    let source = BibSource::read(path, encoding)?;
+    let (output, changed) = edit_group_members(&source.text, keys, group, add);
+    if changed > 0 {
+        source.write(path, &output)?;
+    }
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::read_bibliography;
+    use biblatex::Bibliography;
+
+    const SRC: &str = r#"@article{smith2010,
+  author = {John Smith},
+  title = {Stochastic PDEs},
+  keywords = {SPDE, probability},
+  groups = {Me},
+  year = {2010},
+}
+
+@article{lee2021,
+  author = {Ann Lee},
+  title = {Finite Elements},
+  keywords = {FEM},
+  year = {2021},
+}
+
+@Comment{jabref-meta: databaseType:bibtex;}
+
+@Comment{jabref-meta: grouping:
+0 AllEntriesGroup:;
+1 StaticGroup:Me\;0\;1\;0x8a8a8aff\;\;\;;
+1 KeywordGroup:SPDE\;0\;keywords\;spde\;0\;0\;1\;\;\;\;;
+2 SearchGroup:Recent\;1\;year>=2020\;0\;0\;1\;\;\;\;;
+1 SearchGroup:Smith or Lee\;2\;author=smith or author=lee\;0\;0\;0\;\;\;\;;
+2 StaticGroup:A\\\;B\;0\;1\;\;\;\;;
+}
+"#;
+
+    fn members(tree: &JabRefGroup, name: &str) -> Vec<String> {
+        let refs = read_bibliography(Bibliography::parse(SRC).unwrap());
+        tree.members(name, &refs)
+            .into_iter()
+            .map(|r| r.cite_key)
+            .collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        let tree = parse_jabref_groups(SRC).unwrap();
+        assert_eq!(tree.kind, GroupKind::AllEntries);
+        assert_eq!(tree.count(), 6);
+        let names = tree
+            .children
+            .iter()
+            .map(|g| g.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["Me", "SPDE", "Smith or Lee"]);
+        assert!(tree.children[0].is_explicit());
+        assert_eq!(tree.find("Recent").unwrap().context, GroupContext::Refining);
+        assert!(tree.find("A;B").is_some());
+        assert!(!tree.children[2].expanded);
+        assert!(parse_jabref_groups("@article{a, title = {A}}").is_none());
+
+        // Braces in a group do not end the tree
+        let braced = r#"@Comment{jabref-meta: grouping:
+0 AllEntriesGroup:;
+1 SearchGroup:Braced\;0\;title={PDE}\;0\;0\;1\;\;\;\;;
+1 StaticGroup:After\;0\;1\;\;\;\;;
+}"#;
+        let tree = parse_jabref_groups(braced).unwrap();
+        assert_eq!(tree.count(), 3);
+        assert!(tree.find("After").is_some());
+    }
+
+    #[test]
+    fn test_members() {
+        let tree = parse_jabref_groups(SRC).unwrap();
+        assert_eq!(members(&tree, "All Entries").len(), 2);
+        assert_eq!(members(&tree, "Me"), ["smith2010"]);
+        assert_eq!(members(&tree, "SPDE"), ["smith2010"]);
+        // Refines SPDE, which has no entry from 2020 on
+        assert!(members(&tree, "Recent").is_empty());
+        assert_eq!(members(&tree, "Smith or Lee"), ["smith2010", "lee2021"]);
+        assert!(members(&tree, "missing").is_empty());
+
+        let refs = read_bibliography(Bibliography::parse(SRC).unwrap());
+        let counts = tree.member_counts(&refs);
+        for name in ["All Entries", "Me", "SPDE", "Recent", "Smith or Lee", "A;B"] {
+            assert_eq!(counts[name], members(&tree, name).len(), "{name}");
+        }
+    }
+
+    #[test]
+    fn test_search_query() {
+        assert_eq!(
+            search_query("author=smith and year>2019"),
+            Query::parse("author:smith AND year:2020..").unwrap()
+        );
+        assert_eq!(
+            search_query(r#"title="finite elements""#),
+            Query::parse(r#"title:"finite elements""#).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_edit_members() {
+        let (added, n) = edit_group_members(SRC, &["smith2010", "lee2021"], "Me", true);
+        assert_eq!(n, 1);
+        assert!(added.contains("@article{lee2021,\n  groups = {Me},\n"));
+        let (removed, n) = edit_group_members(SRC, &["smith2010"], "Me", false);
+        assert_eq!(n, 1);
+        assert!(!removed.contains("groups = "));
+        assert!(removed.contains("jabref-meta: grouping:"));
+    }
+}
@@ -0,0 +1,498 @@
+//! Journal abbreviations.
+//!
+//! A [`JournalDatabase`] maps full journal names to their ISO 4 abbreviations, as used by
+//! MathSciNet, from a bundled list and the entries added by the user, see
+//! [`crate::Setting::journal_database`]. By the MathSciNet convention, `journal` holds the
+//! abbreviation and `fjournal` the full name: [`fill_journals`] completes either field in the
+//! source of a `.bib` file, [`apply_journal_style`] switches the names used in the output, and
+//! [`journal_report`] finds journal names written inconsistently.
+use crate::{BibSource, Encoding, Result, bib::Reference, edit};
+use fs_err as fs;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt::Display,
+    path::Path,
+    str::FromStr,
+    sync::LazyLock,
+};
+
+/// Abbreviations bundled with BibCiTeX, in the `full name;abbreviation` format of JabRef.
+static BUNDLED_ABBREVIATIONS: &str = include_str!("../journals/abbreviations.csv");
+
+static BUNDLED: LazyLock<Vec<JournalAbbreviation>> =
+    LazyLock::new(|| parse_abbreviations(BUNDLED_ABBREVIATIONS));
+
+/// A journal with its abbreviation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JournalAbbreviation {
+    pub full: String,
+    pub abbreviation: String,
+}
+
+impl JournalAbbreviation {
+    pub fn new(full: &str, abbreviation: &str) -> Self {
+        Self {
+            full: full.trim().to_string(),
+            abbreviation: abbreviation.trim().to_string(),
+        }
+    }
+
+    /// The name of the journal in the given style.
+    pub fn name(&self, style: JournalStyle) -> &str {
+        match style {
+            JournalStyle::Full => &self.full,
+            JournalStyle::Abbreviated => &self.abbreviation,
+        }
+    }
+}
+
+/// How journal names are written in the output of a library.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JournalStyle {
+    /// Full names, e.g., `SIAM Journal on Numerical Analysis`
+    Full,
+    /// Abbreviations, e.g., `SIAM J. Numer. Anal.`
+    Abbreviated,
+}
+
+impl JournalStyle {
+    pub fn all() -> &'static [Self] {
+        &[Self::Full, Self::Abbreviated]
+    }
+}
+
+impl Display for JournalStyle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Full => write!(f, "full"),
+            Self::Abbreviated => write!(f, "abbreviated"),
+        }
+    }
+}
+
+impl FromStr for JournalStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "full" => Ok(Self::Full),
+            "abbreviated" => Ok(Self::Abbreviated),
+            _ => Err(format!("unknown journal style: {s}")),
+        }
+    }
+}
+
+/// Parse `full name;abbreviation` lines, skipping empty lines and `#` comments.
+pub fn parse_abbreviations(text: &str) -> Vec<JournalAbbreviation> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let (full, abbreviation) = line.split_once(';')?;
+            let abbreviation = abbreviation.split(';').next().unwrap_or_default();
+            (!full.trim().is_empty() && !abbreviation.trim().is_empty())
+                .then(|| JournalAbbreviation::new(full, abbreviation))
+        })
+        .collect()
+}
+
+/// Journal names compared ignoring case, braces, punctuation and a leading `The`.
+fn normalize(name: &str) -> String {
+    let name = name
+        .chars()
+        .filter(|c| !matches!(c, '{' | '}' | '\\' | '.' | ',' | ':'))
+        .collect::<String>()
+        .to_lowercase();
+    let words = name.split_whitespace().collect::<Vec<_>>();
+    match words.split_first() {
+        Some((&"the", rest)) if !rest.is_empty() => rest.join(" "),
+        _ => words.join(" "),
+    }
+}
+
+/// Full journal names and abbreviations, looked up by either.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct JournalDatabase {
+    entries: Vec<JournalAbbreviation>,
+    index: HashMap<String, usize>,
+}
+
+impl JournalDatabase {
+    /// The bundled abbreviations.
+    pub fn bundled() -> Self {
+        Self::from_entries(BUNDLED.iter().cloned())
+    }
+
+    /// A database of the given entries, later ones taking precedence.
+    pub fn from_entries(entries: impl IntoIterator<Item = JournalAbbreviation>) -> Self {
+        let mut database = Self::default();
+        database.extend(entries);
+        database
+    }
+
+    /// Add entries, replacing those with the same full name or abbreviation.
+    pub fn extend(&mut self, entries: impl IntoIterator<Item = JournalAbbreviation>) {
+        // Replaced positions with the positions replacing them, so that the index is updated
+        // once rather than per entry
+        let mut replaced = HashMap::new();
+        let latest = |replaced: &HashMap<usize, usize>, mut position| {
+            while let Some(&next) = replaced.get(&position) {
+                position = next;
+            }
+            position
+        };
+        for entry in entries {
+            let position = self.entries.len();
+            for name in [&entry.full, &entry.abbreviation] {
+                if let Some(old) = self.index.insert(normalize(name), position) {
+                    let old = latest(&replaced, old);
+                    if old != position {
+                        replaced.insert(old, position);
+                    }
+                }
+            }
+            self.entries.push(entry);
+        }
+        // Names of a replaced journal still find it
+        if !replaced.is_empty() {
+            for position in self.index.values_mut() {
+                *position = latest(&replaced, *position);
+            }
+        }
+    }
+
+    /// Read a file of `full name;abbreviation` lines.
+    pub fn read_file(&mut self, path: impl AsRef<Path>) -> Result<usize> {
+        let entries = parse_abbreviations(&fs::read_to_string(path)?);
+        let count = entries.len();
+        self.extend(entries);
+        Ok(count)
+    }
+
+    /// The number of journals.
+    pub fn len(&self) -> usize {
+        self.index.values().collect::<HashSet<_>>().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// The journal with the given full name or abbreviation.
+    pub fn lookup(&self, name: &str) -> Option<&JournalAbbreviation> {
+        self.index
+            .get(&normalize(name))
+            .map(|&position| &self.entries[position])
+    }
+
+    /// The full name of a journal given by either name.
+    pub fn full_name(&self, name: &str) -> Option<&str> {
+        self.lookup(name).map(|entry| entry.full.as_str())
+    }
+
+    /// The abbreviation of a journal given by either name.
+    pub fn abbreviate(&self, name: &str) -> Option<&str> {
+        self.lookup(name).map(|entry| entry.abbreviation.as_str())
+    }
+}
+
+/// Fill a missing `fjournal` or abbreviated `journal` in the source of a `.bib` file.
+///
+/// An entry with a known `journal` and no `fjournal` gets the full name in `fjournal`, and the
+/// abbreviation in `journal` if it held the full name. An entry with a known `fjournal` and no
+/// `journal` gets the abbreviation. Returns the edited source and the number of changed entries.
+pub fn fill_journals(source: &str, database: &JournalDatabase) -> (String, usize) {
+    let mut replacements = Vec::new();
+    let mut changed = 0;
+    for entry in edit::source_entries(source) {
+        let text = |name| entry.field(name).map(|field| field.text(source));
+        let edits = match (text("journal"), text("fjournal")) {
+            (Some(journal), None) => database.lookup(journal).map(|known| {
+                let abbreviate = normalize(journal) == normalize(&known.full)
+                    && normalize(&known.full) != normalize(&known.abbreviation);
+                let mut edits = vec![("fjournal", known.full.as_str())];
+                if abbreviate {
+                    edits.push(("journal", known.abbreviation.as_str()));
+                }
+                edits
+            }),
+            (None, Some(full_journal)) => database
+                .lookup(full_journal)
+                .map(|known| vec![("journal", known.abbreviation.as_str())]),
+            _ => None,
+        };
+        let Some(edits) = edits else {
+            continue;
+        };
+        replacements.extend(edits.into_iter().filter_map(|(name, value)| {
+            edit::field_replacement(source, &entry, name, Some(value))
+        }));
+        changed += 1;
+    }
+    (edit::apply_replacements(source, replacements), changed)
+}
+
+/// Fill missing `fjournal` and abbreviated `journal` fields in a `.bib` file, see
+/// [`fill_journals`].
+///
+/// The file is only written if an entry changed. Returns the number of changed entries.
+pub fn fill_journals_in_file(
+    path: impl AsRef<Path>,
+    encoding: Option<&'static Encoding>,
+    database: &JournalDatabase,
+) -> Result<usize> {
+    let path = path.as_ref();
+    let source = BibSource::read(path, encoding)?;
+    let (output, changed) = fill_journals(&source.text, database);
+    if changed > 0 {
+        source.write(path, &output)?;
+    }
+    Ok(changed)
+}
+
+/// Write the journal names of known journals in the given style, in both the fields and the
+/// source of the references, so that every output format uses them.
+///
+/// The `.bib` file is left as it is. Returns the number of changed references.
+pub fn apply_journal_style(
+    references: &mut [Reference],
+    database: &JournalDatabase,
+    style: JournalStyle,
+) -> usize {
+    let mut changed = 0;
+    for reference in references {
+        let Some(known) = [&reference.journal, &reference.full_journal]
+            .into_iter()
+            .flatten()
+            .find_map(|name| database.lookup(name))
+        else {
+            continue;
+        };
+        let name = known.name(style);
+        if reference.journal.as_deref() == Some(name) {
+            continue;
+        }
+        if let Some(source) = edit::set_field(
+            &reference.source,
+            &reference.cite_key,
+            "journal",
+            Some(name),
+        ) {
+            reference.source = source;
+        }
+        reference.journal = Some(name.to_string());
+        reference
+            .full_journal
+            .get_or_insert_with(|| known.full.clone());
+        changed += 1;
+    }
+    changed
+}
+
+/// A journal written in several ways in the `journal` field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JournalVariants {
+    pub journal: JournalAbbreviation,
+    /// Each spelling with the cite keys using it, most used first
+    pub spellings: Vec<(String, Vec<String>)>,
+}
+
+/// An entry whose `journal` and `fjournal` name different journals.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JournalMismatch {
+    pub key: String,
+    pub journal: String,
+    pub full_journal: String,
+}
+
+/// Inconsistent journal names of a library.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct JournalReport {
+    /// Known journals written in several ways, by full name
+    pub variants: Vec<JournalVariants>,
+    /// Entries whose `journal` and `fjournal` disagree
+    pub mismatches: Vec<JournalMismatch>,
+    /// Journals missing from the database with the cite keys using them, by name
+    pub unknown: Vec<(String, Vec<String>)>,
+}
+
+impl JournalReport {
+    /// Whether the names are consistent, journals missing from the database aside.
+    pub fn is_empty(&self) -> bool {
+        self.variants.is_empty() && self.mismatches.is_empty()
+    }
+}
+
+/// Find journal names written inconsistently in the references.
+pub fn journal_report(references: &[Reference], database: &JournalDatabase) -> JournalReport {
+    let mut known = BTreeMap::<&str, (&JournalAbbreviation, BTreeMap<&str, Vec<String>>)>::new();
+    let mut unknown = BTreeMap::<&str, Vec<String>>::new();
+    let mut mismatches = Vec::new();
+    for reference in references {
+        let Some(journal) = reference.journal.as_deref() else {
+            continue;
+        };
+        let key = reference.cite_key.clone();
+        let Some(entry) = database.lookup(journal) else {
+            unknown.entry(journal).or_default().push(key);
+            continue;
+        };
+        if let Some(full_journal) = reference.full_journal.as_deref()
+            && database
+                .lookup(full_journal)
+                .is_some_and(|other| other != entry)
+        {
+            mismatches.push(JournalMismatch {
+                key: key.clone(),
+                journal: journal.to_string(),
+                full_journal: full_journal.to_string(),
+            });
+        }
+        known
+            .entry(&entry.full)
+            .or_insert_with(|| (entry, BTreeMap::new()))
+            .1
+            .entry(journal)
+            .or_default()
+            .push(key);
+    }
+    let variants = known
+        .into_values()
+        .filter(|(_, spellings)| spellings.len() > 1)
+        .map(|(journal, spellings)| {
+            let mut spellings = spellings
+                .into_iter()
+                .map(|(spelling, keys)| (spelling.to_string(), keys))
+                .collect::<Vec<_>>();
+            spellings.sort_by_key(|(_, keys)| std::cmp::Reverse(keys.len()));
+            JournalVariants {
+                journal: journal.clone(),
+                spellings,
+            }
+        })
+        .collect();
+    JournalReport {
+        variants,
+        mismatches,
+        unknown: unknown
+            .into_iter()
+            .map(|(name, keys)| (name.to_string(), keys))
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::read_bibliography;
+    use biblatex::Bibliography;
+
+    const SRC: &str = r#"@article{smith2010,
+  author = {John Smith},
+  journal = {SIAM Journal on Numerical Analysis},
+}
+
+@article{lee2015,
+  journal = {SIAM J. Numer. Anal.},
+  fjournal = {Mathematics of Computation},
+}
+
+@article{doe2020,
+  fjournal = {{Mathematics of Computation}},
+}
+
+@article{roe2021,
+  journal = {Journal of Unknown Results},
+}
+"#;
+
+    #[test]
+    fn test_lookup() {
+        let database = JournalDatabase::bundled();
+        assert!(database.len() > 100);
+        assert_eq!(
+            database.abbreviate("siam journal on numerical analysis"),
+            Some("SIAM J. Numer. Anal.")
+        );
+        assert_eq!(
+            database.full_name("{SIAM} J Numer Anal"),
+            Some("SIAM Journal on Numerical Analysis")
+        );
+        assert_eq!(
+            database.abbreviate("Annals of Applied Statistics"),
+            Some("Ann. Appl. Stat.")
+        );
+        let mut database = database;
+        database.extend(parse_abbreviations(
+            "# user\nSIAM Journal on Numerical Analysis;SINUM\nbroken line\n",
+        ));
+        assert_eq!(database.abbreviate("SIAM J. Numer. Anal."), Some("SINUM"));
+
+        // Replacing a replacement in the same batch still redirects the first names
+        let database = JournalDatabase::from_entries([
+            JournalAbbreviation::new("Journal A", "J. A"),
+            JournalAbbreviation::new("Journal A", "J. B"),
+            JournalAbbreviation::new("Journal C", "J. B"),
+        ]);
+        assert_eq!(database.full_name("J. A"), Some("Journal C"));
+        assert_eq!(database.abbreviate("Journal A"), Some("J. B"));
+        assert_eq!(database.len(), 1);
+    }
+
+    #[test]
+    fn test_fill_journals() {
+        let database = JournalDatabase::bundled();
+        let (output, changed) = fill_journals(SRC, &database);
+        assert_eq!(changed, 2);
+        let entries = edit::source_entries(&output);
+        let field = |key: &str, name: &str| {
+            let entry = entries.iter().find(|entry| entry.key == key).unwrap();
+            entry
+                .field(name)
+                .map(|field| field.text(&output).to_string())
+        };
+        assert_eq!(
+            field("smith2010", "journal").unwrap(),
+            "SIAM J. Numer. Anal."
+        );
+        assert_eq!(
+            field("smith2010", "fjournal").unwrap(),
+            "SIAM Journal on Numerical Analysis"
+        );
+        assert_eq!(field("doe2020", "journal").unwrap(), "Math. Comp.");
+        assert!(field("roe2021", "fjournal").is_none());
+    }
+
+    #[test]
+    fn test_style_and_report() {
+        let database = JournalDatabase::bundled();
+        let mut refs = read_bibliography(Bibliography::parse(SRC).unwrap());
+        let report = journal_report(&refs, &database);
+        assert_eq!(report.mismatches.len(), 1);
+        assert_eq!(report.mismatches[0].key, "lee2015");
+        assert_eq!(report.variants.len(), 1);
+        assert_eq!(report.variants[0].spellings.len(), 2);
+        assert_eq!(
+            report.unknown,
+            [(
+                "Journal of Unknown Results".to_string(),
+                vec!["roe2021".to_string()]
+            )]
+        );
+
+        assert_eq!(
+            apply_journal_style(&mut refs, &database, JournalStyle::Abbreviated),
+            2
+        );
+        let smith = refs.iter().find(|r| r.cite_key == "smith2010").unwrap();
+        assert_eq!(smith.journal.as_deref(), Some("SIAM J. Numer. Anal."));
+        assert!(smith.source.contains("SIAM J. Numer. Anal."));
+        assert_eq!(
+            smith.full_journal.as_deref(),
+            Some("SIAM Journal on Numerical Analysis")
+        );
+        assert!(journal_report(&refs, &database).variants.is_empty());
+    }
+}
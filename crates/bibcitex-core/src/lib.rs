@@ -1,10 +1,54 @@
 pub mod bib;
+pub mod cjk;
+pub mod edit;
+pub mod encoding;
+pub use encoding::*;
 pub mod error;
 pub use error::*;
 pub mod setting;
 pub use setting::*;
+pub mod highlight;
+pub use highlight::*;
+pub mod index;
+pub use index::*;
+pub mod jabref;
+pub use jabref::*;
+pub mod journal;
+pub use journal::*;
+pub mod notes;
+pub use notes::*;
+pub mod project;
+pub use project::*;
+pub mod markup;
+pub use markup::*;
+pub mod query;
+pub use query::*;
+pub mod rank;
+pub use rank::*;
+pub mod saved_search;
+pub use saved_search::*;
 pub mod search;
 pub mod utils;
 pub use search::*;
 pub mod filter;
 pub use filter::*;
+pub mod coauthor;
+pub use coauthor::*;
+pub mod csl;
+pub use csl::*;
+pub mod gbt7714;
+pub use gbt7714::*;
+pub mod template;
+pub use template::*;
+pub mod usage;
+pub use usage::*;
+pub mod stats;
+pub use stats::*;
+pub mod tag;
+pub use tag::*;
+pub mod tidy;
+pub use tidy::*;
+pub mod title;
+pub use title::*;
+pub mod trim;
+pub use trim::*;
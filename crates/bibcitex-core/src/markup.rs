@@ -0,0 +1,470 @@
+//! Scan Markdown and Typst documents for citations.
+//!
+//! Pandoc and Quarto Markdown cite with `[@key; @other, p. 3]`, `-@key` or `@key` in text, and
+//! take their bibliographies from the `bibliography` field of the YAML front matter or of
+//! `_quarto.yml`. Typst cites with `@key` and `#cite(<key>)`, follows `#include` and takes its
+//! bibliographies from `#bibliography`. [`scan_markup`] gives a [`ProjectScan`] compared with a
+//! library by [`ProjectScan::report`], as for a LaTeX project.
+use crate::{
+    Result,
+    project::{Citation, CitationLocation, ProjectScan},
+};
+use fs_err as fs;
+use regex::{Captures, Regex};
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    sync::LazyLock,
+};
+
+/// Prefixes of Quarto and pandoc-crossref cross references, which are not citations.
+const CROSS_REFERENCE_PREFIXES: [&str; 13] = [
+    "fig", "tbl", "sec", "eq", "lst", "thm", "lem", "cor", "prp", "cnj", "def", "exm", "exr",
+];
+
+static FRONT_MATTER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?s)\A---[ \t]*\r?\n(.*?)\r?\n(?:---|\.\.\.)[ \t]*(?:\r?\n|\z)").unwrap()
+});
+static MARKDOWN_CODE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?ms)^[ \t]*```.*?^[ \t]*```[^\n]*$|^[ \t]*~~~.*?^[ \t]*~~~[^\n]*$|``.*?``|`[^`]*`|<!--.*?-->")
+        .unwrap()
+});
+static MARKDOWN_CITATION: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?m)(?:^|[^\w@\\])-?@(?:\{([^}]+)\}|(\w[\w:.#$%&\-+?<>~/]*))").unwrap()
+});
+static TYPST_SKIPPED: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?s)```.*?```|`[^`]*`|/\*.*?\*/").unwrap());
+static TYPST_LINE_COMMENT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?m)(^|[^:])(//[^\n]*)").unwrap());
+static TYPST_REFERENCE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?m)(?:^|[^\w@])@([\w\-.:]+)").unwrap());
+static TYPST_CITE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"#cite\(\s*(?:<([\w\-.:]+)>|label\(\s*"([^"]+)"\s*\))"#).unwrap()
+});
+static TYPST_LABEL: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?m)(?:^|[^(\w])<([\w\-.:]+)>").unwrap());
+static TYPST_CALL: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"#(bibliography|include)\b").unwrap());
+static STRING: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#""([^"]*)""#).unwrap());
+
+/// Labels and `@` references of Typst files, resolved once all files are read as a label may
+/// be defined in another file.
+#[derive(Default)]
+struct TypstLabels {
+    labels: HashSet<String>,
+    /// Indices of the citations written as `@name`, which may refer to a label
+    references: HashSet<usize>,
+}
+
+/// A document format with citations outside LaTeX.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarkupFormat {
+    /// Pandoc Markdown, Quarto and R Markdown
+    Markdown,
+    Typst,
+}
+
+impl MarkupFormat {
+    /// The format of a file by its extension.
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        let extension = path.as_ref().extension()?.to_str()?.to_lowercase();
+        match extension.as_str() {
+            "md" | "markdown" | "qmd" | "rmd" => Some(Self::Markdown),
+            "typ" => Some(Self::Typst),
+            _ => None,
+        }
+    }
+}
+
+/// Markdown and Typst files in a directory, sorted by name.
+pub fn markup_files(dir: impl AsRef<Path>) -> Result<Vec<PathBuf>> {
+    let mut files = fs::read_dir(dir.as_ref())?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file() && MarkupFormat::from_path(path).is_some())
+        .collect::<Vec<_>>();
+    files.sort();
+    Ok(files)
+}
+
+/// Scan Markdown and Typst documents, the first one being the main file.
+pub fn scan_markup(files: &[PathBuf]) -> Result<ProjectScan> {
+    let mut scan = ProjectScan {
+        main: files.first().cloned().unwrap_or_default(),
+        ..Default::default()
+    };
+    let root = scan.root().to_path_buf();
+    let quarto = root.join("_quarto.yml");
+    if quarto.is_file() {
+        read_front_matter(&mut scan, &fs::read_to_string(&quarto)?, &quarto, &root, 1);
+    }
+    let mut visited = HashSet::new();
+    let mut typst = TypstLabels::default();
+    for file in files {
+        match MarkupFormat::from_path(file) {
+            Some(MarkupFormat::Typst) => scan_typst(&mut scan, file, &mut visited, &mut typst)?,
+            _ => scan_markdown(&mut scan, file, &mut visited)?,
+        }
+    }
+    let mut index = 0;
+    scan.citations.retain(|citation| {
+        let label = typst.references.contains(&index) && typst.labels.contains(&citation.key);
+        index += 1;
+        !label
+    });
+    Ok(scan)
+}
+
+fn scan_markdown(
+    scan: &mut ProjectScan,
+    file: &Path,
+    visited: &mut HashSet<PathBuf>,
+) -> Result<()> {
+    if !visited.insert(file.to_path_buf()) {
+        return Ok(());
+    }
+    let mut source = fs::read_to_string(file)?;
+    scan.files.push(file.to_path_buf());
+    if let Some(front_matter) = FRONT_MATTER.captures(&source) {
+        let yaml = front_matter[1].to_string();
+        let dir = file.parent().unwrap_or(Path::new("")).to_path_buf();
+        // The front matter starts after the opening `---`
+        read_front_matter(scan, &yaml, file, &dir, 2);
+        source = blank_matches(&FRONT_MATTER, &source);
+    }
+    let source = blank_matches(&MARKDOWN_CODE, &source);
+    for captures in MARKDOWN_CITATION.captures_iter(&source) {
+        let Some(key) = captures.get(1).or(captures.get(2)) else {
+            continue;
+        };
+        let key = match captures.get(1) {
+            Some(_) => key.as_str().trim(),
+            None => key
+                .as_str()
+                .trim_end_matches(|c| ":.#$%&-+?<>~/".contains(c)),
+        };
+        if !is_cross_reference(key) {
+            push_citation(
+                scan,
+                key,
+                file,
+                line(&source, captures.get(0).unwrap().end()),
+            );
+        }
+    }
+    Ok(())
+}
+
+fn scan_typst(
+    scan: &mut ProjectScan,
+    file: &Path,
+    visited: &mut HashSet<PathBuf>,
+    typst: &mut TypstLabels,
+) -> Result<()> {
+    if !visited.insert(file.to_path_buf()) {
+        return Ok(());
+    }
+    let source = blank_matches(&TYPST_SKIPPED, &fs::read_to_string(file)?);
+    let source = TYPST_LINE_COMMENT
+        .replace_all(&source, |captures: &Captures| {
+            format!("{}{}", &captures[1], " ".repeat(captures[2].len()))
+        })
+        .into_owned();
+    scan.files.push(file.to_path_buf());
+    let dir = file.parent().unwrap_or(Path::new("")).to_path_buf();
+    let root = scan.root().to_path_buf();
+    let resolve = |path: &str| match path.strip_prefix('/') {
+        Some(path) => root.join(path),
+        None => dir.join(path),
+    };
+
+    let mut includes = Vec::new();
+    for captures in TYPST_CALL.captures_iter(&source) {
+        let whole = captures.get(0).unwrap();
+        let arguments = match &captures[1] {
+            "bibliography" => call_arguments(&source, whole.end()),
+            // `#include` takes an expression, usually a string
+            _ => source[whole.end()..].lines().next().unwrap_or_default(),
+        };
+        for path in STRING.captures_iter(arguments) {
+            let path = resolve(&path[1]);
+            if &captures[1] == "include" {
+                includes.push(path);
+                break;
+            } else if !scan.bibliographies.contains(&path) {
+                scan.bibliographies.push(path);
+            }
+        }
+        if &captures[1] == "bibliography"
+            && arguments
+                .split(',')
+                .any(|argument| argument.split_whitespace().collect::<String>() == "full:true")
+        {
+            scan.nocite_all = true;
+        }
+    }
+
+    typst.labels.extend(
+        TYPST_LABEL
+            .captures_iter(&source)
+            .map(|captures| captures[1].to_string()),
+    );
+    let mut citations = TYPST_CITE
+        .captures_iter(&source)
+        .filter_map(|captures| {
+            let key = captures.get(1).or(captures.get(2))?;
+            Some((key.start(), key.as_str().to_string(), false))
+        })
+        .collect::<Vec<_>>();
+    citations.extend(
+        TYPST_REFERENCE
+            .captures_iter(&source)
+            .filter_map(|captures| {
+                let key = captures.get(1)?;
+                let name = key.as_str().trim_end_matches(['.', ':']);
+                Some((key.start(), name.to_string(), true))
+            }),
+    );
+    citations.sort();
+    for (offset, key, reference) in citations {
+        if reference {
+            typst.references.insert(scan.citations.len());
+        }
+        push_citation(scan, &key, file, line(&source, offset));
+    }
+
+    for include in includes {
+        if include.is_file() {
+            scan_typst(scan, &include, visited, typst)?;
+        } else if !scan.missing.contains(&include) {
+            scan.missing.push(include);
+        }
+    }
+    Ok(())
+}
+
+/// Read `bibliography` and `nocite` of YAML front matter or a Quarto project file, whose first
+/// line is line `first_line` of `file`.
+fn read_front_matter(
+    scan: &mut ProjectScan,
+    yaml: &str,
+    file: &Path,
+    dir: &Path,
+    first_line: usize,
+) {
+    for path in yaml_values(yaml, "bibliography") {
+        let path = dir.join(path);
+        if !scan.bibliographies.contains(&path) {
+            scan.bibliographies.push(path);
+        }
+    }
+    let lines = yaml.lines().collect::<Vec<_>>();
+    let Some(start) = lines.iter().position(|line| line.starts_with("nocite:")) else {
+        return;
+    };
+    let end = (start + 1..lines.len())
+        .find(|&i| !lines[i].is_empty() && !lines[i].starts_with(char::is_whitespace))
+        .unwrap_or(lines.len());
+    for (i, line) in lines.iter().enumerate().take(end).skip(start) {
+        for key in line.split('@').skip(1) {
+            let key = key
+                .split(|c: char| c.is_whitespace() || matches!(c, ',' | ';' | '"' | '\'' | ']'))
+                .next()
+                .unwrap_or_default();
+            if key == "*" {
+                scan.nocite_all = true;
+            } else if !key.is_empty() {
+                push_citation(scan, key, file, i + first_line);
+            }
+        }
+    }
+}
+
+/// Values of a top-level YAML field, either a scalar, a flow sequence or a block sequence.
+fn yaml_values(yaml: &str, field: &str) -> Vec<String> {
+    let unquote = |value: &str| value.trim().trim_matches(['"', '\'']).to_string();
+    let mut lines = yaml.lines();
+    let Some(value) = lines.find_map(|line| line.strip_prefix(field)?.strip_prefix(':')) else {
+        return Vec::new();
+    };
+    let value = value.split(" #").next().unwrap_or_default().trim();
+    if let Some(list) = value.strip_prefix('[') {
+        list.trim_end_matches(']').split(',').map(unquote).collect()
+    } else if !value.is_empty() {
+        vec![unquote(value)]
+    } else {
+        lines
+            .map_while(|line| line.trim_start().strip_prefix("- "))
+            .map(unquote)
+            .collect()
+    }
+    .into_iter()
+    .filter(|value| !value.is_empty())
+    .collect()
+}
+
+/// The arguments of a Typst function call whose name ends at `pos`.
+fn call_arguments(source: &str, pos: usize) -> &str {
+    let rest = &source[pos..];
+    if !rest.starts_with('(') {
+        return "";
+    }
+    let mut depth = 0usize;
+    for (i, c) in rest.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return &rest[1..i];
+                }
+            }
+            _ => {}
+        }
+    }
+    &rest[1..]
+}
+
+fn is_cross_reference(key: &str) -> bool {
+    key.split_once(['-', ':'])
+        .is_some_and(|(prefix, _)| CROSS_REFERENCE_PREFIXES.contains(&prefix))
+}
+
+fn push_citation(scan: &mut ProjectScan, key: &str, file: &Path, line: usize) {
+    scan.citations.push(Citation {
+        key: key.to_string(),
+        location: CitationLocation {
+            file: file.to_path_buf(),
+            line,
+        },
+    });
+}
+
+fn line(source: &str, offset: usize) -> usize {
+    source[..offset].matches('\n').count() + 1
+}
+
+/// Replace the matches with spaces, keeping line numbers.
+fn blank_matches(regex: &Regex, source: &str) -> String {
+    regex
+        .replace_all(source, |captures: &Captures| {
+            captures[0]
+                .chars()
+                .map(|c| if c == '\n' { '\n' } else { ' ' })
+                .collect::<String>()
+        })
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(scan: &ProjectScan) -> Vec<(&str, usize)> {
+        scan.citations
+            .iter()
+            .map(|c| (c.key.as_str(), c.location.line))
+            .collect()
+    }
+
+    #[test]
+    fn test_yaml_values() {
+        assert_eq!(
+            yaml_values("bibliography: refs.bib", "bibliography"),
+            ["refs.bib"]
+        );
+        assert_eq!(
+            yaml_values("title: x\nbibliography: [\"a.bib\", b.bib]", "bibliography"),
+            ["a.bib", "b.bib"]
+        );
+        assert_eq!(
+            yaml_values(
+                "bibliography:\n  - a.bib\n  - 'b.bib'\nlang: en",
+                "bibliography"
+            ),
+            ["a.bib", "b.bib"]
+        );
+        assert!(yaml_values("title: x", "bibliography").is_empty());
+    }
+
+    #[test]
+    fn test_scan_markdown() {
+        let dir = std::env::temp_dir().join(format!("bibcitex-markdown-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("paper.qmd");
+        fs::write(
+            &file,
+            "---
+title: Paper
+bibliography:
+  - refs.bib
+nocite: |
+  @extra
+---
+
+As shown [@knuth1984; @lamport1994, p. 3], -@knuth1984 says.
+@{doe:2020} argues, see @fig-plot and mail me@example.com.
+
+```python
+x = @decorator
+```
+Inline `@code` is skipped <!-- @hidden -->.
+",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("_quarto.yml"),
+            "project:\n  type: book\nnocite: \"@book\"\n",
+        )
+        .unwrap();
+        let scan = scan_markup(std::slice::from_ref(&file)).unwrap();
+        assert_eq!(scan.bibliographies, [dir.join("refs.bib")]);
+        assert_eq!(
+            keys(&scan),
+            [
+                ("book", 3),
+                ("extra", 6),
+                ("knuth1984", 9),
+                ("lamport1994", 9),
+                ("knuth1984", 9),
+                ("doe:2020", 10)
+            ]
+        );
+        assert!(!scan.nocite_all);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_scan_typst() {
+        let dir = std::env::temp_dir().join(format!("bibcitex-typst-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("main.typ"),
+            r#"= Introduction <intro>
+As @knuth1984 shows, see @intro, @results and #cite(<lamport1994>, form: "prose").
+// @commented and https://example.com
+#include "chapter.typ"
+#include "missing.typ"
+#bibliography(("refs.bib", "more.bib"), full: true)
+"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("chapter.typ"),
+            "Also @doe2020, see @intro.\n== Results <results>\n",
+        )
+        .unwrap();
+        let scan = scan_markup(&[dir.join("main.typ")]).unwrap();
+        assert_eq!(
+            keys(&scan),
+            [("knuth1984", 2), ("lamport1994", 2), ("doe2020", 1)]
+        );
+        assert_eq!(scan.files.len(), 2);
+        assert_eq!(scan.missing, [dir.join("missing.typ")]);
+        assert_eq!(
+            scan.bibliographies,
+            [dir.join("refs.bib"), dir.join("more.bib")]
+        );
+        assert!(scan.nocite_all);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,239 @@
+//! Personal notes, reading status and rating of references.
+//!
+//! Annotations are private to the user, so they are kept in a sidecar file per library in the
+//! config directory rather than in the shared `.bib` file, keyed by cite key. See
+//! [`NoteStore::file_path`].
+use crate::{Result, bib::Reference};
+use fs_err as fs;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// Highest rating of a reference.
+pub const MAX_RATING: u8 = 5;
+
+/// Reading status of a reference.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReadingStatus {
+    #[default]
+    Unread,
+    Reading,
+    Read,
+}
+
+impl ReadingStatus {
+    /// All statuses, in reading order.
+    pub fn all() -> &'static [Self] {
+        &[Self::Unread, Self::Reading, Self::Read]
+    }
+}
+
+impl Display for ReadingStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unread => write!(f, "unread"),
+            Self::Reading => write!(f, "reading"),
+            Self::Read => write!(f, "read"),
+        }
+    }
+}
+
+impl FromStr for ReadingStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Self::all()
+            .iter()
+            .find(|status| status.to_string().eq_ignore_ascii_case(s.trim()))
+            .copied()
+            .ok_or_else(|| format!("unknown reading status `{s}`"))
+    }
+}
+
+/// Private annotation of a reference.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Annotation {
+    /// Notes in Markdown
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub notes: String,
+    #[serde(default)]
+    pub status: ReadingStatus,
+    /// Rating from 1 to [`MAX_RATING`], `None` if not rated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rating: Option<u8>,
+}
+
+impl Annotation {
+    /// Check whether the annotation holds nothing worth storing.
+    pub fn is_empty(&self) -> bool {
+        self.notes.trim().is_empty()
+            && self.status == ReadingStatus::Unread
+            && self.rating.is_none()
+    }
+}
+
+/// Annotations of the references in a library, stored in a sidecar file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NoteStore {
+    path: PathBuf,
+    entries: BTreeMap<String, Annotation>,
+}
+
+impl NoteStore {
+    /// The sidecar file of a library, e.g., `$HOME/.config/BibCiTeX/notes/<library>.json` on
+    /// Linux, in the same directory as [`crate::Setting::config_file_path`].
+    pub fn file_path(library: &str) -> PathBuf {
+        let name = library
+            .chars()
+            .map(|c| {
+                if c.is_control()
+                    || matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|')
+                {
+                    '_'
+                } else {
+                    c
+                }
+            })
+            .collect::<String>();
+        dirs::config_dir()
+            .unwrap()
+            .join("BibCiTeX")
+            .join("notes")
+            .join(format!("{name}.json"))
+    }
+
+    /// Load the annotations of a library, empty if it has none yet.
+    pub fn load(library: &str) -> Result<Self> {
+        Self::open(Self::file_path(library))
+    }
+
+    /// Load annotations from a sidecar file, empty if it does not exist.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let entries = if path.exists() {
+            serde_json::from_str(&fs::read_to_string(&path)?)?
+        } else {
+            BTreeMap::new()
+        };
+        Ok(Self { path, entries })
+    }
+
+    /// Write the annotations back to the sidecar file, creating it if needed.
+    pub fn save(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&self.path, serde_json::to_string_pretty(&self.entries)?)?;
+        Ok(())
+    }
+
+    /// The annotation of a reference, if any.
+    pub fn get(&self, key: &str) -> Option<&Annotation> {
+        self.entries.get(key)
+    }
+
+    /// Number of annotated references.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Set the annotation of a reference, removing it if empty.
+    ///
+    /// The rating is clamped to `1..=MAX_RATING`, and a rating of 0 clears it.
+    pub fn set(&mut self, key: &str, mut annotation: Annotation) {
+        annotation.rating = annotation
+            .rating
+            .filter(|&rating| rating > 0)
+            .map(|rating| rating.min(MAX_RATING));
+        if annotation.is_empty() {
+            self.entries.remove(key);
+        } else {
+            self.entries.insert(key.to_string(), annotation);
+        }
+    }
+
+    /// Move the annotation of a reference to its new cite key.
+    ///
+    /// Returns whether there was an annotation to move. An annotation already stored under the
+    /// new key is replaced.
+    pub fn rename_key(&mut self, old: &str, new: &str) -> bool {
+        match self.entries.remove(old) {
+            Some(annotation) => {
+                self.entries.insert(new.to_string(), annotation);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Attach the annotations to the references of the library.
+    pub fn annotate(&self, references: &mut [Reference]) {
+        for reference in references {
+            reference.annotation = self.entries.get(&reference.cite_key).cloned();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::read_bibliography;
+    use biblatex::Bibliography;
+
+    const SRC: &str = r#"@inproceedings{smith2010,
+  title = {A Paper},
+  crossref = {proc2010},
+}
+
+@proceedings{proc2010,
+  title = {Proceedings},
+  year = {2010},
+}
+"#;
+
+    #[test]
+    fn test_store() {
+        let path = std::env::temp_dir().join(format!("bibcitex-notes-{}.json", std::process::id()));
+        let mut store = NoteStore::open(&path).unwrap();
+        assert!(store.is_empty());
+        store.set(
+            "smith2010",
+            Annotation {
+                notes: "# Summary".into(),
+                status: ReadingStatus::Reading,
+                rating: Some(9),
+            },
+        );
+        store.set("proc2010", Annotation::default());
+        assert_eq!(store.len(), 1);
+        assert_eq!(store.get("smith2010").unwrap().rating, Some(MAX_RATING));
+        store.save().unwrap();
+        let mut loaded = NoteStore::open(&path).unwrap();
+        assert_eq!(loaded, store);
+        assert!(loaded.rename_key("smith2010", "smith10"));
+        assert!(!loaded.rename_key("smith2010", "smith10"));
+        let mut refs = read_bibliography(Bibliography::parse(SRC).unwrap());
+        refs[0].cite_key = "smith10".into();
+        loaded.annotate(&mut refs);
+        assert_eq!(refs[0].annotation.as_ref().unwrap().notes, "# Summary");
+        assert!(refs[1].annotation.is_none());
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_status() {
+        assert_eq!("Reading".parse(), Ok(ReadingStatus::Reading));
+        assert!("done".parse::<ReadingStatus>().is_err());
+        let json = serde_json::to_string(&Annotation::default()).unwrap();
+        assert_eq!(json, r#"{"status":"unread"}"#);
+    }
+}
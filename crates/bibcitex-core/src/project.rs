@@ -0,0 +1,420 @@
+//! Scan a LaTeX project for citations.
+//!
+//! Starting from the main `.tex` file, [`scan_project`] follows `\input`, `\include` and
+//! `\subfile`, collects the keys of every `\cite`-family command and `\nocite`, and the
+//! bibliographies given by `\bibliography` and `\addbibresource`. [`ProjectScan::report`] then
+//! compares the citations with the entries of the bibliographies.
+//!
+//! Markdown and Typst documents are scanned by [`crate::markup`] into the same [`ProjectScan`].
+use crate::{
+    Error, MarkupFormat, Result,
+    bib::{Reference, parse},
+    markup_files, scan_markup,
+    utils::read_bibliography_with,
+};
+use fs_err as fs;
+use regex::Regex;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    path::{Path, PathBuf},
+    sync::LazyLock,
+};
+
+/// Commands containing `cite` that do not take keys.
+const NOT_CITE_COMMANDS: [&str; 2] = ["citestyle", "citesetup"];
+
+static COMMAND: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\\([A-Za-z]+)\*?").unwrap());
+
+/// Where a key is cited.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CitationLocation {
+    pub file: PathBuf,
+    /// Line number, starting at 1
+    pub line: usize,
+}
+
+impl Display for CitationLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.file.display(), self.line)
+    }
+}
+
+/// A cited key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Citation {
+    pub key: String,
+    pub location: CitationLocation,
+}
+
+/// The citations and bibliographies of a LaTeX project.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProjectScan {
+    /// The main `.tex` file
+    pub main: PathBuf,
+    /// Scanned `.tex` files, in the order they are included
+    pub files: Vec<PathBuf>,
+    /// Included files that do not exist
+    pub missing: Vec<PathBuf>,
+    /// Citations in source order, `\nocite{*}` excluded
+    pub citations: Vec<Citation>,
+    /// Whether the project has `\nocite{*}`, citing every entry
+    pub nocite_all: bool,
+    /// Bibliography files from `\bibliography` and `\addbibresource`
+    pub bibliographies: Vec<PathBuf>,
+}
+
+/// Citations of a project compared with the entries of its bibliographies.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProjectReport {
+    /// Cited keys without an entry, sorted
+    pub undefined: Vec<String>,
+    /// Keys of entries never cited, in library order, empty with `\nocite{*}`
+    pub unused: Vec<String>,
+    /// Cited keys with their locations, most cited first
+    pub counts: Vec<(String, Vec<CitationLocation>)>,
+}
+
+impl ProjectScan {
+    /// The directory of the main file, against which included files are resolved.
+    pub fn root(&self) -> &Path {
+        self.main.parent().unwrap_or(Path::new(""))
+    }
+
+    /// Cited keys with their locations, most cited first, then by key.
+    pub fn citation_counts(&self) -> Vec<(String, Vec<CitationLocation>)> {
+        let mut counts = Vec::<(String, Vec<CitationLocation>)>::new();
+        let mut positions = HashMap::new();
+        for citation in &self.citations {
+            let position = *positions.entry(citation.key.as_str()).or_insert_with(|| {
+                counts.push((citation.key.clone(), Vec::new()));
+                counts.len() - 1
+            });
+            counts[position].1.push(citation.location.clone());
+        }
+        counts.sort_by(|a, b| b.1.len().cmp(&a.1.len()).then_with(|| a.0.cmp(&b.0)));
+        counts
+    }
+
+    /// Compare the citations with the references of the bibliographies.
+    pub fn report(&self, references: &[Reference]) -> ProjectReport {
+        let defined = references
+            .iter()
+            .map(|reference| reference.cite_key.as_str())
+            .collect::<HashSet<_>>();
+        let cited = self
+            .citations
+            .iter()
+            .map(|citation| citation.key.as_str())
+            .collect::<HashSet<_>>();
+        let mut undefined = cited
+            .iter()
+            .filter(|key| !defined.contains(*key))
+            .map(|key| key.to_string())
+            .collect::<Vec<_>>();
+        undefined.sort();
+        let unused = if self.nocite_all {
+            Vec::new()
+        } else {
+            references
+                .iter()
+                .filter(|reference| !cited.contains(reference.cite_key.as_str()))
+                .map(|reference| reference.cite_key.clone())
+                .collect()
+        };
+        ProjectReport {
+            undefined,
+            unused,
+            counts: self.citation_counts(),
+        }
+    }
+
+    /// Read the references of all bibliographies of the project.
+    pub fn read_bibliographies(&self, tag_separators: &str) -> Result<Vec<Reference>> {
+        let mut references = Vec::new();
+        for path in &self.bibliographies {
+            references.extend(read_bibliography_with(parse(path)?, tag_separators));
+        }
+        Ok(references)
+    }
+}
+
+/// Scan a LaTeX project, given its main `.tex` file or its root directory.
+///
+/// In a directory, the main file is `main.tex` if it has `\documentclass`, otherwise the first
+/// `.tex` file with `\documentclass` by name. Without one, all Markdown and Typst files of the
+/// directory are scanned, as is a single Markdown or Typst file, see [`scan_markup`].
+pub fn scan_project(path: impl AsRef<Path>) -> Result<ProjectScan> {
+    let path = path.as_ref();
+    let main = if path.is_dir() {
+        match find_main_file(path) {
+            Ok(main) => main,
+            Err(error) => {
+                let files = markup_files(path)?;
+                if files.is_empty() {
+                    return Err(error);
+                }
+                return scan_markup(&files);
+            }
+        }
+    } else if MarkupFormat::from_path(path).is_some() {
+        return scan_markup(&[path.to_path_buf()]);
+    } else {
+        path.to_path_buf()
+    };
+    let mut scan = ProjectScan {
+        main: main.clone(),
+        ..Default::default()
+    };
+    let mut visited = HashSet::new();
+    scan_file(&mut scan, &main, &mut visited)?;
+    Ok(scan)
+}
+
+fn find_main_file(dir: &Path) -> Result<PathBuf> {
+    let mut candidates = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "tex"))
+        .collect::<Vec<_>>();
+    candidates.sort_by_key(|path| (path.file_name() != Some("main.tex".as_ref()), path.clone()));
+    candidates
+        .into_iter()
+        .find(|path| {
+            fs::read_to_string(path)
+                .is_ok_and(|source| strip_comments(&source).contains(r"\documentclass"))
+        })
+        .ok_or_else(|| {
+            Error::IOError(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!(
+                    "no main .tex file with \\documentclass in {}",
+                    dir.display()
+                ),
+            ))
+        })
+}
+
+fn scan_file(scan: &mut ProjectScan, file: &Path, visited: &mut HashSet<PathBuf>) -> Result<()> {
+    if !visited.insert(file.to_path_buf()) {
+        return Ok(());
+    }
+    let source = strip_comments(&fs::read_to_string(file)?);
+    scan.files.push(file.to_path_buf());
+    let root = scan.root().to_path_buf();
+    for captures in COMMAND.captures_iter(&source) {
+        let (Some(whole), Some(name)) = (captures.get(0), captures.get(1)) else {
+            continue;
+        };
+        let name = name.as_str();
+        let args = || arguments(&source, whole.end(), name.ends_with("cites"));
+        match name {
+            "input" | "include" | "subfile" => {
+                let Some((_, included)) = args().into_iter().next() else {
+                    continue;
+                };
+                let mut included = root.join(included.trim());
+                if included.extension().is_none() {
+                    included.set_extension("tex");
+                }
+                if included.is_file() {
+                    scan_file(scan, &included, visited)?;
+                } else if !scan.missing.contains(&included) {
+                    scan.missing.push(included);
+                }
+            }
+            "bibliography" | "addbibresource" | "addglobalbib" => {
+                for (_, resources) in args() {
+                    for resource in resources
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|r| !r.is_empty())
+                    {
+                        let mut resource = root.join(resource);
+                        if resource.extension().is_none() {
+                            resource.set_extension("bib");
+                        }
+                        if !scan.bibliographies.contains(&resource) {
+                            scan.bibliographies.push(resource);
+                        }
+                    }
+                }
+            }
+            _ if name.contains("cite") && !NOT_CITE_COMMANDS.contains(&name) => {
+                for (offset, keys) in args() {
+                    let line = source[..offset].matches('\n').count() + 1;
+                    for key in keys.split(',').map(str::trim).filter(|k| !k.is_empty()) {
+                        if key == "*" {
+                            scan.nocite_all = true;
+                            continue;
+                        }
+                        scan.citations.push(Citation {
+                            key: key.to_string(),
+                            location: CitationLocation {
+                                file: file.to_path_buf(),
+                                line,
+                            },
+                        });
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Mandatory arguments after a command starting at `pos`, with their offsets, skipping optional
+/// ones in brackets or parentheses. Multicite commands like `\cites` take several.
+fn arguments(source: &str, mut pos: usize, multiple: bool) -> Vec<(usize, &str)> {
+    let bytes = source.as_bytes();
+    let mut args = Vec::new();
+    loop {
+        while pos < bytes.len() && bytes[pos].is_ascii_whitespace() {
+            pos += 1;
+        }
+        match bytes.get(pos) {
+            Some(&open @ (b'[' | b'(' | b'{')) => {
+                let close = match open {
+                    b'[' => b']',
+                    b'(' => b')',
+                    _ => b'}',
+                };
+                let mut depth = 0usize;
+                let Some(end) = (pos + 1..bytes.len()).find(|&i| match bytes[i] {
+                    b'{' => {
+                        depth += 1;
+                        false
+                    }
+                    b'}' if depth > 0 => {
+                        depth -= 1;
+                        false
+                    }
+                    b if b == close && depth == 0 => true,
+                    _ => false,
+                }) else {
+                    break;
+                };
+                if open == b'{' {
+                    args.push((pos, &source[pos + 1..end]));
+                    if !multiple {
+                        break;
+                    }
+                }
+                pos = end + 1;
+            }
+            _ => break,
+        }
+    }
+    args
+}
+
+/// Replace comments with spaces, keeping line numbers and offsets.
+fn strip_comments(source: &str) -> String {
+    source
+        .split_inclusive('\n')
+        .map(|line| {
+            let bytes = line.as_bytes();
+            let comment = (0..bytes.len()).find(|&i| {
+                bytes[i] == b'%'
+                    && bytes[..i].iter().rev().take_while(|&&b| b == b'\\').count() % 2 == 0
+            });
+            match comment {
+                Some(start) => {
+                    let end = line.trim_end_matches(['\n', '\r']).len().max(start);
+                    format!(
+                        "{}{}{}",
+                        &line[..start],
+                        " ".repeat(end - start),
+                        &line[end..]
+                    )
+                }
+                None => line.to_string(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::read_bibliography;
+    use biblatex::Bibliography;
+
+    #[test]
+    fn test_arguments() {
+        let source = r"\cites[see][12]{a, b}[]{c} text";
+        assert_eq!(
+            arguments(source, 6, true)
+                .into_iter()
+                .map(|(_, arg)| arg)
+                .collect::<Vec<_>>(),
+            ["a, b", "c"]
+        );
+        assert_eq!(arguments(r"\cite {x}{y}", 5, false).len(), 1);
+        assert_eq!(
+            strip_comments("a % \\cite{x}\nb\\% c"),
+            "a           \nb\\% c"
+        );
+    }
+
+    #[test]
+    fn test_scan_project() {
+        let dir = std::env::temp_dir().join(format!("bibcitex-project-{}", std::process::id()));
+        fs::create_dir_all(dir.join("chapters")).unwrap();
+        fs::write(
+            dir.join("main.tex"),
+            r"\documentclass{article}
+\addbibresource[label=x]{refs.bib}
+\begin{document}
+See \cite[p.~3]{knuth1984, lamport1994}.
+% \cite{commented}
+\input{chapters/intro}
+\include{chapters/missing}
+\end{document}
+",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("chapters/intro.tex"),
+            "\\textcite{knuth1984} and \\parencite*{ghost}\n\\input{main}\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("refs.bib"),
+            "@book{knuth1984, title = {The TeXbook}}\n@book{lamport1994, title = {LaTeX}}\n@misc{unused, title = {Unused}}\n",
+        )
+        .unwrap();
+
+        let scan = scan_project(&dir).unwrap();
+        assert_eq!(scan.main, dir.join("main.tex"));
+        assert_eq!(scan.files.len(), 2);
+        assert_eq!(scan.missing, [dir.join("chapters/missing.tex")]);
+        assert_eq!(scan.bibliographies, [dir.join("refs.bib")]);
+        let keys = scan
+            .citations
+            .iter()
+            .map(|c| c.key.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(keys, ["knuth1984", "lamport1994", "knuth1984", "ghost"]);
+
+        let refs = scan.read_bibliographies(",").unwrap();
+        let report = scan.report(&refs);
+        assert_eq!(report.undefined, ["ghost"]);
+        assert_eq!(report.unused, ["unused"]);
+        let (key, locations) = &report.counts[0];
+        assert_eq!(key, "knuth1984");
+        assert_eq!(locations.iter().map(|l| l.line).collect::<Vec<_>>(), [4, 1]);
+        assert_eq!(locations[1].file, dir.join("chapters/intro.tex"));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_nocite_all() {
+        let scan = ProjectScan {
+            nocite_all: true,
+            ..Default::default()
+        };
+        let refs = read_bibliography(Bibliography::parse("@misc{a, title = {A}}").unwrap());
+        assert!(scan.report(&refs).unused.is_empty());
+    }
+}
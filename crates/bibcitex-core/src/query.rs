@@ -0,0 +1,843 @@
+//! A small query language for searching references.
+//!
+//! # Syntax
+//!
+//! - `knuth`: a term matching any field but the type, see [`QueryField::defaults`]
+//! - `"literate programming"`: a quoted phrase
+//! - `author:knuth`, `journal:"SIAM J"`: a term restricted to a field
+//! - `year:2010..2020`, `year:2010..`, `year:..2020`: an inclusive year range
+//! - `type:article`: an entry type, `type:thesis` also matches master's and PhD theses
+//! - `doi:10.1137/*`: `*` matches any sequence, and the whole field value must match
+//! - `status:reading`, `rating:5`: the private reading status and rating, see [`crate::notes`]
+//! - `AND`, `OR`, `NOT` (or a leading `-`) and parentheses
+//!
+//! Adjacent terms are combined with `AND`. Operators bind in the order `NOT`, `AND`, `OR`.
+//!
+//! # Example
+//!
+//! ```
+//! use bibcitex_core::Query;
+//!
+//! let query = Query::parse(r#"author:knuth AND (year:1980..1990 OR "literate")"#).unwrap();
+//! assert!(Query::parse("author:").is_err());
+//! ```
+use crate::{Error, Result, bib::Reference, cjk};
+use biblatex::EntryType;
+use serde::{Deserialize, Serialize};
+use std::{borrow::Cow, cell::OnceCell, fmt::Display, str::FromStr};
+
+/// Fields that can be used in a query, e.g., `author:knuth`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QueryField {
+    Key,
+    Title,
+    Author,
+    Journal,
+    Year,
+    Type,
+    Doi,
+    Note,
+    Abstract,
+    Keywords,
+    Eprint,
+    Isbn,
+    Series,
+    Publisher,
+    BookTitle,
+    School,
+    Institution,
+    Status,
+    Rating,
+}
+
+impl QueryField {
+    /// All fields supported in queries, in declaration order.
+    pub fn all() -> &'static [Self] {
+        &[
+            Self::Key,
+            Self::Title,
+            Self::Author,
+            Self::Journal,
+            Self::Year,
+            Self::Type,
+            Self::Doi,
+            Self::Note,
+            Self::Abstract,
+            Self::Keywords,
+            Self::Eprint,
+            Self::Isbn,
+            Self::Series,
+            Self::Publisher,
+            Self::BookTitle,
+            Self::School,
+            Self::Institution,
+            Self::Status,
+            Self::Rating,
+        ]
+    }
+
+    /// Fields searched by an unqualified term by default, i.e., all but the type and the
+    /// annotation.
+    pub fn defaults() -> &'static [Self] {
+        &[
+            Self::Key,
+            Self::Title,
+            Self::Author,
+            Self::Journal,
+            Self::Year,
+            Self::Doi,
+            Self::Note,
+            Self::Abstract,
+            Self::Keywords,
+            Self::Eprint,
+            Self::Isbn,
+            Self::Series,
+            Self::Publisher,
+            Self::BookTitle,
+            Self::School,
+            Self::Institution,
+        ]
+    }
+
+    /// Values of the field in a reference, as displayed.
+    pub fn raw_values(self, reference: &Reference) -> Vec<String> {
+        let chunks = |chunks: &Option<Vec<biblatex::Chunk>>| {
+            chunks
+                .iter()
+                .map(|chunks| chunks.iter().map(|chunk| chunk.get()).collect::<String>())
+                .collect()
+        };
+        let string = |value: &Option<String>| value.iter().cloned().collect();
+        let list = |values: &Option<Vec<String>>| values.iter().flatten().cloned().collect();
+        match self {
+            Self::Key => vec![reference.cite_key.clone()],
+            Self::Title => chunks(&reference.title),
+            Self::Author => list(&reference.author),
+            Self::Journal => reference
+                .journal
+                .iter()
+                .chain(&reference.full_journal)
+                .cloned()
+                .collect(),
+            Self::Year => reference.year.iter().map(|year| year.to_string()).collect(),
+            Self::Type => vec![reference.type_.to_string()],
+            Self::Doi => string(&reference.doi),
+            Self::Note => chunks(&reference.note),
+            Self::Abstract => chunks(&reference.abstract_),
+            Self::Keywords => list(&reference.keywords),
+            Self::Eprint => string(&reference.eprint),
+            Self::Isbn => string(&reference.isbn),
+            Self::Series => string(&reference.series),
+            Self::Publisher => list(&reference.publisher),
+            Self::BookTitle => chunks(&reference.book_title),
+            Self::School => string(&reference.school),
+            Self::Institution => string(&reference.institution),
+            Self::Status => vec![
+                reference
+                    .annotation
+                    .as_ref()
+                    .map(|annotation| annotation.status)
+                    .unwrap_or_default()
+                    .to_string(),
+            ],
+            Self::Rating => reference
+                .annotation
+                .iter()
+                .filter_map(|annotation| annotation.rating)
+                .map(|rating| rating.to_string())
+                .collect(),
+        }
+    }
+
+    /// Lowercase values of the field in a reference.
+    ///
+    /// Values are folded by [`cjk::normalize`], and values with CJK ideographs are followed by
+    /// their pinyin forms.
+    pub(crate) fn values(self, reference: &Reference) -> Vec<String> {
+        self.raw_values(reference)
+            .iter()
+            .flat_map(|value| cjk::search_forms(value))
+            .collect()
+    }
+}
+
+/// Lowercase values of the fields of a reference, computed once per field when first needed,
+/// so that a query with several terms does not fold the same values again.
+pub(crate) struct FieldValues<'a> {
+    reference: &'a Reference,
+    values: Vec<OnceCell<Vec<String>>>,
+}
+
+impl<'a> FieldValues<'a> {
+    pub(crate) fn new(reference: &'a Reference) -> Self {
+        Self {
+            reference,
+            values: QueryField::all().iter().map(|_| OnceCell::new()).collect(),
+        }
+    }
+
+    pub(crate) fn get(&self, field: QueryField) -> Cow<'_, [String]> {
+        Cow::Borrowed(self.values[field as usize].get_or_init(|| field.values(self.reference)))
+    }
+}
+
+impl Display for QueryField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Key => write!(f, "key"),
+            Self::Title => write!(f, "title"),
+            Self::Author => write!(f, "author"),
+            Self::Journal => write!(f, "journal"),
+            Self::Year => write!(f, "year"),
+            Self::Type => write!(f, "type"),
+            Self::Doi => write!(f, "doi"),
+            Self::Note => write!(f, "note"),
+            Self::Abstract => write!(f, "abstract"),
+            Self::Keywords => write!(f, "keywords"),
+            Self::Eprint => write!(f, "eprint"),
+            Self::Isbn => write!(f, "isbn"),
+            Self::Series => write!(f, "series"),
+            Self::Publisher => write!(f, "publisher"),
+            Self::BookTitle => write!(f, "booktitle"),
+            Self::School => write!(f, "school"),
+            Self::Institution => write!(f, "institution"),
+            Self::Status => write!(f, "status"),
+            Self::Rating => write!(f, "rating"),
+        }
+    }
+}
+
+impl FromStr for QueryField {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Self::all()
+            .iter()
+            .find(|field| field.to_string().eq_ignore_ascii_case(s))
+            .copied()
+            .ok_or_else(|| {
+                let fields = Self::all()
+                    .iter()
+                    .map(|field| field.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!("unknown field `{s}`, expected one of {fields}")
+            })
+    }
+}
+
+/// A parsed search query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Query {
+    /// Matches every reference, parsed from an empty query
+    All,
+    /// A term or phrase matching any of the fields
+    Term {
+        fields: Vec<QueryField>,
+        text: String,
+    },
+    /// An inclusive year range, open if a bound is missing
+    YearRange {
+        start: Option<i32>,
+        end: Option<i32>,
+    },
+    /// An entry type
+    Type(EntryType),
+    And(Box<Query>, Box<Query>),
+    Or(Box<Query>, Box<Query>),
+    Not(Box<Query>),
+}
+
+impl Query {
+    /// Parse a query, searching the default fields for unqualified terms.
+    pub fn parse(input: &str) -> Result<Self> {
+        Self::parse_in(input, QueryField::defaults())
+    }
+
+    /// Parse a query, falling back to a plain term if it is not a valid query.
+    ///
+    /// Terms with an unknown field prefix are plain text, except that arXiv identifiers and
+    /// arXiv and DOI URLs are looked up in the `eprint` and `doi` fields, so that a pasted
+    /// `arXiv:2101.00001` or `https://doi.org/10.1137/...` finds its entry.
+    pub(crate) fn parse_or_plain(input: &str, fields: &[QueryField]) -> Self {
+        tokenize(input)
+            .and_then(|tokens| {
+                let tokens = tokens
+                    .into_iter()
+                    .map(|(pos, token)| (pos, lenient_token(token)))
+                    .collect();
+                Self::parse_tokens(input, tokens, fields)
+            })
+            .unwrap_or_else(|_| Self::Term {
+                fields: fields.to_vec(),
+                text: cjk::normalize(input.trim()),
+            })
+    }
+
+    /// Parse a query, searching `fields` for unqualified terms.
+    pub fn parse_in(input: &str, fields: &[QueryField]) -> Result<Self> {
+        Self::parse_tokens(input, tokenize(input)?, fields)
+    }
+
+    fn parse_tokens(
+        input: &str,
+        tokens: Vec<(usize, Token)>,
+        fields: &[QueryField],
+    ) -> Result<Self> {
+        let mut parser = Parser {
+            tokens,
+            pos: 0,
+            end: input.chars().count(),
+            fields,
+        };
+        if parser.tokens.is_empty() {
+            return Ok(Self::All);
+        }
+        let query = parser.parse_or()?;
+        match parser.peek() {
+            None => Ok(query),
+            Some((pos, Token::RParen)) => Err(query_error(*pos, "unmatched `)`")),
+            Some((pos, _)) => Err(query_error(*pos, "unexpected token")),
+        }
+    }
+
+    /// Check whether a reference matches the query.
+    pub fn matches(&self, reference: &Reference) -> bool {
+        let values = FieldValues::new(reference);
+        self.matches_with(reference, &|field| values.get(field))
+    }
+
+    /// Check whether a reference matches the query, with its lowercase field values given by
+    /// `values`, e.g., precomputed by an index.
+    pub(crate) fn matches_with<'a, F>(&self, reference: &Reference, values: &F) -> bool
+    where
+        F: Fn(QueryField) -> Cow<'a, [String]>,
+    {
+        match self {
+            Self::All => true,
+            Self::Term { fields, text } => fields.iter().any(|&field| {
+                values(field)
+                    .iter()
+                    .any(|value| match_text(field, value, text))
+            }),
+            Self::YearRange { start, end } => reference.year.is_some_and(|year| {
+                start.is_none_or(|start| year >= start) && end.is_none_or(|end| year <= end)
+            }),
+            Self::Type(entry_type) => match entry_type {
+                EntryType::Unknown(_) => reference.type_ == *entry_type,
+                _ => reference.type_.to_biblatex() == entry_type.to_biblatex(),
+            },
+            Self::And(lhs, rhs) => {
+                lhs.matches_with(reference, values) && rhs.matches_with(reference, values)
+            }
+            Self::Or(lhs, rhs) => {
+                lhs.matches_with(reference, values) || rhs.matches_with(reference, values)
+            }
+            Self::Not(query) => !query.matches_with(reference, values),
+        }
+    }
+}
+
+impl FromStr for Query {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s)
+    }
+}
+
+fn query_error(position: usize, message: impl Into<String>) -> Error {
+    Error::QueryError {
+        position,
+        message: message.into(),
+    }
+}
+
+/// Match a lowercase field value against a lowercase term.
+///
+/// Years, statuses and ratings are compared exactly, terms with `*` are matched as a glob against the whole value,
+/// other terms as a substring.
+fn match_text(field: QueryField, value: &str, text: &str) -> bool {
+    if text.contains('*') {
+        glob_match(text, value)
+    } else if matches!(
+        field,
+        QueryField::Year | QueryField::Status | QueryField::Rating
+    ) {
+        value == text
+    } else {
+        value.contains(text)
+    }
+}
+
+pub(crate) fn glob_match(pattern: &str, value: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = value.strip_prefix(first) else {
+        return false;
+    };
+    let parts = parts.collect::<Vec<_>>();
+    for (index, part) in parts.iter().enumerate() {
+        if index == parts.len() - 1 {
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(found) => rest = &rest[found + part.len()..],
+            None => return false,
+        }
+    }
+    rest.is_empty()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    /// A word or a phrase, with the field prefix if any
+    Term {
+        field: Option<String>,
+        text: String,
+    },
+    And,
+    Or,
+    Not,
+    LParen,
+    RParen,
+}
+
+/// Split a query into tokens, each with its char position.
+fn tokenize(input: &str) -> Result<Vec<(usize, Token)>> {
+    let chars = input.chars().collect::<Vec<_>>();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '(' {
+            tokens.push((start, Token::LParen));
+            i += 1;
+        } else if c == ')' {
+            tokens.push((start, Token::RParen));
+            i += 1;
+        } else if c == '-' && i + 1 < chars.len() && !chars[i + 1].is_whitespace() {
+            tokens.push((start, Token::Not));
+            i += 1;
+        } else {
+            // A word, which may be a field prefix followed by a phrase.
+            let mut word = String::new();
+            while i < chars.len() && !is_delimiter(chars[i]) {
+                word.push(chars[i]);
+                i += 1;
+                if chars[i - 1] == ':' {
+                    break;
+                }
+            }
+            let field = match word.strip_suffix(':') {
+                Some(field) if !field.is_empty() => Some(field.to_string()),
+                _ => None,
+            };
+            let text = if field.is_some() {
+                if i < chars.len() && chars[i] == '"' {
+                    read_phrase(&chars, &mut i)?
+                } else {
+                    let mut value = String::new();
+                    while i < chars.len() && !is_delimiter(chars[i]) {
+                        value.push(chars[i]);
+                        i += 1;
+                    }
+                    value
+                }
+            } else if c == '"' {
+                read_phrase(&chars, &mut i)?
+            } else {
+                word
+            };
+            let token = match (field.is_none(), text.as_str()) {
+                (true, "AND") => Token::And,
+                (true, "OR") => Token::Or,
+                (true, "NOT") => Token::Not,
+                _ => Token::Term { field, text },
+            };
+            tokens.push((start, token));
+        }
+    }
+    Ok(tokens)
+}
+
+/// A term with an unknown field prefix as plain text or an identifier, see
+/// [`Query::parse_or_plain`].
+fn lenient_token(token: Token) -> Token {
+    let Token::Term {
+        field: Some(prefix),
+        text,
+    } = token
+    else {
+        return token;
+    };
+    if prefix.parse::<QueryField>().is_ok() {
+        return Token::Term {
+            field: Some(prefix),
+            text,
+        };
+    }
+    match identifier(&prefix, &text) {
+        Some((field, id)) => Token::Term {
+            field: Some(field.to_string()),
+            text: id.to_string(),
+        },
+        None => Token::Term {
+            field: None,
+            text: format!("{prefix}:{text}"),
+        },
+    }
+}
+
+/// The field and the identifier of an arXiv identifier or an arXiv or DOI URL, split at the
+/// colon after `arXiv` or the URL scheme.
+///
+/// Versions of arXiv identifiers are dropped, as the `eprint` field usually has none.
+fn identifier<'a>(prefix: &str, text: &'a str) -> Option<(QueryField, &'a str)> {
+    let arxiv = |id: &'a str| {
+        let id = id.trim_end_matches(".pdf");
+        let unversioned = id
+            .rsplit_once('v')
+            .filter(|(id, version)| {
+                id.ends_with(|c: char| c.is_ascii_digit())
+                    && !version.is_empty()
+                    && version.chars().all(|c| c.is_ascii_digit())
+            })
+            .map_or(id, |(id, _)| id);
+        (QueryField::Eprint, unversioned)
+    };
+    if prefix.eq_ignore_ascii_case("arxiv") {
+        return Some(arxiv(text));
+    }
+    if !prefix.eq_ignore_ascii_case("http") && !prefix.eq_ignore_ascii_case("https") {
+        return None;
+    }
+    let url = text.strip_prefix("//")?;
+    let url = url.strip_prefix("www.").unwrap_or(url);
+    if let Some(doi) = url
+        .strip_prefix("doi.org/")
+        .or_else(|| url.strip_prefix("dx.doi.org/"))
+    {
+        return Some((QueryField::Doi, doi));
+    }
+    url.strip_prefix("arxiv.org/abs/")
+        .or_else(|| url.strip_prefix("arxiv.org/pdf/"))
+        .map(arxiv)
+}
+
+fn is_delimiter(c: char) -> bool {
+    c.is_whitespace() || c == '(' || c == ')' || c == '"'
+}
+
+/// Read a quoted phrase starting at the opening quote.
+fn read_phrase(chars: &[char], i: &mut usize) -> Result<String> {
+    let start = *i;
+    *i += 1;
+    let mut phrase = String::new();
+    while *i < chars.len() && chars[*i] != '"' {
+        phrase.push(chars[*i]);
+        *i += 1;
+    }
+    if *i == chars.len() {
+        return Err(query_error(start, "unclosed quote"));
+    }
+    *i += 1;
+    Ok(phrase)
+}
+
+struct Parser<'a> {
+    tokens: Vec<(usize, Token)>,
+    pos: usize,
+    /// Char length of the input, used as the position of errors at the end
+    end: usize,
+    /// Fields of unqualified terms
+    fields: &'a [QueryField],
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&(usize, Token)> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<(usize, Token)> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn parse_or(&mut self) -> Result<Query> {
+        let mut query = self.parse_and()?;
+        while let Some((_, Token::Or)) = self.peek() {
+            self.pos += 1;
+            let rhs = self.parse_and()?;
+            query = Query::Or(Box::new(query), Box::new(rhs));
+        }
+        Ok(query)
+    }
+
+    fn parse_and(&mut self) -> Result<Query> {
+        let mut query = self.parse_unary()?;
+        loop {
+            match self.peek() {
+                Some((_, Token::And)) => {
+                    self.pos += 1;
+                }
+                Some((_, Token::Term { .. } | Token::Not | Token::LParen)) => {}
+                _ => break,
+            }
+            let rhs = self.parse_unary()?;
+            query = Query::And(Box::new(query), Box::new(rhs));
+        }
+        Ok(query)
+    }
+
+    fn parse_unary(&mut self) -> Result<Query> {
+        match self.next() {
+            Some((_, Token::Not)) => Ok(Query::Not(Box::new(self.parse_unary()?))),
+            Some((pos, Token::LParen)) => {
+                if let Some((_, Token::RParen)) = self.peek() {
+                    return Err(query_error(pos, "empty parentheses"));
+                }
+                let query = self.parse_or()?;
+                match self.next() {
+                    Some((_, Token::RParen)) => Ok(query),
+                    _ => Err(query_error(pos, "missing `)`")),
+                }
+            }
+            Some((pos, Token::Term { field, text })) => self.parse_term(pos, field, text),
+            Some((pos, Token::RParen)) => Err(query_error(pos, "unmatched `)`")),
+            Some((pos, token)) => {
+                let operator = match token {
+                    Token::And => "AND",
+                    _ => "OR",
+                };
+                Err(query_error(
+                    pos,
+                    format!("`{operator}` needs a term before it"),
+                ))
+            }
+            None => Err(query_error(self.end, "expected a term at the end")),
+        }
+    }
+
+    fn parse_term(&self, pos: usize, field: Option<String>, text: String) -> Result<Query> {
+        let field = field
+            .map(|field| field.parse::<QueryField>())
+            .transpose()
+            .map_err(|e| query_error(pos, e))?;
+        if text.is_empty() {
+            let field = field.map(|f| f.to_string()).unwrap_or_default();
+            return Err(query_error(pos, format!("`{field}:` needs a value")));
+        }
+        match field {
+            Some(QueryField::Year) if text.contains("..") => {
+                let (start, end) = text.split_once("..").unwrap_or_default();
+                let bound = |s: &str| {
+                    if s.is_empty() {
+                        Ok(None)
+                    } else {
+                        s.parse::<i32>()
+                            .map(Some)
+                            .map_err(|_| query_error(pos, format!("invalid year `{s}`")))
+                    }
+                };
+                let (start, end) = (bound(start)?, bound(end)?);
+                if let (Some(s), Some(e)) = (start, end)
+                    && s > e
+                {
+                    return Err(query_error(pos, format!("empty year range `{text}`")));
+                }
+                Ok(Query::YearRange { start, end })
+            }
+            Some(QueryField::Type) => Ok(Query::Type(EntryType::new(&text))),
+            Some(field) => Ok(Query::Term {
+                fields: vec![field],
+                text: cjk::normalize(&text),
+            }),
+            None => Ok(Query::Term {
+                fields: self.fields.to_vec(),
+                text: cjk::normalize(&text),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::read_bibliography;
+    use biblatex::Bibliography;
+
+    const SRC: &str = r#"
+@article{knuth1984,
+    author = {Donald E. Knuth},
+    title = {Literate Programming},
+    journal = {The Computer Journal},
+    year = {1984},
+    doi = {10.1093/comjnl/27.2.97},
+    keywords = {documentation; WEB},
+}
+@phdthesis{lamport1972,
+    author = {Leslie Lamport},
+    title = {The Analytic Cauchy Problem with Singular Data},
+    school = {Brandeis University},
+    year = {1972},
+}
+@article{trefethen2013,
+    author = {Lloyd N. Trefethen},
+    title = {Approximation Theory and Approximation Practice},
+    journal = {SIAM J. Numer. Anal.},
+    year = {2013},
+    doi = {10.1137/1.9781611975949},
+    abstract = {Chebyshev interpolants converge geometrically for analytic functions.},
+    eprint = {1101.0001},
+    publisher = {SIAM},
+}
+"#;
+
+    fn search(query: &str) -> Vec<String> {
+        let refs = read_bibliography(Bibliography::parse(SRC).unwrap());
+        let query = Query::parse(query).unwrap();
+        refs.iter()
+            .filter(|r| query.matches(r))
+            .map(|r| r.cite_key.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_fields() {
+        assert_eq!(search("author:knuth"), ["knuth1984"]);
+        assert_eq!(search(r#"journal:"SIAM J""#), ["trefethen2013"]);
+        assert_eq!(search("doi:10.1137/*"), ["trefethen2013"]);
+        assert_eq!(search("type:thesis"), ["lamport1972"]);
+        assert_eq!(search("year:1980..2020").len(), 2);
+        assert_eq!(search("year:..1980"), ["lamport1972"]);
+        assert_eq!(search("year:2013"), ["trefethen2013"]);
+    }
+
+    #[test]
+    fn test_more_fields() {
+        assert_eq!(search("keywords:web"), ["knuth1984"]);
+        assert_eq!(search("chebyshev"), ["trefethen2013"]);
+        assert_eq!(search("1101.0001"), ["trefethen2013"]);
+        assert_eq!(search("school:brandeis"), ["lamport1972"]);
+        assert_eq!(search("publisher:siam"), ["trefethen2013"]);
+    }
+
+    #[test]
+    fn test_annotation() {
+        let mut refs = read_bibliography(Bibliography::parse(SRC).unwrap());
+        refs[1].annotation = Some(crate::Annotation {
+            status: crate::ReadingStatus::Read,
+            rating: Some(5),
+            ..Default::default()
+        });
+        let search = |query: &str| {
+            let query = Query::parse(query).unwrap();
+            refs.iter()
+                .filter(|r| query.matches(r))
+                .map(|r| r.cite_key.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(search("status:read"), ["lamport1972"]);
+        assert_eq!(search("status:unread").len(), 2);
+        assert_eq!(search("rating:5"), ["lamport1972"]);
+        assert!(search("read").is_empty());
+    }
+
+    #[test]
+    fn test_cjk() {
+        let src = r#"
+@article{zhang2020,
+    author = {張三},
+    title = {偏微分方程數值方法},
+    journal = {中国科学：数学},
+    year = {2020},
+}
+"#;
+        let refs = read_bibliography(Bibliography::parse(src).unwrap());
+        let matches = |query: &str| Query::parse(query).unwrap().matches(&refs[0]);
+        assert!(matches("zgkx"));
+        assert!(matches("zhongguo kexue"));
+        assert!(matches("author:zhangsan"));
+        assert!(matches("数值方法"));
+        assert!(matches("數值"));
+        assert!(matches("ＺＧＫＸ"));
+        assert!(!matches("zgsx"));
+    }
+
+    #[test]
+    fn test_parse_in() {
+        let refs = read_bibliography(Bibliography::parse(SRC).unwrap());
+        let search_in = |query: &str, fields: &[QueryField]| {
+            let query = Query::parse_in(query, fields).unwrap();
+            refs.iter()
+                .filter(|r| query.matches(r))
+                .map(|r| r.cite_key.clone())
+                .collect::<Vec<_>>()
+        };
+        assert!(search_in("chebyshev", &[QueryField::Title]).is_empty());
+        assert_eq!(
+            search_in("chebyshev", &[QueryField::Abstract]),
+            ["trefethen2013"]
+        );
+        assert!(search_in("siam", &[QueryField::Title]).is_empty());
+        assert_eq!(
+            search_in("author:knuth", &[QueryField::Title]),
+            ["knuth1984"]
+        );
+    }
+
+    #[test]
+    fn test_operators() {
+        assert_eq!(search(r#""literate programming""#), ["knuth1984"]);
+        assert_eq!(search("approximation theory"), ["trefethen2013"]);
+        assert_eq!(search("knuth OR lamport").len(), 2);
+        assert_eq!(search("type:article NOT knuth"), ["trefethen2013"]);
+        assert_eq!(search("type:article -knuth"), ["trefethen2013"]);
+        assert_eq!(search("(knuth OR lamport) AND year:1980.."), ["knuth1984"]);
+        assert_eq!(search("").len(), 3);
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |query: &str| match Query::parse(query) {
+            Err(Error::QueryError { position, .. }) => position,
+            other => panic!("{query}: {other:?}"),
+        };
+        assert_eq!(error("(knuth"), 0);
+        assert_eq!(error("knuth)"), 5);
+        assert_eq!(error("knuth AND"), 9);
+        assert_eq!(error("OR knuth"), 0);
+        assert_eq!(error(r#"title:"literate"#), 6);
+        assert_eq!(error("pages:12"), 0);
+        assert_eq!(error("year:2020..2010"), 0);
+        assert_eq!(error("author:"), 0);
+    }
+
+    #[test]
+    fn test_parse_or_plain() {
+        let refs = read_bibliography(Bibliography::parse(SRC).unwrap());
+        let search = |query: &str| {
+            let query = Query::parse_or_plain(query, QueryField::defaults());
+            refs.iter()
+                .filter(|r| query.matches(r))
+                .map(|r| r.cite_key.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(search("arXiv:1101.0001"), ["trefethen2013"]);
+        assert_eq!(
+            search("https://arxiv.org/abs/1101.0001v2"),
+            ["trefethen2013"]
+        );
+        assert_eq!(
+            search("https://doi.org/10.1137/1.9781611975949"),
+            ["trefethen2013"]
+        );
+        assert!(search("pages:12").is_empty());
+    }
+}
@@ -0,0 +1,375 @@
+//! Ranked search with typo tolerance.
+//!
+//! Each term of a query is scored against the fields of a reference. An exact substring scores
+//! highest, followed by a word within a small edit distance and a fuzzy subsequence. Scores are
+//! weighted by field so that a key prefix ranks above a title, a title above an author and an
+//! author above a journal. Typos and subsequences are only looked for in these short fields, see
+//! [`FUZZY_FIELDS`].
+use crate::{
+    bib::Reference,
+    query::{FieldValues, Query, QueryField, glob_match},
+    search::THRESHOLD_PARALLEL_SIZE,
+};
+use rayon::prelude::*;
+use std::{borrow::Cow, cmp::Reverse};
+
+/// Score of a cite key starting with the term
+const KEY_PREFIX_SCORE: u32 = 200;
+
+/// Fields searched for typos and subsequences, as scoring long fields such as abstracts word by
+/// word is slow and matches almost anything
+const FUZZY_FIELDS: [QueryField; 4] = [
+    QueryField::Key,
+    QueryField::Title,
+    QueryField::Author,
+    QueryField::Journal,
+];
+
+/// Weight of a field in ranked search.
+fn weight(field: QueryField) -> u32 {
+    match field {
+        QueryField::Key => 10,
+        QueryField::Title => 8,
+        QueryField::Author => 6,
+        QueryField::Keywords => 5,
+        QueryField::Journal | QueryField::Year | QueryField::BookTitle => 4,
+        QueryField::Type
+        | QueryField::Doi
+        | QueryField::Eprint
+        | QueryField::Isbn
+        | QueryField::Series => 3,
+        QueryField::Note | QueryField::Publisher | QueryField::School | QueryField::Institution => {
+            2
+        }
+        QueryField::Abstract | QueryField::Status | QueryField::Rating => 1,
+    }
+}
+
+impl Query {
+    /// Score a reference, or `None` if it does not match.
+    ///
+    /// Unlike [`Query::matches`], terms match with typos and as fuzzy subsequences.
+    pub fn score(&self, reference: &Reference) -> Option<u32> {
+        let values = FieldValues::new(reference);
+        self.score_with(reference, &|field| values.get(field))
+    }
+
+    /// Score a reference with its lowercase field values given by `values`.
+    pub(crate) fn score_with<'a, F>(&self, reference: &Reference, values: &F) -> Option<u32>
+    where
+        F: Fn(QueryField) -> Cow<'a, [String]>,
+    {
+        match self {
+            Self::All => Some(0),
+            Self::Term { fields, text } => {
+                let best = |score: fn(QueryField, &str, &str) -> Option<u32>, fuzzy: bool| {
+                    fields
+                        .iter()
+                        .filter(|field| !fuzzy || FUZZY_FIELDS.contains(field))
+                        .filter_map(|&field| {
+                            values(field)
+                                .iter()
+                                .filter_map(|value| score(field, value, text))
+                                .max()
+                        })
+                        .max()
+                };
+                // Typos are only looked for if there is no exact match.
+                best(score_exact, false).or_else(|| best(score_fuzzy, true))
+            }
+            Self::YearRange { .. } | Self::Type(_) => {
+                self.matches_with(reference, values).then_some(0)
+            }
+            Self::And(lhs, rhs) => {
+                Some(lhs.score_with(reference, values)? + rhs.score_with(reference, values)?)
+            }
+            Self::Or(lhs, rhs) => lhs
+                .score_with(reference, values)
+                .max(rhs.score_with(reference, values)),
+            Self::Not(query) => (!query.matches_with(reference, values)).then_some(0),
+        }
+    }
+}
+
+/// Score a lowercase field value containing a lowercase term.
+fn score_exact(field: QueryField, value: &str, term: &str) -> Option<u32> {
+    let weight = weight(field);
+    if term.contains('*') {
+        return glob_match(term, value).then_some(weight * 10);
+    }
+    if matches!(
+        field,
+        QueryField::Year | QueryField::Status | QueryField::Rating
+    ) {
+        return (value == term).then_some(weight * 10);
+    }
+    if field == QueryField::Key && value.starts_with(term) {
+        return Some(KEY_PREFIX_SCORE);
+    }
+    if let Some(pos) = value.find(term) {
+        let at_word = value[..pos]
+            .chars()
+            .next_back()
+            .is_none_or(|c| !c.is_alphanumeric());
+        return Some(weight * if at_word { 10 } else { 8 });
+    }
+    None
+}
+
+/// Score a lowercase field value matching a lowercase term with typos or as a subsequence.
+fn score_fuzzy(field: QueryField, value: &str, term: &str) -> Option<u32> {
+    if term.contains('*')
+        || matches!(
+            field,
+            QueryField::Year | QueryField::Status | QueryField::Rating
+        )
+    {
+        return None;
+    }
+    let weight = weight(field);
+    let len = term.chars().count();
+    if len < 3 {
+        return None;
+    }
+    if field == QueryField::Key {
+        // A mistyped key prefix still ranks above other fields.
+        let prefix = value.chars().take(len).collect::<String>();
+        let distance = strsim::osa_distance(term, &prefix);
+        if distance <= tolerance(len) {
+            return Some(KEY_PREFIX_SCORE - 50 * distance as u32);
+        }
+    }
+    if let Some(distance) = edit_distance(value, term) {
+        return Some(weight * (6 - distance as u32));
+    }
+    subsequence(value, term).map(|ratio| weight * (1 + (ratio * 2.0) as u32))
+}
+
+/// The smallest edit distance between the term and a word of the value, or a prefix of the
+/// word for terms still being typed, if within the tolerance.
+///
+/// Adjacent transpositions count as one edit. Words too short to be within the tolerance are
+/// skipped, and only the prefix of words too long.
+fn edit_distance(value: &str, term: &str) -> Option<usize> {
+    let len = term.chars().count();
+    let tolerance = tolerance(len);
+    value
+        .split(|c: char| !c.is_alphanumeric())
+        .filter_map(|word| {
+            let word_len = word.chars().count();
+            if word_len + tolerance < len {
+                return None;
+            }
+            let whole = (word_len <= len + tolerance).then(|| strsim::osa_distance(term, word));
+            let prefix = (word_len > len).then(|| {
+                let end = word.char_indices().nth(len).map_or(word.len(), |(i, _)| i);
+                strsim::osa_distance(term, &word[..end]) + 1
+            });
+            whole.into_iter().chain(prefix).min()
+        })
+        .min()
+        .filter(|&distance| distance <= tolerance)
+}
+
+/// Maximal edit distance tolerated for a term of the given length.
+pub(crate) fn tolerance(len: usize) -> usize {
+    if len <= 4 { 1 } else { 2 }
+}
+
+/// If the term is a subsequence of the value, the ratio of its length to the length of the
+/// shortest span it occupies, at least one half.
+pub(crate) fn subsequence(value: &str, term: &str) -> Option<f64> {
+    let value = value.chars().collect::<Vec<_>>();
+    let term = term.chars().collect::<Vec<_>>();
+    let mut best = None;
+    for start in 0..value.len() {
+        if value[start] != term[0] {
+            continue;
+        }
+        let mut matched = 1;
+        let mut end = start;
+        for (i, &c) in value.iter().enumerate().skip(start + 1) {
+            if matched == term.len() {
+                break;
+            }
+            if c == term[matched] {
+                matched += 1;
+                end = i;
+            }
+        }
+        if matched == term.len() {
+            let span = end - start + 1;
+            best = Some(best.map_or(span, |best: usize| best.min(span)));
+        }
+    }
+    best.map(|span| term.len() as f64 / span as f64)
+        .filter(|&ratio| ratio >= 0.5)
+}
+
+/// Search for references that match the given query, best match first.
+///
+/// See [`Query`] for the syntax, an invalid query is searched as plain text. References with
+/// equal scores keep their order.
+pub fn rank_references(references: &[Reference], query: &str) -> Vec<Reference> {
+    rank_references_in(references, query, QueryField::defaults())
+}
+
+/// Search for references that match the given query, best match first, looking up unqualified
+/// terms in `fields`.
+pub fn rank_references_in(
+    references: &[Reference],
+    query: &str,
+    fields: &[QueryField],
+) -> Vec<Reference> {
+    rank_by(references, query, fields, |_| 0)
+}
+
+/// Search for references that match the given query, best match first, adding `boost` to the
+/// score of each match, e.g., from [`crate::UsageLog::boosts`].
+pub fn rank_references_boosted<F>(references: &[Reference], query: &str, boost: F) -> Vec<Reference>
+where
+    F: Fn(&Reference) -> u32 + Sync,
+{
+    rank_by(references, query, QueryField::defaults(), boost)
+}
+
+fn rank_by<F>(
+    references: &[Reference],
+    query: &str,
+    fields: &[QueryField],
+    boost: F,
+) -> Vec<Reference>
+where
+    F: Fn(&Reference) -> u32 + Sync,
+{
+    let query = Query::parse_or_plain(query, fields);
+    let score = |(index, reference): (usize, &Reference)| {
+        Some((query.score(reference)? + boost(reference), index))
+    };
+    let mut scored = if references.len() > THRESHOLD_PARALLEL_SIZE {
+        references
+            .par_iter()
+            .enumerate()
+            .filter_map(score)
+            .collect::<Vec<_>>()
+    } else {
+        references
+            .iter()
+            .enumerate()
+            .filter_map(score)
+            .collect::<Vec<_>>()
+    };
+    scored.sort_by_key(|&(score, index)| (Reverse(score), index));
+    scored
+        .into_iter()
+        .map(|(_, index)| references[index].clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::read_bibliography;
+    use biblatex::Bibliography;
+
+    const SRC: &str = r#"
+@article{smith2010,
+    author = {John Smith},
+    title = {Discontinuous Galerkin Methods},
+    journal = {Journal of Galerkin Studies},
+    year = {2010},
+}
+@article{galerkin1915,
+    author = {Boris Galerkin},
+    title = {Rods and Plates},
+    journal = {Vestnik Inzhenerov},
+    year = {1915},
+}
+@book{brenner2008,
+    author = {Susanne Brenner and Ridgway Scott},
+    title = {The Mathematical Theory of Finite Element Methods},
+    publisher = {Springer},
+    year = {2008},
+}
+"#;
+
+    fn rank(query: &str) -> Vec<String> {
+        let refs = read_bibliography(Bibliography::parse(SRC).unwrap());
+        rank_references(&refs, query)
+            .into_iter()
+            .map(|r| r.cite_key)
+            .collect()
+    }
+
+    #[test]
+    fn test_field_weights() {
+        assert_eq!(rank("galerkin"), ["galerkin1915", "smith2010"]);
+        assert_eq!(rank("smith"), ["smith2010"]);
+    }
+
+    #[test]
+    fn test_typos() {
+        assert_eq!(rank("galerkn"), ["galerkin1915", "smith2010"]);
+        assert_eq!(rank("finte element"), ["brenner2008"]);
+        assert_eq!(rank("mthds"), ["smith2010", "brenner2008"]);
+        assert!(rank("xyz").is_empty());
+
+        // Long fields only match exactly
+        let refs = read_bibliography(
+            Bibliography::parse("@article{a, title = {Rods}, abstract = {On elastic plates}}")
+                .unwrap(),
+        );
+        assert_eq!(rank_references(&refs, "elastic").len(), 1);
+        assert!(rank_references(&refs, "elastc").is_empty());
+    }
+
+    #[test]
+    fn test_boost() {
+        let refs = read_bibliography(Bibliography::parse(SRC).unwrap());
+        let boosted = |key: &str, boost: u32| {
+            rank_references_boosted(
+                &refs,
+                "methods",
+                |r| {
+                    if r.cite_key == key { boost } else { 0 }
+                },
+            )
+            .into_iter()
+            .map(|r| r.cite_key)
+            .collect::<Vec<_>>()
+        };
+        assert_eq!(boosted("", 0), ["smith2010", "brenner2008"]);
+        assert_eq!(boosted("brenner2008", 10), ["brenner2008", "smith2010"]);
+        assert!(!boosted("galerkin1915", 100).contains(&"galerkin1915".to_string()));
+    }
+
+    #[test]
+    fn test_query() {
+        assert_eq!(rank("galerkn year:2010"), ["smith2010"]);
+        assert_eq!(rank("methods NOT type:book"), ["smith2010"]);
+    }
+
+    #[test]
+    fn test_annotation() {
+        let mut refs = read_bibliography(Bibliography::parse(SRC).unwrap());
+        refs[0].annotation = Some(crate::Annotation {
+            status: crate::ReadingStatus::Unread,
+            rating: Some(3),
+            ..Default::default()
+        });
+        refs[1].annotation = Some(crate::Annotation {
+            status: crate::ReadingStatus::Read,
+            rating: Some(5),
+            ..Default::default()
+        });
+        let rank = |query: &str| {
+            rank_references(&refs, query)
+                .into_iter()
+                .map(|r| r.cite_key)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(rank("status:read"), ["galerkin1915"]);
+        assert!(rank("status:rea").is_empty());
+        assert_eq!(rank("rating:5"), ["galerkin1915"]);
+    }
+}
@@ -0,0 +1,323 @@
+//! Saved searches, shown as smart groups.
+//!
+//! A [`SavedSearch`] stores a query with the fields it searches, a [`Filter`], a JabRef group and
+//! a [`SortOrder`] under a name. It is run against the current references each time, so the
+//! group follows changes to the library.
+use crate::{
+    bib::Reference,
+    filter::{Filter, filter_references},
+    jabref::JabRefGroup,
+    query::QueryField,
+    rank::rank_references_in,
+    search::search_references_in,
+};
+use biblatex::Bibliography;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+
+/// Order of the references of a saved search.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    /// Best match first, or the order of the library without a query
+    #[default]
+    Relevance,
+    /// Newest first
+    YearDesc,
+    /// Oldest first
+    YearAsc,
+    /// By title
+    Title,
+    /// By the family name of the first author
+    Author,
+    /// By cite key
+    Key,
+}
+
+impl SortOrder {
+    /// All sort orders.
+    pub fn all() -> &'static [Self] {
+        &[
+            Self::Relevance,
+            Self::YearDesc,
+            Self::YearAsc,
+            Self::Title,
+            Self::Author,
+            Self::Key,
+        ]
+    }
+
+    /// Sort references in place, keeping the order of equal ones.
+    ///
+    /// References without the sorted field come last.
+    pub fn sort(self, references: &mut [Reference]) {
+        match self {
+            Self::Relevance => {}
+            Self::YearDesc => references.sort_by_key(|r| (r.year.is_none(), Reverse(r.year))),
+            Self::YearAsc => references.sort_by_key(|r| (r.year.is_none(), r.year)),
+            Self::Title => references.sort_by_cached_key(|r| {
+                let title = r.title.as_ref().map(|title| {
+                    title
+                        .iter()
+                        .map(|chunk| chunk.get().to_lowercase())
+                        .collect::<String>()
+                });
+                (title.is_none(), title)
+            }),
+            Self::Author => references.sort_by_cached_key(|r| {
+                let author = first_author(r);
+                (author.is_none(), author)
+            }),
+            Self::Key => references.sort_by(|a, b| a.cite_key.cmp(&b.cite_key)),
+        }
+    }
+}
+
+/// The lowercase family and given names of the first author of a reference.
+///
+/// [`Reference::author`] keeps names as `Given Family`, so the family name is read from the
+/// source, falling back to the whole name.
+fn first_author(reference: &Reference) -> Option<(String, String)> {
+    let name = reference.author.as_ref()?.first()?;
+    let person = Bibliography::parse(&reference.source)
+        .ok()
+        .and_then(|bibliography| {
+            let authors = bibliography.get(&reference.cite_key)?.author().ok()?;
+            authors.into_iter().next()
+        });
+    Some(match person {
+        Some(person) => (person.name.to_lowercase(), person.given_name.to_lowercase()),
+        None => (name.to_lowercase(), String::new()),
+    })
+}
+
+impl std::fmt::Display for SortOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Relevance => write!(f, "relevance"),
+            Self::YearDesc => write!(f, "newest"),
+            Self::YearAsc => write!(f, "oldest"),
+            Self::Title => write!(f, "title"),
+            Self::Author => write!(f, "author"),
+            Self::Key => write!(f, "key"),
+        }
+    }
+}
+
+/// A named search, e.g., `SPDE 2020+`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedSearch {
+    /// Name of the search
+    pub name: String,
+    /// Query, see [`crate::Query`] for the syntax, empty for every reference
+    #[serde(default)]
+    pub query: String,
+    /// Fields searched by unqualified terms of the query, empty for [`QueryField::defaults`]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<QueryField>,
+    /// Filter applied before the query
+    #[serde(default)]
+    pub filter: Filter,
+    /// JabRef group the references are taken from after the filter, see [`JabRefGroup::members`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jabref_group: Option<String>,
+    /// Order of the results
+    #[serde(default)]
+    pub sort: SortOrder,
+}
+
+impl SavedSearch {
+    pub fn new(name: impl Into<String>, query: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            query: query.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Set the filter.
+    pub fn with_filter(mut self, filter: Filter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Set the fields searched by unqualified terms.
+    pub fn with_fields(mut self, fields: &[QueryField]) -> Self {
+        self.fields = fields.to_vec();
+        self
+    }
+
+    /// Set the JabRef group.
+    pub fn with_jabref_group(mut self, group: Option<String>) -> Self {
+        self.jabref_group = group;
+        self
+    }
+
+    /// Set the sort order.
+    pub fn with_sort(mut self, sort: SortOrder) -> Self {
+        self.sort = sort;
+        self
+    }
+
+    /// The shortcut of the search in the helper, i.e., `#` and the name with spaces replaced by
+    /// `-`.
+    pub fn shortcut(&self) -> String {
+        format!("#{}", self.name.trim().replace(char::is_whitespace, "-"))
+    }
+
+    /// Check whether a shortcut without `#` names this search, ignoring case.
+    pub fn is_named(&self, shortcut: &str) -> bool {
+        self.shortcut()[1..].to_lowercase() == shortcut.to_lowercase()
+    }
+
+    /// Run the search on the references of a library.
+    ///
+    /// `library` is the name of the library, used by [`Filter::Library`], and `groups` its
+    /// JabRef group tree. A search in a JabRef group finds nothing if the group is missing.
+    pub fn run(
+        &self,
+        references: &[Reference],
+        library: Option<&str>,
+        groups: Option<&JabRefGroup>,
+    ) -> Vec<Reference> {
+        let filtered = filter_references(references, &self.filter, library);
+        let filtered = match (&self.jabref_group, groups) {
+            (None, _) => filtered,
+            (Some(name), Some(groups)) => groups.members(name, &filtered),
+            (Some(_), None) => Vec::new(),
+        };
+        let fields = if self.fields.is_empty() {
+            QueryField::defaults()
+        } else {
+            &self.fields
+        };
+        let mut result = if self.query.trim().is_empty() {
+            filtered
+        } else if self.sort == SortOrder::Relevance {
+            rank_references_in(&filtered, &self.query, fields)
+        } else {
+            search_references_in(&filtered, &self.query, fields)
+        };
+        self.sort.sort(&mut result);
+        result
+    }
+}
+
+/// Split a leading `#group` shortcut off a helper query.
+///
+/// ```
+/// use bibcitex_core::split_shortcut;
+///
+/// assert_eq!(split_shortcut("#spde galerkin"), (Some("spde"), "galerkin"));
+/// assert_eq!(split_shortcut("galerkin"), (None, "galerkin"));
+/// ```
+pub fn split_shortcut(query: &str) -> (Option<&str>, &str) {
+    match query.trim_start().strip_prefix('#') {
+        Some(rest) => {
+            let (name, rest) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+            (Some(name), rest.trim_start())
+        }
+        None => (None, query),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::read_bibliography;
+    use biblatex::{Bibliography, EntryType};
+
+    const SRC: &str = r#"
+@article{smith2010,
+    author = {John Smith},
+    title = {Stochastic PDEs},
+    year = {2010},
+}
+@article{lee2021,
+    author = {Ann Lee},
+    title = {Numerical Methods for Stochastic PDEs},
+    year = {2021},
+}
+@misc{zhao2022,
+    author = {Aaron Zhao},
+    title = {An Arxiv Preprint on SPDEs},
+    year = {2022},
+}
+"#;
+
+    fn run(search: &SavedSearch) -> Vec<String> {
+        let refs = read_bibliography(Bibliography::parse(SRC).unwrap());
+        search
+            .run(&refs, None, None)
+            .into_iter()
+            .map(|r| r.cite_key)
+            .collect()
+    }
+
+    #[test]
+    fn test_run() {
+        assert_eq!(run(&SavedSearch::new("all", "")).len(), 3);
+        let spde = SavedSearch::new("SPDE 2020+", "stochastic")
+            .with_filter(Filter::years(Some(2020), None));
+        assert_eq!(run(&spde), ["lee2021"]);
+        let articles = SavedSearch::new("articles", "")
+            .with_filter(Filter::types([EntryType::Article]))
+            .with_sort(SortOrder::YearDesc);
+        assert_eq!(run(&articles), ["lee2021", "smith2010"]);
+        let by_title = SavedSearch::new("titles", "").with_sort(SortOrder::Title);
+        assert_eq!(run(&by_title), ["zhao2022", "lee2021", "smith2010"]);
+        let by_author = SavedSearch::new("authors", "").with_sort(SortOrder::Author);
+        // By family name, not by `Aaron Zhao` first
+        assert_eq!(run(&by_author), ["lee2021", "smith2010", "zhao2022"]);
+    }
+
+    #[test]
+    fn test_reproduces_view() {
+        let src = r#"
+@article{a, title = {Stochastic PDEs}, keywords = {spde}, groups = {Mine}, year = {2010}}
+@article{b, title = {Numerical PDEs}, keywords = {spde}, groups = {Mine}, year = {2011}}
+@article{c, title = {Stochastic Methods}, keywords = {spde}, year = {2012}}
+@book{d, title = {Stochastic PDEs}, keywords = {spde}, groups = {Mine}, year = {2013}}
+@article{e, title = {Other}, abstract = {stochastic}, keywords = {spde}, groups = {Mine}}
+@Comment{jabref-meta: grouping:
+0 AllEntriesGroup:;
+1 StaticGroup:Mine\;0\;1\;\;\;\;;
+}
+"#;
+        let mut refs = read_bibliography(Bibliography::parse(src).unwrap());
+        refs[1].annotation = Some(crate::Annotation {
+            status: crate::ReadingStatus::Read,
+            ..Default::default()
+        });
+        let tree = crate::parse_jabref_groups(src).unwrap();
+        let filter = Filter::types([EntryType::Article])
+            .and(Filter::Status(crate::ReadingStatus::Unread))
+            .and(Filter::Tag("SPDE".to_string()));
+        let fields = [QueryField::Title];
+
+        // As the reference view filters, takes the group and searches
+        let shown = tree.members("Mine", &filter_references(&refs, &filter, Some("lib")));
+        let view = rank_references_in(&shown, "stochastic", &fields);
+
+        let search = SavedSearch::new("view", "stochastic")
+            .with_filter(filter)
+            .with_fields(&fields)
+            .with_jabref_group(Some("Mine".to_string()));
+        assert_eq!(search.run(&refs, Some("lib"), Some(&tree)), view);
+        assert_eq!(view.len(), 1);
+        assert!(search.run(&refs, Some("lib"), None).is_empty());
+
+        let json = serde_json::to_string(&search).unwrap();
+        assert_eq!(serde_json::from_str::<SavedSearch>(&json).unwrap(), search);
+    }
+
+    #[test]
+    fn test_shortcut() {
+        let search = SavedSearch::new("SPDE 2020+", "");
+        assert_eq!(search.shortcut(), "#SPDE-2020+");
+        assert!(search.is_named("spde-2020+"));
+        assert!(!search.is_named("spde"));
+        assert_eq!(split_shortcut("#spde"), (Some("spde"), ""));
+        assert_eq!(split_shortcut("  #spde  x y"), (Some("spde"), "x y"));
+    }
+}
@@ -1,9 +1,31 @@
-use crate::bib::Reference;
+use crate::{
+    bib::Reference,
+    query::{Query, QueryField},
+};
 use rayon::prelude::*;
 
-const THRESHOLD_PARALLEL_SIZE: usize = 100;
+pub(crate) const THRESHOLD_PARALLEL_SIZE: usize = 100;
+
+/// Search for references that match a parsed query sequentially or in parallel.
+pub fn query_references(references: &[Reference], query: &Query) -> Vec<Reference> {
+    if references.len() > THRESHOLD_PARALLEL_SIZE {
+        references
+            .par_iter()
+            .filter(|&reference| query.matches(reference))
+            .cloned()
+            .collect()
+    } else {
+        references
+            .iter()
+            .filter(|&reference| query.matches(reference))
+            .cloned()
+            .collect()
+    }
+}
 
 /// Search for references that match the given query.
+///
+/// See [`Query`] for the syntax. An invalid query is searched as plain text.
 pub fn search_references(references: &[Reference], query: &str) -> Vec<Reference> {
     if references.len() > THRESHOLD_PARALLEL_SIZE {
         par_search_references(references, query)
@@ -12,122 +34,51 @@ pub fn search_references(references: &[Reference], query: &str) -> Vec<Reference
     }
 }
 
+/// Search for references that match the given query, looking up unqualified terms in `fields`.
+pub fn search_references_in(
+    references: &[Reference],
+    query: &str,
+    fields: &[QueryField],
+) -> Vec<Reference> {
+    query_references(references, &Query::parse_or_plain(query, fields))
+}
+
 /// Search for references that match the given query in parallel.
 pub fn par_search_references(references: &[Reference], query: &str) -> Vec<Reference> {
-    let query = query.trim().to_lowercase();
+    let query = Query::parse_or_plain(query, QueryField::defaults());
     references
         .par_iter()
-        .filter(|&reference| check(reference, &query))
+        .filter(|&reference| query.matches(reference))
         .cloned()
         .collect()
 }
 
 /// Search for references that match the given query sequentially.
 pub fn seq_search_references(references: &[Reference], query: &str) -> Vec<Reference> {
-    let query = query.trim().to_lowercase();
+    let query = Query::parse_or_plain(query, QueryField::defaults());
     references
         .iter()
-        .filter(|&reference| check(reference, &query))
+        .filter(|&reference| query.matches(reference))
         .cloned()
         .collect()
 }
 
-fn check_key(reference: &Reference, query: &str) -> bool {
-    reference.cite_key.to_lowercase().contains(query)
-}
-
-fn check_title(reference: &Reference, query: &str) -> bool {
-    if query.contains('-') {
-        let mut queries = query.split('-');
-        queries.all(|q| _check_title(reference, q))
-    } else {
-        _check_title(reference, query)
-    }
-}
-
-fn _check_title(reference: &Reference, query: &str) -> bool {
-    if let Some(title) = &reference.title {
-        for chunk in title {
-            if chunk.get().to_lowercase().contains(query) {
-                return true;
-            }
-        }
-    }
-    false
-}
-
-fn check_author(reference: &Reference, query: &str) -> bool {
-    if let Some(author) = &reference.author {
-        for name in author {
-            if name.to_lowercase().contains(query) {
-                return true;
-            }
-        }
-    }
-    false
-}
-
-fn check_journal(reference: &Reference, query: &str) -> bool {
-    if let Some(journal) = &reference.journal {
-        journal.to_lowercase().contains(query)
-    } else {
-        false
-    }
-}
-
-fn check_full_journal(reference: &Reference, query: &str) -> bool {
-    if let Some(journal) = &reference.full_journal {
-        journal.to_lowercase().contains(query)
-    } else {
-        false
-    }
-}
-
-fn check_year(reference: &Reference, query: &str) -> bool {
-    if let Some(year) = &reference.year {
-        year.to_string().eq(query)
-    } else {
-        false
-    }
-}
-
-fn check_note(reference: &Reference, query: &str) -> bool {
-    if let Some(note) = &reference.note {
-        for chunk in note {
-            if chunk.get().to_lowercase().contains(query) {
-                return true;
-            }
-        }
-    }
-    false
-}
-
-fn check(reference: &Reference, query: &str) -> bool {
-    check_key(reference, query)
-        || check_title(reference, query)
-        || check_author(reference, query)
-        || check_journal(reference, query)
-        || check_full_journal(reference, query)
-        || check_year(reference, query)
-        || check_note(reference, query)
-}
-
 /// Search for references that match the given author sequentially.
 pub fn seq_search_references_by_author(references: &[Reference], query: &str) -> Vec<Reference> {
-    let query = query.trim().to_lowercase();
+    let query = Query::parse_or_plain(query, &[QueryField::Author]);
     references
         .iter()
-        .filter(|reference| check_author(reference, &query))
+        .filter(|reference| query.matches(reference))
         .cloned()
         .collect()
 }
 
 /// Search for references that match the given author in parallel.
 pub fn par_search_references_by_author(references: &[Reference], query: &str) -> Vec<Reference> {
-    let query = query.trim().to_lowercase();
+    let query = Query::parse_or_plain(query, &[QueryField::Author]);
     references
         .par_iter()
-        .filter(|reference| check_author(reference, &query))
+        .filter(|reference| query.matches(reference))
         .cloned()
         .collect()
 }
@@ -143,20 +94,20 @@ pub fn search_references_by_author(references: &[Reference], query: &str) -> Vec
 
 /// Search for references that match the given title sequentially.
 pub fn seq_search_references_by_title(references: &[Reference], query: &str) -> Vec<Reference> {
-    let query = query.trim().to_lowercase();
+    let query = Query::parse_or_plain(query, &[QueryField::Title]);
     references
         .iter()
-        .filter(|reference| check_title(reference, &query))
+        .filter(|reference| query.matches(reference))
         .cloned()
         .collect()
 }
 
 /// Search for references that match the given title in parallel.
 pub fn par_search_references_by_title(references: &[Reference], query: &str) -> Vec<Reference> {
-    let query = query.trim().to_lowercase();
+    let query = Query::parse_or_plain(query, &[QueryField::Title]);
     references
         .par_iter()
-        .filter(|reference| check_title(reference, &query))
+        .filter(|reference| query.matches(reference))
         .cloned()
         .collect()
 }
@@ -172,20 +123,20 @@ pub fn search_references_by_title(references: &[Reference], query: &str) -> Vec<
 
 /// Search for references that match the given journal sequentially.
 pub fn seq_search_references_by_journal(references: &[Reference], query: &str) -> Vec<Reference> {
-    let query = query.trim().to_lowercase();
+    let query = Query::parse_or_plain(query, &[QueryField::Journal]);
     references
         .iter()
-        .filter(|reference| check_journal(reference, &query))
+        .filter(|reference| query.matches(reference))
         .cloned()
         .collect()
 }
 
 /// Search for references that match the given journal in parallel.
 pub fn par_search_references_by_journal(references: &[Reference], query: &str) -> Vec<Reference> {
-    let query = query.trim().to_lowercase();
+    let query = Query::parse_or_plain(query, &[QueryField::Journal]);
     references
         .par_iter()
-        .filter(|reference| check_journal(reference, &query))
+        .filter(|reference| query.matches(reference))
         .cloned()
         .collect()
 }
@@ -201,20 +152,20 @@ pub fn search_references_by_journal(references: &[Reference], query: &str) -> Ve
 
 /// Search for references that match the given year sequentially.
 pub fn seq_search_references_by_year(references: &[Reference], query: &str) -> Vec<Reference> {
-    let query = query.trim().to_lowercase();
+    let query = Query::parse_or_plain(query, &[QueryField::Year]);
     references
         .iter()
-        .filter(|reference| check_year(reference, &query))
+        .filter(|reference| query.matches(reference))
         .cloned()
         .collect()
 }
 
 /// Search for references that match the given year in parallel.
 pub fn par_search_references_by_year(references: &[Reference], query: &str) -> Vec<Reference> {
-    let query = query.trim().to_lowercase();
+    let query = Query::parse_or_plain(query, &[QueryField::Year]);
     references
         .par_iter()
-        .filter(|reference| check_year(reference, &query))
+        .filter(|reference| query.matches(reference))
         .cloned()
         .collect()
 }
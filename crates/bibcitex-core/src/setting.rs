@@ -1,4 +1,8 @@
-use crate::{Error, Result, bib::parse};
+use crate::{
+    BibSource, CitationTemplate, CslStyle, DEFAULT_PROTECTED_WORDS, DEFAULT_TAG_SEPARATORS,
+    Encoding, Error, JournalAbbreviation, JournalDatabase, JournalStyle, Result, SavedSearch,
+    TidyOptions, bib::parse_with_encoding, encoding_for_label,
+};
 use biblatex::Bibliography;
 use chrono::{DateTime, Local};
 use fs_err as fs;
@@ -17,13 +21,87 @@ pub struct BibliographyInfo {
     /// Description
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+    /// Name of the citation template overriding the default one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    /// Journal names used in the output, `None` to keep them as written
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub journal_style: Option<JournalStyle>,
+    /// Encoding label of the file, e.g., `latin1` or `gbk`, `None` to detect it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<String>,
+}
+
+impl BibliographyInfo {
+    /// The encoding of the file set by the user, `None` to detect it
+    pub fn encoding(&self) -> Result<Option<&'static Encoding>> {
+        self.encoding.as_deref().map(encoding_for_label).transpose()
+    }
+
+    /// Parse the bibliography file in its encoding
+    pub fn parse(&self) -> Result<Bibliography> {
+        parse_with_encoding(&self.path, self.encoding()?)
+    }
+
+    /// Read the source of the bibliography file in its encoding, e.g., to edit and write it back
+    pub fn read_source(&self) -> Result<BibSource> {
+        BibSource::read(&self.path, self.encoding()?)
+    }
 }
 
 /// Setting for BibCiTeX
-#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Setting {
     /// List of bibliographies
     pub bibliographies: BTreeMap<String, BibliographyInfo>,
+    /// User-provided CSL style files
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub csl_styles: BTreeMap<String, PathBuf>,
+    /// Citation templates used by the copy and paste actions
+    #[serde(default = "CitationTemplate::presets")]
+    pub templates: Vec<CitationTemplate>,
+    /// Name of the default citation template
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_template: Option<String>,
+    /// Saved searches shown as smart groups
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub saved_searches: Vec<SavedSearch>,
+    /// Separators of tags in the `keywords` field
+    #[serde(default = "default_tag_separators")]
+    pub tag_separators: String,
+    /// Journal abbreviations added by the user, taking precedence over the bundled ones
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub journal_abbreviations: Vec<JournalAbbreviation>,
+    /// Proper nouns whose casing is kept and protected in titles
+    #[serde(default = "default_protected_words")]
+    pub protected_words: Vec<String>,
+    /// Options of tidying `.bib` files
+    #[serde(default)]
+    pub tidy: TidyOptions,
+}
+
+fn default_tag_separators() -> String {
+    DEFAULT_TAG_SEPARATORS.to_string()
+}
+
+fn default_protected_words() -> Vec<String> {
+    DEFAULT_PROTECTED_WORDS.map(str::to_string).to_vec()
+}
+
+impl Default for Setting {
+    fn default() -> Self {
+        Self {
+            bibliographies: BTreeMap::new(),
+            csl_styles: BTreeMap::new(),
+            templates: CitationTemplate::presets(),
+            default_template: None,
+            saved_searches: Vec::new(),
+            tag_separators: default_tag_separators(),
+            journal_abbreviations: Vec::new(),
+            protected_words: default_protected_words(),
+            tidy: TidyOptions::default(),
+        }
+    }
 }
 
 impl Setting {
@@ -110,6 +188,9 @@ impl Setting {
                     created_at: info.created_at,
                     updated_at: Local::now(),
                     description,
+                    template: info.template.clone(),
+                    journal_style: info.journal_style,
+                    encoding: info.encoding.clone(),
                 },
             ))
         } else {
@@ -121,6 +202,9 @@ impl Setting {
                     created_at,
                     updated_at: created_at,
                     description,
+                    template: None,
+                    journal_style: None,
+                    encoding: None,
                 },
             ))
         }
@@ -133,12 +217,173 @@ impl Setting {
         self.bibliographies.remove(name)
     }
 
+    /// The citation template of a bibliography
+    ///
+    /// The template of the bibliography takes precedence over the default template.
+    /// If neither is set or found, bare cite keys are used.
+    pub fn citation_template(&self, bibliography: Option<&str>) -> CitationTemplate {
+        bibliography
+            .and_then(|name| self.bibliographies.get(name))
+            .and_then(|info| info.template.as_deref())
+            .or(self.default_template.as_deref())
+            .and_then(|name| self.templates.iter().find(|t| t.name == name))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Add or update a citation template
+    ///
+    /// If the name already exists, the old template will be returned, otherwise `None` will be returned.
+    pub fn add_update_template(&mut self, template: CitationTemplate) -> Option<CitationTemplate> {
+        if let Some(old) = self.templates.iter_mut().find(|t| t.name == template.name) {
+            Some(std::mem::replace(old, template))
+        } else {
+            self.templates.push(template);
+            None
+        }
+    }
+
+    /// Remove a citation template, and reset the default or bibliography templates using it.
+    ///
+    /// If the name does not exist, `None` will be returned.
+    pub fn remove_template(&mut self, name: &str) -> Option<CitationTemplate> {
+        let index = self.templates.iter().position(|t| t.name == name)?;
+        if self.default_template.as_deref() == Some(name) {
+            self.default_template = None;
+        }
+        for info in self.bibliographies.values_mut() {
+            if info.template.as_deref() == Some(name) {
+                info.template = None;
+            }
+        }
+        Some(self.templates.remove(index))
+    }
+
+    /// Set the default citation template, `None` for bare cite keys.
+    pub fn set_default_template(&mut self, name: Option<&str>) -> Result<()> {
+        self.default_template = self.check_template(name)?;
+        Ok(())
+    }
+
+    /// Set the citation template of a bibliography, `None` to follow the default template.
+    pub fn set_bibliography_template(
+        &mut self,
+        bibliography: &str,
+        name: Option<&str>,
+    ) -> Result<()> {
+        let template = self.check_template(name)?;
+        let info = self
+            .bibliographies
+            .get_mut(bibliography)
+            .ok_or(Error::BibNotFound(bibliography.to_string()))?;
+        info.template = template;
+        Ok(())
+    }
+
+    fn check_template(&self, name: Option<&str>) -> Result<Option<String>> {
+        match name {
+            Some(name) if !self.templates.iter().any(|t| t.name == name) => {
+                Err(Error::TemplateNotFound(name.to_string()))
+            }
+            _ => Ok(name.map(str::to_string)),
+        }
+    }
+
+    /// Add or update a saved search
+    ///
+    /// If the name already exists, the old search will be returned, otherwise `None` will be returned.
+    pub fn add_update_saved_search(&mut self, search: SavedSearch) -> Option<SavedSearch> {
+        if let Some(old) = self
+            .saved_searches
+            .iter_mut()
+            .find(|s| s.name == search.name)
+        {
+            Some(std::mem::replace(old, search))
+        } else {
+            self.saved_searches.push(search);
+            None
+        }
+    }
+
+    /// Remove a saved search
+    ///
+    /// If the name does not exist, `None` will be returned.
+    pub fn remove_saved_search(&mut self, name: &str) -> Option<SavedSearch> {
+        let index = self.saved_searches.iter().position(|s| s.name == name)?;
+        Some(self.saved_searches.remove(index))
+    }
+
+    /// Find a saved search by its `#group` shortcut, see [`SavedSearch::shortcut`]
+    pub fn saved_search_by_shortcut(&self, shortcut: &str) -> Option<&SavedSearch> {
+        self.saved_searches.iter().find(|s| s.is_named(shortcut))
+    }
+
+    /// The bundled journal abbreviations with those added by the user.
+    pub fn journal_database(&self) -> JournalDatabase {
+        let mut database = JournalDatabase::bundled();
+        database.extend(self.journal_abbreviations.iter().cloned());
+        database
+    }
+
+    /// Add or update a journal abbreviation by full name
+    ///
+    /// If the journal already exists, the old abbreviation will be returned, otherwise `None` will be returned.
+    pub fn add_update_journal_abbreviation(
+        &mut self,
+        abbreviation: JournalAbbreviation,
+    ) -> Option<JournalAbbreviation> {
+        if let Some(old) = self
+            .journal_abbreviations
+            .iter_mut()
+            .find(|j| j.full == abbreviation.full)
+        {
+            Some(std::mem::replace(old, abbreviation))
+        } else {
+            self.journal_abbreviations.push(abbreviation);
+            None
+        }
+    }
+
+    /// Remove a journal abbreviation added by the user
+    ///
+    /// If the journal does not exist, `None` will be returned.
+    pub fn remove_journal_abbreviation(&mut self, full: &str) -> Option<JournalAbbreviation> {
+        let index = self
+            .journal_abbreviations
+            .iter()
+            .position(|j| j.full == full)?;
+        Some(self.journal_abbreviations.remove(index))
+    }
+
+    /// Add or update a user-provided CSL style
+    ///
+    /// The style file is validated before it is stored. Returns the loaded style.
+    pub fn add_csl_style(&mut self, name: &str, path: PathBuf) -> Result<CslStyle> {
+        let style = CslStyle::from_file(&path)?;
+        self.csl_styles.insert(name.to_string(), path);
+        Ok(style)
+    }
+
+    /// Remove a user-provided CSL style
+    pub fn remove_csl_style(&mut self, name: &str) -> Option<PathBuf> {
+        self.csl_styles.remove(name)
+    }
+
+    /// Load a user-provided CSL style by name
+    pub fn load_csl_style(&self, name: &str) -> Result<CslStyle> {
+        let path = self
+            .csl_styles
+            .get(name)
+            .ok_or(Error::CSLError(format!("CSL style {name} not found")))?;
+        CslStyle::from_file(path)
+    }
+
     pub fn parse(&self, name: &str) -> Result<Bibliography> {
         let info = self
             .bibliographies
             .get(name)
             .ok_or(Error::BibNotFound(name.to_string()))?;
-        parse(&info.path)
+        info.parse()
     }
 }
 
@@ -166,6 +411,70 @@ mod tests {
         Setting::delete().unwrap();
     }
 
+    #[test]
+    fn test_citation_template() {
+        let mut setting = Setting::default();
+        let path = PathBuf::from("Cargo.toml");
+        setting
+            .add_update_bibliography("test", path.clone(), None)
+            .unwrap();
+        assert_eq!(
+            setting.citation_template(Some("test")),
+            CitationTemplate::key()
+        );
+        setting.set_default_template(Some("LaTeX")).unwrap();
+        assert_eq!(setting.citation_template(Some("test")).name, "LaTeX");
+        setting
+            .set_bibliography_template("test", Some("Pandoc"))
+            .unwrap();
+        assert_eq!(setting.citation_template(Some("test")).name, "Pandoc");
+        assert_eq!(setting.citation_template(None).name, "LaTeX");
+        assert!(setting.set_default_template(Some("missing")).is_err());
+        setting.remove_template("Pandoc");
+        assert_eq!(setting.citation_template(Some("test")).name, "LaTeX");
+    }
+
+    #[test]
+    fn test_saved_search() {
+        let mut setting = Setting::default();
+        let search = SavedSearch::new("My papers", "author:smith");
+        assert!(setting.add_update_saved_search(search.clone()).is_none());
+        let updated = SavedSearch::new("My papers", "author:lee");
+        assert_eq!(
+            setting.add_update_saved_search(updated.clone()),
+            Some(search)
+        );
+        assert_eq!(
+            setting.saved_search_by_shortcut("my-papers"),
+            Some(&updated)
+        );
+        assert_eq!(setting.remove_saved_search("My papers"), Some(updated));
+        assert!(setting.saved_searches.is_empty());
+    }
+
+    #[test]
+    fn test_journal_abbreviation() {
+        let mut setting = Setting::default();
+        let full = "SIAM Journal on Numerical Analysis";
+        let sinum = JournalAbbreviation::new(full, "SINUM");
+        assert!(
+            setting
+                .add_update_journal_abbreviation(sinum.clone())
+                .is_none()
+        );
+        assert_eq!(
+            setting
+                .journal_database()
+                .abbreviate("SIAM J. Numer. Anal."),
+            Some("SINUM")
+        );
+        assert_eq!(setting.remove_journal_abbreviation(full), Some(sinum));
+        assert_eq!(
+            setting.journal_database().abbreviate(full),
+            Some("SIAM J. Numer. Anal.")
+        );
+    }
+
     #[test]
     fn test_update() {
         let mut setting = Setting::load();
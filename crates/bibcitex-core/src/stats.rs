@@ -0,0 +1,352 @@
+//! Statistics of a library for reports.
+//!
+//! [`LibraryStats`] counts the entries of a library by type, year, author, co-author pair, journal
+//! and publisher, and the entries with a DOI, URL or PDF. The statistics can be exported as CSV
+//! with [`LibraryStats::to_csv`] or as a Markdown summary with [`LibraryStats::to_markdown`].
+use crate::{
+    bib::Reference,
+    coauthor::{CoauthorGraph, GraphFilter, normalize_author},
+    filter::{Filter, filter_references},
+};
+use std::{collections::HashMap, fmt::Write};
+
+/// Longest span of years whose years without entries are listed, so that a mistyped year such
+/// as `20100` does not list thousands of empty years
+const MAX_YEAR_SPAN: i32 = 300;
+
+/// Statistics of the references of a library.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LibraryStats {
+    /// Number of entries
+    pub total: usize,
+    /// Entries per type, most frequent first
+    pub entry_types: Vec<(String, usize)>,
+    /// Entries per year, from the earliest to the latest year, including years without entries
+    /// unless they span more than [`MAX_YEAR_SPAN`] years
+    pub years: Vec<(i32, usize)>,
+    /// Entries without a year
+    pub undated: usize,
+    /// Entries per author, most frequent first, matching names by [`normalize_author`]
+    pub authors: Vec<(String, usize)>,
+    /// Entries per pair of co-authors, most frequent first, as the edges of the
+    /// [`CoauthorGraph`]
+    pub coauthors: Vec<((String, String), usize)>,
+    /// Entries per journal, most frequent first
+    pub journals: Vec<(String, usize)>,
+    /// Entries per publisher, most frequent first
+    pub publishers: Vec<(String, usize)>,
+    /// Entries with a DOI
+    pub with_doi: usize,
+    /// Entries with a URL
+    pub with_url: usize,
+    /// Entries with a PDF in the `file` field, e.g., `paper.pdf` or JabRef's `:paper.pdf:PDF`
+    pub with_file: usize,
+}
+
+/// Count names with the same `key`, ignoring surrounding whitespace, most frequent first.
+///
+/// The first spelling of a name is kept.
+fn count<'a>(
+    names: impl IntoIterator<Item = &'a str>,
+    key: impl Fn(&str) -> String,
+) -> Vec<(String, usize)> {
+    let mut counts: Vec<(String, usize)> = Vec::new();
+    let mut positions = HashMap::new();
+    for name in names {
+        let name = name.trim();
+        if name.is_empty() {
+            continue;
+        }
+        let position = *positions.entry(key(name)).or_insert_with(|| {
+            counts.push((name.to_string(), 0));
+            counts.len() - 1
+        });
+        counts[position].1 += 1;
+    }
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts
+}
+
+impl LibraryStats {
+    /// Statistics of the references of a library matching the filter.
+    ///
+    /// `library` is the name of the library, used by [`Filter::Library`].
+    pub fn new(references: &[Reference], filter: &Filter, library: Option<&str>) -> Self {
+        let references = &filter_references(references, filter, library);
+        let entry_types = references
+            .iter()
+            .map(|r| r.type_.to_string())
+            .collect::<Vec<_>>();
+
+        let mut per_year = HashMap::new();
+        for year in references.iter().filter_map(|r| r.year) {
+            *per_year.entry(year).or_insert(0) += 1;
+        }
+        let years = match (per_year.keys().min(), per_year.keys().max()) {
+            (Some(&first), Some(&last)) if last - first <= MAX_YEAR_SPAN => (first..=last)
+                .map(|year| (year, per_year.get(&year).copied().unwrap_or_default()))
+                .collect(),
+            _ => {
+                let mut years = per_year.into_iter().collect::<Vec<_>>();
+                years.sort();
+                years
+            }
+        };
+
+        let graph = CoauthorGraph::new(references, &GraphFilter::default(), library);
+        let labels = graph
+            .nodes
+            .iter()
+            .map(|node| (node.id.as_str(), node.label.as_str()))
+            .collect::<HashMap<_, _>>();
+        let coauthors = graph
+            .edges
+            .iter()
+            .map(|edge| {
+                let pair = (
+                    labels[edge.source.as_str()].to_string(),
+                    labels[edge.target.as_str()].to_string(),
+                );
+                (pair, edge.weight)
+            })
+            .collect();
+
+        let present = |field: fn(&Reference) -> &Option<String>| {
+            references
+                .iter()
+                .filter(|r| field(r).as_deref().is_some_and(|v| !v.trim().is_empty()))
+                .count()
+        };
+
+        Self {
+            total: references.len(),
+            entry_types: count(entry_types.iter().map(String::as_str), str::to_lowercase),
+            years,
+            undated: references.iter().filter(|r| r.year.is_none()).count(),
+            authors: count(
+                references
+                    .iter()
+                    .filter_map(|r| r.author.as_ref())
+                    .flatten()
+                    .map(String::as_str),
+                normalize_author,
+            ),
+            coauthors,
+            journals: count(
+                references.iter().filter_map(|r| r.journal.as_deref()),
+                str::to_lowercase,
+            ),
+            publishers: count(
+                references
+                    .iter()
+                    .filter_map(|r| r.publisher.as_ref())
+                    .flatten()
+                    .map(String::as_str),
+                str::to_lowercase,
+            ),
+            with_doi: present(|r| &r.doi),
+            with_url: present(|r| &r.url),
+            with_file: references.iter().filter(|r| has_pdf(r)).count(),
+        }
+    }
+
+    /// Percentage of the entries, `0` for an empty library.
+    pub fn share(&self, count: usize) -> f64 {
+        if self.total == 0 {
+            0.0
+        } else {
+            count as f64 * 100.0 / self.total as f64
+        }
+    }
+
+    /// Export all statistics as CSV with the columns `category`, `name` and `count`.
+    ///
+    /// Co-author pairs are written as `first & second`.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("category,name,count\n");
+        let mut row = |category: &str, name: &str, count: usize| {
+            writeln!(csv, "{category},{},{count}", csv_field(name)).unwrap();
+        };
+        row("total", "entries", self.total);
+        for (name, n) in &self.entry_types {
+            row("type", name, *n);
+        }
+        for (year, n) in &self.years {
+            row("year", &year.to_string(), *n);
+        }
+        row("year", "undated", self.undated);
+        for (name, n) in &self.authors {
+            row("author", name, *n);
+        }
+        for ((first, second), n) in &self.coauthors {
+            row("coauthors", &format!("{first} & {second}"), *n);
+        }
+        for (name, n) in &self.journals {
+            row("journal", name, *n);
+        }
+        for (name, n) in &self.publishers {
+            row("publisher", name, *n);
+        }
+        row("field", "doi", self.with_doi);
+        row("field", "url", self.with_url);
+        row("field", "pdf", self.with_file);
+        csv
+    }
+
+    /// Summarize the statistics in Markdown, listing at most `top` authors, co-author pairs,
+    /// journals and publishers.
+    pub fn to_markdown(&self, title: &str, top: usize) -> String {
+        let mut md = format!("# {title}\n\n");
+        writeln!(md, "{} entries.\n", self.total).unwrap();
+
+        md.push_str("## Fields\n\n| Field | Entries | Share |\n| --- | ---: | ---: |\n");
+        for (name, n) in [
+            ("DOI", self.with_doi),
+            ("URL", self.with_url),
+            ("PDF", self.with_file),
+        ] {
+            writeln!(md, "| {name} | {n} | {:.1}% |", self.share(n)).unwrap();
+        }
+
+        md.push_str("\n## Entry types\n\n");
+        table(&mut md, "Type", &self.entry_types, usize::MAX);
+
+        md.push_str("\n## Publications per year\n\n| Year | Entries |\n| --- | ---: |\n");
+        for (year, n) in &self.years {
+            writeln!(md, "| {year} | {n} |").unwrap();
+        }
+        if self.undated > 0 {
+            writeln!(md, "| Undated | {} |", self.undated).unwrap();
+        }
+
+        md.push_str("\n## Top authors\n\n");
+        table(&mut md, "Author", &self.authors, top);
+        md.push_str("\n## Top co-author pairs\n\n");
+        let pairs = self
+            .coauthors
+            .iter()
+            .map(|((first, second), n)| (format!("{first} & {second}"), *n))
+            .collect::<Vec<_>>();
+        table(&mut md, "Co-authors", &pairs, top);
+        md.push_str("\n## Top journals\n\n");
+        table(&mut md, "Journal", &self.journals, top);
+        md.push_str("\n## Top publishers\n\n");
+        table(&mut md, "Publisher", &self.publishers, top);
+        md
+    }
+}
+
+/// Whether a reference has a PDF among the files of its `file` field, separated by `;`.
+fn has_pdf(reference: &Reference) -> bool {
+    reference.file.as_deref().is_some_and(|files| {
+        files.split(';').any(|file| {
+            let file = file.trim().to_lowercase();
+            file.ends_with(".pdf") || file.ends_with(":pdf")
+        })
+    })
+}
+
+/// Write the first `top` counts as a Markdown table.
+fn table(md: &mut String, header: &str, counts: &[(String, usize)], top: usize) {
+    if counts.is_empty() {
+        md.push_str("None.\n");
+        return;
+    }
+    writeln!(md, "| {header} | Entries |\n| --- | ---: |").unwrap();
+    for (name, n) in counts.iter().take(top) {
+        writeln!(md, "| {} | {n} |", name.replace('|', "\\|")).unwrap();
+    }
+}
+
+/// Quote a CSV field containing a comma, quote or line break.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::read_bibliography;
+    use biblatex::Bibliography;
+
+    const SRC: &str = r#"
+@article{a,
+  author = {Smith, John and Doe, Jane},
+  title = {First},
+  journal = {SIAM Review},
+  year = {2010},
+  doi = {10.1/a},
+  file = {Full text:papers/a.PDF:PDF},
+}
+@article{b,
+  author = {Doe, Jane and Smith, J{ohn} and Roe, Richard},
+  title = {Second},
+  journal = {siam review},
+  year = {2012},
+  url = {https://example.com},
+}
+@book{c,
+  author = {Knuth, Donald E.},
+  title = {The {\TeX}book},
+  publisher = {Addison-Wesley, Reading},
+  file = {notes.txt},
+}
+"#;
+
+    fn stats() -> LibraryStats {
+        let refs = read_bibliography(Bibliography::parse(SRC).unwrap());
+        LibraryStats::new(&refs, &Filter::All, None)
+    }
+
+    #[test]
+    fn test_stats() {
+        let stats = stats();
+        assert_eq!(stats.total, 3);
+        assert_eq!(
+            stats.entry_types,
+            [("article".to_string(), 2), ("book".to_string(), 1)]
+        );
+        assert_eq!(stats.years, [(2010, 1), (2011, 0), (2012, 1)]);
+        assert_eq!(stats.undated, 1);
+        assert_eq!(stats.authors[0], ("Jane Doe".to_string(), 2));
+        assert_eq!(stats.authors[1], ("John Smith".to_string(), 2));
+        assert_eq!(
+            stats.coauthors[0],
+            (("Jane Doe".to_string(), "John Smith".to_string()), 2)
+        );
+        assert_eq!(stats.coauthors.len(), 3);
+        assert_eq!(stats.journals, [("SIAM Review".to_string(), 2)]);
+        assert_eq!((stats.with_doi, stats.with_url, stats.with_file), (1, 1, 1));
+        assert!((stats.share(1) - 33.3).abs() < 0.1);
+
+        let refs = read_bibliography(
+            Bibliography::parse("@misc{a, year = {2010}}\n@misc{b, year = {20100}}").unwrap(),
+        );
+        let stats = LibraryStats::new(&refs, &Filter::All, None);
+        assert_eq!(stats.years, [(2010, 1), (20100, 1)]);
+        let stats = LibraryStats::new(&refs, &Filter::years(None, Some(2020)), None);
+        assert_eq!((stats.total, stats.years.clone()), (1, vec![(2010, 1)]));
+    }
+
+    #[test]
+    fn test_export() {
+        let stats = stats();
+        let csv = stats.to_csv();
+        assert!(csv.starts_with("category,name,count\ntotal,entries,3\n"));
+        assert!(csv.contains("year,2011,0\n"));
+        assert!(csv.contains("coauthors,Jane Doe & John Smith,2\n"));
+        assert!(csv.contains("publisher,\"Addison-Wesley, Reading\",1\n"));
+
+        let md = stats.to_markdown("Statistics", 1);
+        assert!(md.starts_with("# Statistics\n\n3 entries.\n"));
+        assert!(md.contains("| DOI | 1 | 33.3% |"));
+        assert!(md.contains("| Undated | 1 |"));
+        assert!(md.contains(
+            "## Top authors\n\n| Author | Entries |\n| --- | ---: |\n| Jane Doe | 2 |\n\n"
+        ));
+    }
+}
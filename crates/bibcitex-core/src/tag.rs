@@ -0,0 +1,238 @@
+//! Tags, stored in the `keywords` field of entries.
+//!
+//! Tags are split on configurable separators, see [`DEFAULT_TAG_SEPARATORS`]. Edits are written
+//! back to the source of the `.bib` file with [`crate::edit`], keeping the rest of the file as
+//! it is.
+use crate::{BibSource, Encoding, Result, bib::Reference, cjk, edit};
+use std::{collections::HashMap, path::Path};
+
+/// Separators of tags in the `keywords` field, unless configured otherwise.
+pub const DEFAULT_TAG_SEPARATORS: &str = ",;";
+
+/// Split the `keywords` field into tags on any of the `separators`.
+///
+/// Tags are trimmed, and empty or repeated ones, compared case-insensitively, are dropped.
+pub fn split_tags(text: &str, separators: &str) -> Vec<String> {
+    let separators = if separators.is_empty() {
+        DEFAULT_TAG_SEPARATORS
+    } else {
+        separators
+    };
+    let mut tags: Vec<String> = Vec::new();
+    for tag in text.split(|c| separators.contains(c)).map(str::trim) {
+        if !tag.is_empty() && !tags.iter().any(|t| same_tag(t, tag)) {
+            tags.push(tag.to_string());
+        }
+    }
+    tags
+}
+
+/// Join tags with the first of the `separators` followed by a space.
+pub fn join_tags(tags: &[String], separators: &str) -> String {
+    let separator = separators
+        .chars()
+        .next()
+        .or(DEFAULT_TAG_SEPARATORS.chars().next())
+        .unwrap_or(',');
+    tags.join(&format!("{separator} "))
+}
+
+/// Check whether two tags are the same, ignoring case and full-width forms.
+pub fn same_tag(lhs: &str, rhs: &str) -> bool {
+    cjk::normalize(lhs.trim()) == cjk::normalize(rhs.trim())
+}
+
+/// Tags of the references with the number of references having each, most used first.
+///
+/// Tags differing only in case are counted together under their first spelling.
+pub fn tag_counts(references: &[Reference]) -> Vec<(String, usize)> {
+    let mut counts: Vec<(String, usize)> = Vec::new();
+    let mut positions = HashMap::new();
+    for tag in references
+        .iter()
+        .filter_map(|r| r.keywords.as_ref())
+        .flatten()
+    {
+        let position = *positions
+            .entry(cjk::normalize(tag.trim()))
+            .or_insert_with(|| {
+                counts.push((tag.clone(), 0));
+                counts.len() - 1
+            });
+        counts[position].1 += 1;
+    }
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts
+}
+
+/// An edit of the tags of entries.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TagEdit {
+    /// Add a tag if missing
+    Add(String),
+    /// Remove a tag
+    Remove(String),
+    /// Rename a tag, merging it into `to` if both exist
+    Rename { from: String, to: String },
+}
+
+impl TagEdit {
+    /// Apply the edit to a list of tags, returning whether it changed.
+    pub fn apply(&self, tags: &mut Vec<String>) -> bool {
+        match self {
+            Self::Add(tag) => {
+                let tag = tag.trim();
+                if tag.is_empty() || tags.iter().any(|t| same_tag(t, tag)) {
+                    return false;
+                }
+                tags.push(tag.to_string());
+                true
+            }
+            Self::Remove(tag) => {
+                let len = tags.len();
+                tags.retain(|t| !same_tag(t, tag));
+                tags.len() != len
+            }
+            Self::Rename { from, to } => {
+                let to = to.trim();
+                let Some(index) = tags.iter().position(|t| same_tag(t, from)) else {
+                    return false;
+                };
+                if to.is_empty() || tags[index] == to {
+                    return false;
+                }
+                if tags.iter().any(|t| same_tag(t, to) && !same_tag(t, from)) {
+                    tags.remove(index);
+                } else {
+                    tags[index] = to.to_string();
+                }
+                true
+            }
+        }
+    }
+}
+
+/// Apply a tag edit to the entries with the given cite keys in the source of a `.bib` file.
+///
+/// Returns the edited source and the number of changed entries. The `keywords` field is removed
+/// once it has no tags left.
+pub fn edit_tags(source: &str, keys: &[&str], edit: &TagEdit, separators: &str) -> (String, usize) {
+    edit_list_field(source, keys, "keywords", edit, separators)
+}
+
+/// Apply a tag edit to a field holding a list, e.g., `keywords` or the JabRef `groups`.
+pub(crate) fn edit_list_field(
+    source: &str,
+    keys: &[&str],
+    field: &str,
+    edit: &TagEdit,
+    separators: &str,
+) -> (String, usize) {
+    let mut output = source.to_string();
+    let mut changed = 0;
+    for key in keys {
+        let Some(entry) = edit::find_entry(&output, key) else {
+            continue;
+        };
+        let mut tags = entry
+            .field(field)
+            .map(|field| split_tags(field.text(&output), separators))
+            .unwrap_or_default();
+        if !edit.apply(&mut tags) {
+            continue;
+        }
+        let value = (!tags.is_empty()).then(|| join_tags(&tags, separators));
+        if let Some(edited) = edit::set_field(&output, key, field, value.as_deref()) {
+            output = edited;
+            changed += 1;
+        }
+    }
+    (output, changed)
+}
+
+/// Apply a tag edit to the entries with the given cite keys in a `.bib` file.
+///
+/// The file is only written if an entry changed. Returns the number of changed entries.
+pub fn edit_tags_in_file(
+    path: impl AsRef<Path>,
+    encoding: Option<&'static Encoding>,
+    keys: &[&str],
+    edit: &TagEdit,
+    separators: &str,
+) -> Result<usize> {
+    let path = path.as_ref();
+    let source = BibSource::read(path, encoding)?;
+    let (output, changed) = edit_tags(&source.text, keys, edit, separators);
+    if changed > 0 {
+        source.write(path, &output)?;
+    }
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::read_bibliography;
+    use biblatex::Bibliography;
+
+    const SRC: &str = r#"@article{smith2010,
+  author = {John Smith},
+  keywords = {DG; finite elements},
+  year = {2010},
+}
+
+@book{knuth1984,
+  title = {The TeXbook},
+  keywords = {TeX, dg},
+}
+"#;
+
+    #[test]
+    fn test_split_tags() {
+        assert_eq!(split_tags("a, b;c ,, A", ""), ["a", "b", "c"]);
+        assert_eq!(split_tags("a, b; c", ";"), ["a, b", "c"]);
+        assert_eq!(join_tags(&["a".into(), "b".into()], ";"), "a; b");
+    }
+
+    #[test]
+    fn test_tag_counts() {
+        let refs = read_bibliography(Bibliography::parse(SRC).unwrap());
+        assert_eq!(
+            tag_counts(&refs),
+            [
+                ("DG".to_string(), 2),
+                ("TeX".to_string(), 1),
+                ("finite elements".to_string(), 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_edit_tags() {
+        let keys = ["smith2010", "knuth1984"];
+        let (added, n) = edit_tags(SRC, &keys, &TagEdit::Add("PDE".into()), ",;");
+        assert_eq!(n, 2);
+        assert!(added.contains("keywords = {DG, finite elements, PDE},"));
+        assert!(added.contains("keywords = {TeX, dg, PDE},"));
+        let (removed, n) = edit_tags(SRC, &keys, &TagEdit::Remove("dg".into()), ",;");
+        assert_eq!(n, 2);
+        assert!(removed.contains("keywords = {finite elements},"));
+        assert!(removed.contains("keywords = {TeX},"));
+        let rename = TagEdit::Rename {
+            from: "tex".into(),
+            to: "DG".into(),
+        };
+        let (renamed, n) = edit_tags(SRC, &keys, &rename, ",;");
+        assert_eq!(n, 1);
+        assert!(renamed.contains("keywords = {dg},"));
+        let (cleared, _) = edit_tags(
+            &renamed,
+            &["knuth1984"],
+            &TagEdit::Remove("DG".into()),
+            ",;",
+        );
+        assert!(cleared.ends_with("@book{knuth1984,\n  title = {The TeXbook},\n}\n"));
+        let (unchanged, n) = edit_tags(SRC, &["missing"], &TagEdit::Add("x".into()), ",;");
+        assert_eq!((unchanged.as_str(), n), (SRC, 0));
+    }
+}
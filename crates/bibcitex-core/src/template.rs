@@ -0,0 +1,134 @@
+use serde::{Deserialize, Serialize};
+
+fn default_separator() -> String {
+    ",".to_string()
+}
+
+/// A citation template used by the copy and paste actions.
+///
+/// The pattern supports the following placeholders:
+///
+/// - `%k`: the cite keys, joined by [`Self::separator`]
+/// - `%p`: the locator, e.g., a page number. A bracket pair around an empty locator, such as `[%p]`, is removed.
+/// - `%%`: a literal `%`
+///
+/// # Example
+///
+/// ```
+/// use bibcitex_core::CitationTemplate;
+///
+/// let template = CitationTemplate::new("Pandoc", "[@%k]", "; @");
+/// assert_eq!(template.render(&["a", "b"], None), "[@a; @b]");
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CitationTemplate {
+    /// Name of the template
+    pub name: String,
+    /// Pattern with placeholders
+    pub pattern: String,
+    /// Separator between multiple keys
+    #[serde(default = "default_separator")]
+    pub separator: String,
+}
+
+impl CitationTemplate {
+    pub fn new(name: &str, pattern: &str, separator: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            pattern: pattern.to_string(),
+            separator: separator.to_string(),
+        }
+    }
+
+    /// The template copying bare cite keys.
+    pub fn key() -> Self {
+        Self::new("Key", "%k", ",")
+    }
+
+    /// Templates available out of the box.
+    pub fn presets() -> Vec<Self> {
+        vec![
+            Self::key(),
+            Self::new("LaTeX", r"\cite{%k}", ","),
+            Self::new("natbib", r"\citep{%k}", ","),
+            Self::new("BibLaTeX", r"\autocite[%p]{%k}", ","),
+            Self::new("Pandoc", "[@%k]", "; @"),
+            Self::new("Typst", "@%k", " @"),
+        ]
+    }
+
+    /// Whether the pattern uses the locator placeholder `%p`.
+    pub fn has_locator(&self) -> bool {
+        self.pattern.contains("%p")
+    }
+
+    /// Render the template with the given keys and an optional locator.
+    pub fn render<S: AsRef<str>>(&self, keys: &[S], locator: Option<&str>) -> String {
+        let keys = keys
+            .iter()
+            .map(|key| key.as_ref())
+            .collect::<Vec<_>>()
+            .join(&self.separator);
+        let locator = locator.map(str::trim).unwrap_or_default();
+        let mut pattern = self.pattern.clone();
+        if locator.is_empty() {
+            for (open, close) in [('[', ']'), ('(', ')'), ('{', '}')] {
+                pattern = pattern.replace(&format!("{open}%p{close}"), "");
+            }
+        }
+        let mut out = String::with_capacity(pattern.len() + keys.len());
+        let mut chars = pattern.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                out.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('k') => out.push_str(&keys),
+                Some('p') => out.push_str(locator),
+                Some('%') => out.push('%'),
+                Some(other) => {
+                    out.push('%');
+                    out.push(other);
+                }
+                None => out.push('%'),
+            }
+        }
+        out
+    }
+}
+
+impl Default for CitationTemplate {
+    fn default() -> Self {
+        Self::key()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let presets = CitationTemplate::presets();
+        let render = |name: &str, keys: &[&str], locator: Option<&str>| {
+            presets
+                .iter()
+                .find(|t| t.name == name)
+                .unwrap()
+                .render(keys, locator)
+        };
+        assert_eq!(render("Key", &["a"], None), "a");
+        assert_eq!(render("LaTeX", &["a", "b", "c"], None), r"\cite{a,b,c}");
+        assert_eq!(render("BibLaTeX", &["a"], Some("12")), r"\autocite[12]{a}");
+        assert_eq!(render("BibLaTeX", &["a"], None), r"\autocite{a}");
+        assert_eq!(render("Pandoc", &["a", "b"], None), "[@a; @b]");
+        assert_eq!(render("Typst", &["a", "b"], None), "@a @b");
+    }
+
+    #[test]
+    fn test_escape() {
+        let template = CitationTemplate::new("Percent", "%%k %k %x", ",");
+        assert_eq!(template.render(&["a"], None), "%k a %x");
+    }
+}
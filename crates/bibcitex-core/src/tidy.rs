@@ -0,0 +1,521 @@
+//! Tidy the source of a `.bib` file, in the style of bibtex-tidy.
+//!
+//! [`tidy`] rewrites every entry with the layout of [`TidyOptions`]: field order, indentation and
+//! alignment, delimiters, lowercase names, `--` page ranges, empty fields and trailing commas.
+//! Text outside entries, e.g., comments, `@string` macros and `@preamble`s, is kept as it is.
+//! The [`TidyReport`] lists the changed entries, so a dry run can show a diff before
+//! [`tidy_file`] writes the file.
+use crate::{BibSource, Encoding, Result, edit};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt::Display, path::Path, str::FromStr, sync::LazyLock};
+
+static PAGE_RANGE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(\w)\s*(?:-+|–|—|‐)\s*(\w)").unwrap());
+
+/// Fields listed first, in this order, in entries of any type.
+pub const DEFAULT_FIELD_ORDER: [&str; 24] = [
+    "author",
+    "editor",
+    "title",
+    "booktitle",
+    "journal",
+    "fjournal",
+    "year",
+    "month",
+    "volume",
+    "number",
+    "pages",
+    "edition",
+    "series",
+    "chapter",
+    "publisher",
+    "address",
+    "school",
+    "institution",
+    "organization",
+    "note",
+    "doi",
+    "url",
+    "isbn",
+    "issn",
+];
+
+/// Delimiters of field values.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Delimiter {
+    /// `{...}`
+    #[default]
+    Braces,
+    /// `"..."`, falling back to braces for values with a `"`
+    Quotes,
+    /// Keep the delimiters as written
+    Keep,
+}
+
+/// Order of the entries of a tidied file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortBy {
+    /// By cite key, ignoring case
+    Key,
+    /// By year, newest first, then by cite key
+    Year,
+    /// By entry type, then by cite key
+    Type,
+}
+
+impl SortBy {
+    pub fn all() -> &'static [Self] {
+        &[Self::Key, Self::Year, Self::Type]
+    }
+}
+
+impl Display for SortBy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Key => write!(f, "key"),
+            Self::Year => write!(f, "year"),
+            Self::Type => write!(f, "type"),
+        }
+    }
+}
+
+impl FromStr for SortBy {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "key" => Ok(Self::Key),
+            "year" => Ok(Self::Year),
+            "type" => Ok(Self::Type),
+            _ => Err(format!("unknown sort order: {s}")),
+        }
+    }
+}
+
+/// Options of [`tidy`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TidyOptions {
+    /// Indentation of fields
+    pub indent: String,
+    /// Whether to align the `=` of the fields of an entry
+    pub align: bool,
+    pub delimiter: Delimiter,
+    /// Whether to lowercase entry types and field names
+    pub lowercase: bool,
+    /// Whether to write page ranges with `--`
+    pub page_ranges: bool,
+    /// Whether to remove fields with an empty value
+    pub remove_empty: bool,
+    /// Whether to end the last field with a comma
+    pub trailing_comma: bool,
+    /// Fields listed first in entries of any type, other fields following in source order
+    pub field_order: Vec<String>,
+    /// Field orders of entry types overriding [`Self::field_order`], by lowercase type
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub type_field_orders: BTreeMap<String, Vec<String>>,
+    /// Order of the entries, `None` to keep the source order
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort: Option<SortBy>,
+}
+
+impl Default for TidyOptions {
+    fn default() -> Self {
+        Self {
+            indent: "  ".to_string(),
+            align: true,
+            delimiter: Delimiter::Braces,
+            lowercase: true,
+            page_ranges: true,
+            remove_empty: true,
+            trailing_comma: true,
+            field_order: DEFAULT_FIELD_ORDER.map(str::to_string).to_vec(),
+            type_field_orders: BTreeMap::new(),
+            sort: None,
+        }
+    }
+}
+
+impl TidyOptions {
+    /// The field order of an entry type.
+    pub fn field_order(&self, entry_type: &str) -> &[String] {
+        self.type_field_orders
+            .get(&entry_type.to_lowercase())
+            .unwrap_or(&self.field_order)
+    }
+}
+
+/// An entry changed by [`tidy`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TidyChange {
+    pub key: String,
+    pub old: String,
+    pub new: String,
+}
+
+/// The result of [`tidy`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TidyReport {
+    /// The tidied source
+    pub output: String,
+    /// Changed entries in source order
+    pub changes: Vec<TidyChange>,
+    /// Whether the entries were sorted into another order
+    pub reordered: bool,
+}
+
+impl TidyReport {
+    /// Whether the file is left unchanged, blank lines aside.
+    pub fn is_unchanged(&self) -> bool {
+        self.changes.is_empty() && !self.reordered
+    }
+}
+
+/// A line of a diff.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffLine {
+    Same(String),
+    Removed(String),
+    Added(String),
+}
+
+/// Line diff of two texts, by longest common subsequence.
+pub fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let old = old.lines().collect::<Vec<_>>();
+    let new = new.lines().collect::<Vec<_>>();
+    // lengths[i][j]: longest common subsequence of old[i..] and new[j..]
+    let mut lengths = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i][j] = if old[i] == new[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    let mut diff = Vec::new();
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            diff.push(DiffLine::Same(old[i].to_string()));
+            i += 1;
+            j += 1;
+        } else if j < new.len() && (i == old.len() || lengths[i][j + 1] >= lengths[i + 1][j]) {
+            diff.push(DiffLine::Added(new[j].to_string()));
+            j += 1;
+        } else {
+            diff.push(DiffLine::Removed(old[i].to_string()));
+            i += 1;
+        }
+    }
+    diff
+}
+
+/// Tidy the source of a `.bib` file.
+///
+/// Blocks are separated by a blank line. When the entries are sorted, the text outside entries
+/// comes first, so that `@string` macros stay before the entries using them.
+pub fn tidy(source: &str, options: &TidyOptions) -> TidyReport {
+    let entries = edit::source_entries(source);
+    let mut others = Vec::new();
+    let mut blocks = Vec::new();
+    let mut changes = Vec::new();
+    let mut pos = 0;
+    for (index, entry) in entries.iter().enumerate() {
+        let gap = source[pos..entry.range.start].trim();
+        if !gap.is_empty() {
+            others.push(gap.to_string());
+            blocks.push((None, gap.to_string()));
+        }
+        let old = &source[entry.range.clone()];
+        let new = format_entry(source, entry, options);
+        if new != old {
+            changes.push(TidyChange {
+                key: entry.key.clone(),
+                old: old.to_string(),
+                new: new.clone(),
+            });
+        }
+        blocks.push((Some(index), new));
+        pos = entry.range.end;
+    }
+    let rest = source[pos..].trim();
+    if !rest.is_empty() {
+        others.push(rest.to_string());
+        blocks.push((None, rest.to_string()));
+    }
+
+    let mut reordered = false;
+    if let Some(sort) = options.sort {
+        let mut sorted = blocks
+            .into_iter()
+            .filter_map(|(index, text)| Some((index?, text)))
+            .collect::<Vec<_>>();
+        let year = |index: usize| {
+            entries[index]
+                .field("year")
+                .and_then(|field| {
+                    let digits = field
+                        .text(source)
+                        .chars()
+                        .filter(char::is_ascii_digit)
+                        .collect::<String>();
+                    digits.parse::<u32>().ok()
+                })
+                .unwrap_or_default()
+        };
+        let key = |index: usize| entries[index].key.to_lowercase();
+        let before = sorted.iter().map(|(index, _)| *index).collect::<Vec<_>>();
+        match sort {
+            SortBy::Key => sorted.sort_by_key(|(index, _)| key(*index)),
+            SortBy::Year => {
+                sorted.sort_by_key(|(index, _)| (std::cmp::Reverse(year(*index)), key(*index)))
+            }
+            SortBy::Type => sorted
+                .sort_by_key(|(index, _)| (entries[*index].entry_type.to_lowercase(), key(*index))),
+        }
+        reordered = sorted.iter().map(|(index, _)| *index).ne(before);
+        blocks = others
+            .into_iter()
+            .map(|text| (None, text))
+            .chain(sorted.into_iter().map(|(index, text)| (Some(index), text)))
+            .collect();
+    }
+
+    let mut output = blocks
+        .into_iter()
+        .map(|(_, text)| text)
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    if !output.is_empty() {
+        output.push('\n');
+    }
+    TidyReport {
+        output,
+        changes,
+        reordered,
+    }
+}
+
+/// Tidy a `.bib` file, writing it unless it is a dry run or nothing changed.
+pub fn tidy_file(
+    path: impl AsRef<Path>,
+    encoding: Option<&'static Encoding>,
+    options: &TidyOptions,
+    dry_run: bool,
+) -> Result<TidyReport> {
+    let path = path.as_ref();
+    let source = BibSource::read(path, encoding)?;
+    let report = tidy(&source.text, options);
+    if !dry_run && report.output != source.text {
+        source.write(path, &report.output)?;
+    }
+    Ok(report)
+}
+
+fn format_entry(source: &str, entry: &edit::SourceEntry, options: &TidyOptions) -> String {
+    let entry_type = if options.lowercase {
+        entry.entry_type.to_lowercase()
+    } else {
+        entry.entry_type.clone()
+    };
+    let mut fields = entry
+        .fields
+        .iter()
+        .filter_map(|field| {
+            let name = if options.lowercase {
+                field.name.to_lowercase()
+            } else {
+                field.name.clone()
+            };
+            let value = format_value(&name, &source[field.value.clone()], options)?;
+            Some((name, value))
+        })
+        .collect::<Vec<_>>();
+    let order = options.field_order(&entry.entry_type);
+    let rank = |name: &str| {
+        order
+            .iter()
+            .position(|field| field.eq_ignore_ascii_case(name))
+            .unwrap_or(order.len())
+    };
+    fields.sort_by_key(|(name, _)| rank(name));
+
+    let width = if options.align {
+        fields
+            .iter()
+            .map(|(name, _)| name.chars().count())
+            .max()
+            .unwrap_or_default()
+    } else {
+        0
+    };
+    let mut output = format!("@{entry_type}{{{}", entry.key);
+    for (i, (name, value)) in fields.iter().enumerate() {
+        let padding = " ".repeat(width.saturating_sub(name.chars().count()));
+        output.push_str(&format!(",\n{}{name}{padding} = {value}", options.indent));
+        if i + 1 == fields.len() && options.trailing_comma {
+            output.push(',');
+        }
+    }
+    output.push_str("\n}");
+    output
+}
+
+/// Format a field value as written, or `None` to remove it.
+fn format_value(name: &str, raw: &str, options: &TidyOptions) -> Option<String> {
+    let raw = raw.trim();
+    let (inner, quoted) = match delimited(raw) {
+        Some(delimited) => delimited,
+        // Numbers, macros and concatenations
+        None => return Some(raw.to_string()),
+    };
+    if options.remove_empty && inner.trim().is_empty() {
+        return None;
+    }
+    let inner = if options.page_ranges && name.eq_ignore_ascii_case("pages") {
+        PAGE_RANGE.replace_all(inner, "$1--$2").into_owned()
+    } else {
+        inner.to_string()
+    };
+    let quotes = match options.delimiter {
+        Delimiter::Braces => false,
+        Delimiter::Quotes => !has_top_level_quote(&inner),
+        Delimiter::Keep => quoted,
+    };
+    Some(if quotes {
+        format!("\"{inner}\"")
+    } else {
+        format!("{{{inner}}}")
+    })
+}
+
+/// The inside of a value delimited by a single pair of braces or quotes, and whether they are
+/// quotes.
+fn delimited(raw: &str) -> Option<(&str, bool)> {
+    if raw.len() < 2 {
+        return None;
+    }
+    let inner = &raw[1..raw.len() - 1];
+    let mut depth = 0usize;
+    match (raw.as_bytes()[0], raw.as_bytes()[raw.len() - 1]) {
+        (b'{', b'}') => {
+            for b in inner.bytes() {
+                match b {
+                    b'{' => depth += 1,
+                    b'}' if depth == 0 => return None,
+                    b'}' => depth -= 1,
+                    _ => {}
+                }
+            }
+            (depth == 0).then_some((inner, false))
+        }
+        (b'"', b'"') => (!has_top_level_quote(inner)).then_some((inner, true)),
+        _ => None,
+    }
+}
+
+fn has_top_level_quote(text: &str) -> bool {
+    let mut depth = 0usize;
+    text.bytes().any(|b| {
+        match b {
+            b'{' => depth += 1,
+            b'}' => depth = depth.saturating_sub(1),
+            b'"' => return depth == 0,
+            _ => {}
+        }
+        false
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SRC: &str = r#"% My references
+@string{siam = "SIAM"}
+
+@ARTICLE{smith2010,
+    Title = "A Study",
+  YEAR = 2010,
+  author = {John Smith},
+  pages = {12 - 34},
+  note = {},
+  journal = siam # " Review"
+}
+@book{Knuth1984,
+  author = {Donald E. Knuth},
+  title = {The {\TeX}book},
+  year = {1984},
+}
+"#;
+
+    #[test]
+    fn test_tidy() {
+        let report = tidy(SRC, &TidyOptions::default());
+        assert_eq!(
+            report.output,
+            r#"% My references
+@string{siam = "SIAM"}
+
+@article{smith2010,
+  author  = {John Smith},
+  title   = {A Study},
+  journal = siam # " Review",
+  year    = 2010,
+  pages   = {12--34},
+}
+
+@book{Knuth1984,
+  author = {Donald E. Knuth},
+  title  = {The {\TeX}book},
+  year   = {1984},
+}
+"#
+        );
+        assert_eq!(report.changes.len(), 2);
+        assert!(!report.reordered);
+        assert!(biblatex::Bibliography::parse(&report.output).is_ok());
+        // Tidying is idempotent
+        assert!(tidy(&report.output, &TidyOptions::default()).is_unchanged());
+    }
+
+    #[test]
+    fn test_options() {
+        let options = TidyOptions {
+            indent: "\t".to_string(),
+            align: false,
+            delimiter: Delimiter::Quotes,
+            remove_empty: false,
+            trailing_comma: false,
+            sort: Some(SortBy::Key),
+            ..Default::default()
+        };
+        let report = tidy(SRC, &options);
+        assert!(report.reordered);
+        let output = &report.output;
+        assert!(
+            output.starts_with("% My references\n@string{siam = \"SIAM\"}\n\n@book{Knuth1984,")
+        );
+        assert!(output.contains("\ttitle = \"The {\\TeX}book\",\n\tyear = \"1984\"\n}"));
+        assert!(output.contains("\tnote = \"\"\n}"));
+    }
+
+    #[test]
+    fn test_diff_lines() {
+        let diff = diff_lines("a\nb\nc", "a\nc\nd");
+        assert_eq!(
+            diff,
+            [
+                DiffLine::Same("a".to_string()),
+                DiffLine::Removed("b".to_string()),
+                DiffLine::Same("c".to_string()),
+                DiffLine::Added("d".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,501 @@
+//! Title case conversion and brace protection of titles.
+//!
+//! Titles are converted on their BibTeX source, see [`TitleEdit`]: braced groups, math and
+//! commands are kept as they are, and so are acronyms like `DNA` or `iPhone` unless the whole
+//! title is in capitals. Proper nouns from a dictionary, e.g., [`DEFAULT_PROTECTED_WORDS`], get
+//! their casing and can be protected with braces, so that styles lowercasing titles keep them.
+//! Edits are previewed with [`preview_titles`] before they are written back with
+//! [`apply_titles`].
+use crate::{BibSource, Encoding, Result, edit};
+use std::{collections::HashMap, fmt::Display, path::Path, str::FromStr};
+
+/// Proper nouns protected by default.
+pub const DEFAULT_PROTECTED_WORDS: [&str; 32] = [
+    "Banach",
+    "Bayes",
+    "Bayesian",
+    "Chebyshev",
+    "Dirichlet",
+    "Euler",
+    "Fourier",
+    "Galerkin",
+    "Gauss",
+    "Hamilton",
+    "Hermite",
+    "Hilbert",
+    "Jacobi",
+    "Krylov",
+    "Kutta",
+    "Lagrange",
+    "Laplace",
+    "Legendre",
+    "Lipschitz",
+    "Markov",
+    "Maxwell",
+    "Monte Carlo",
+    "Navier–Stokes",
+    "Neumann",
+    "Newton",
+    "Poisson",
+    "Riemann",
+    "Runge",
+    "Schrödinger",
+    "Sobolev",
+    "Taylor",
+    "Wasserstein",
+];
+
+/// Words kept lowercase inside a title in title case.
+const SMALL_WORDS: [&str; 21] = [
+    "a", "an", "and", "as", "at", "but", "by", "for", "from", "in", "into", "nor", "of", "on",
+    "or", "per", "the", "to", "via", "vs", "with",
+];
+
+/// Characters joining the parts of a compound word.
+const DASHES: [char; 4] = ['-', '–', '—', '/'];
+
+/// The case a title is converted to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TitleCase {
+    /// Every word capitalized except small words, e.g., `A Study of the Finite Element Method`
+    Title,
+    /// Only the first word capitalized, e.g., `A study of the finite element method`
+    Sentence,
+}
+
+impl TitleCase {
+    pub fn all() -> &'static [Self] {
+        &[Self::Title, Self::Sentence]
+    }
+}
+
+impl Display for TitleCase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Title => write!(f, "title"),
+            Self::Sentence => write!(f, "sentence"),
+        }
+    }
+}
+
+impl FromStr for TitleCase {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "title" => Ok(Self::Title),
+            "sentence" => Ok(Self::Sentence),
+            _ => Err(format!("unknown title case: {s}")),
+        }
+    }
+}
+
+/// An edit of titles: a case conversion, brace protection, or both.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TitleEdit {
+    /// Case to convert to, `None` to keep the case
+    pub case: Option<TitleCase>,
+    /// Whether to protect acronyms and proper nouns with braces
+    pub protect: bool,
+    /// Proper nouns written with their casing, e.g., `Fourier` or `Navier–Stokes`
+    pub dictionary: Vec<String>,
+}
+
+/// A part of a title.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token<'a> {
+    Space(&'a str),
+    Word(&'a str),
+    /// A braced group, math or a command, kept as it is
+    Verbatim(&'a str),
+}
+
+impl TitleEdit {
+    /// Apply the edit to the BibTeX source of a title.
+    pub fn apply(&self, title: &str) -> String {
+        let dictionary = self
+            .dictionary
+            .iter()
+            .flat_map(|entry| entry.split(|c: char| c.is_whitespace() || DASHES.contains(&c)))
+            .filter(|word| !word.is_empty())
+            .map(|word| (word.to_lowercase(), word))
+            .collect::<HashMap<_, _>>();
+        let tokens = tokenize(title);
+        let all_caps = is_all_caps(&tokens);
+        let last_word = tokens.iter().rposition(|t| !matches!(t, Token::Space(_)));
+        let mut output = String::new();
+        let mut first = true;
+        for (i, token) in tokens.iter().enumerate() {
+            match *token {
+                Token::Space(text) | Token::Verbatim(text) => output.push_str(text),
+                Token::Word(word) if word.contains(['\\', '{']) => output.push_str(word),
+                Token::Word(word) => {
+                    let mut parts = Vec::new();
+                    let split = split_parts(word);
+                    let count = split.len();
+                    for (j, part) in split.into_iter().enumerate() {
+                        let mut chars = part.chars();
+                        if chars.next().is_some_and(|c| DASHES.contains(&c))
+                            && chars.next().is_none()
+                        {
+                            parts.push(part.to_string());
+                            continue;
+                        }
+                        let core = core(part);
+                        let lower = core.to_lowercase();
+                        let known = dictionary.get(&lower).copied();
+                        let kept = known.is_some() || (!all_caps && is_acronym(core));
+                        let mut text = match (self.case, known) {
+                            (_, Some(known)) => known.to_string(),
+                            (Some(_), None) if !kept => {
+                                let capitalize = match self.case {
+                                    Some(TitleCase::Sentence) => first && j == 0,
+                                    _ => {
+                                        (first && j == 0)
+                                            || (Some(i) == last_word && j + 1 == count)
+                                            || !SMALL_WORDS.contains(&lower.as_str())
+                                    }
+                                };
+                                let base = if all_caps || self.case == Some(TitleCase::Sentence) {
+                                    lower.clone()
+                                } else {
+                                    core.to_string()
+                                };
+                                if capitalize {
+                                    capitalize_first(&base)
+                                } else if self.case == Some(TitleCase::Title) {
+                                    lower.clone()
+                                } else {
+                                    base
+                                }
+                            }
+                            _ => core.to_string(),
+                        };
+                        if self.protect && kept && !text.is_empty() {
+                            text = format!("{{{text}}}");
+                        }
+                        parts.push(part.replacen(core, &text, 1));
+                    }
+                    output.push_str(&parts.concat());
+                    first = word.ends_with([':', '?', '!']);
+                    continue;
+                }
+            }
+            if !matches!(token, Token::Space(_)) {
+                first = false;
+            }
+        }
+        output
+    }
+}
+
+/// Split a title into spaces, words and verbatim parts.
+fn tokenize(title: &str) -> Vec<Token<'_>> {
+    let bytes = title.as_bytes();
+    let mut tokens = Vec::new();
+    let mut word_start = None;
+    let mut i = 0;
+    while i < bytes.len() {
+        let end = match bytes[i] {
+            b if b.is_ascii_whitespace() => {
+                let end = (i..bytes.len())
+                    .find(|&j| !bytes[j].is_ascii_whitespace())
+                    .unwrap_or(bytes.len());
+                flush_word(title, &mut tokens, &mut word_start, i);
+                tokens.push(Token::Space(&title[i..end]));
+                i = end;
+                continue;
+            }
+            b'{' => group_end(bytes, i),
+            b'$' => {
+                let double = bytes.get(i + 1) == Some(&b'$');
+                let delimiter = if double { "$$" } else { "$" };
+                let start = i + delimiter.len();
+                title[start..]
+                    .find(delimiter)
+                    .map_or(bytes.len(), |offset| start + offset + delimiter.len())
+            }
+            b'\\' => {
+                // A command with its braced argument, e.g., `\emph{x}` or `\"{o}`
+                let mut end = i + 1;
+                while end < bytes.len() && bytes[end].is_ascii_alphabetic() {
+                    end += 1;
+                }
+                if end == i + 1 && end < bytes.len() {
+                    end += title[end..].chars().next().map_or(1, char::len_utf8);
+                }
+                if bytes.get(end) == Some(&b'{') {
+                    end = group_end(bytes, end);
+                }
+                if word_start.is_none() {
+                    word_start = Some(i);
+                }
+                i = end;
+                continue;
+            }
+            _ => {
+                if word_start.is_none() {
+                    word_start = Some(i);
+                }
+                i += title[i..].chars().next().map_or(1, char::len_utf8);
+                continue;
+            }
+        };
+        // A verbatim part, which may be inside a word, e.g., `{DNA}-binding`
+        flush_word(title, &mut tokens, &mut word_start, i);
+        tokens.push(Token::Verbatim(&title[i..end]));
+        i = end;
+    }
+    flush_word(title, &mut tokens, &mut word_start, bytes.len());
+    tokens
+}
+
+fn flush_word<'a>(
+    title: &'a str,
+    tokens: &mut Vec<Token<'a>>,
+    word_start: &mut Option<usize>,
+    end: usize,
+) {
+    if let Some(start) = word_start.take() {
+        tokens.push(Token::Word(&title[start..end]));
+    }
+}
+
+/// The end of the braced group starting at `start`, or of the text if unbalanced.
+fn group_end(bytes: &[u8], start: usize) -> usize {
+    let mut depth = 0usize;
+    for (i, &b) in bytes.iter().enumerate().skip(start) {
+        match b {
+            b'{' => depth += 1,
+            b'}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return i + 1;
+                }
+            }
+            _ => {}
+        }
+    }
+    bytes.len()
+}
+
+/// Split a compound word into parts and the dashes between them, e.g., `Navier`, `–Stokes`.
+fn split_parts(word: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    for (i, c) in word.char_indices() {
+        if i > 0 && DASHES.contains(&c) {
+            parts.push(&word[start..i]);
+            parts.push(&word[i..i + c.len_utf8()]);
+            start = i + c.len_utf8();
+        }
+    }
+    parts.push(&word[start..]);
+    parts.retain(|part| !part.is_empty());
+    parts
+}
+
+/// A word without surrounding punctuation.
+fn core(word: &str) -> &str {
+    word.trim_matches(|c: char| !c.is_alphanumeric())
+}
+
+/// Whether a word is written with capitals inside, e.g., `DNA`, `GPUs`, `iPhone` or `3D`.
+fn is_acronym(word: &str) -> bool {
+    let letters = word
+        .chars()
+        .filter(|c| c.is_alphabetic())
+        .collect::<Vec<_>>();
+    let has_upper = letters.iter().any(|c| c.is_uppercase());
+    letters.iter().skip(1).any(|c| c.is_uppercase())
+        || (has_upper && word.chars().any(|c| c.is_ascii_digit()))
+}
+
+/// Whether all letters of the words of a title are capitals.
+fn is_all_caps(tokens: &[Token]) -> bool {
+    let mut letters = tokens
+        .iter()
+        .filter_map(|token| match token {
+            Token::Word(word) => Some(word.chars()),
+            _ => None,
+        })
+        .flatten()
+        .filter(|c| c.is_alphabetic())
+        .peekable();
+    letters.peek().is_some() && letters.all(|c| !c.is_lowercase())
+}
+
+fn capitalize_first(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// A change of the title of an entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TitleChange {
+    pub key: String,
+    pub old: String,
+    pub new: String,
+}
+
+/// Preview the changes of the titles of the entries with the given cite keys in the source of a
+/// `.bib` file. Unchanged titles are skipped.
+pub fn preview_titles(source: &str, keys: &[&str], title_edit: &TitleEdit) -> Vec<TitleChange> {
+    edit::source_entries(source)
+        .into_iter()
+        .filter(|entry| keys.contains(&entry.key.as_str()))
+        .filter_map(|entry| {
+            let old = entry.field("title")?.text(source);
+            let new = title_edit.apply(old);
+            (new != old).then(|| TitleChange {
+                key: entry.key.clone(),
+                old: old.to_string(),
+                new,
+            })
+        })
+        .collect()
+}
+
+/// Preview the changes of the titles of the entries with the given cite keys in a `.bib` file.
+pub fn preview_titles_in_file(
+    path: impl AsRef<Path>,
+    encoding: Option<&'static Encoding>,
+    keys: &[&str],
+    title_edit: &TitleEdit,
+) -> Result<Vec<TitleChange>> {
+    let source = BibSource::read(path, encoding)?;
+    Ok(preview_titles(&source.text, keys, title_edit))
+}
+
+/// Write previewed title changes to the source of a `.bib` file.
+///
+/// A change is skipped if the title of its entry is no longer [`TitleChange::old`], e.g., as it
+/// was edited since the preview. Returns the edited source, the number of changed entries and
+/// the cite keys of the skipped changes.
+pub fn apply_titles(source: &str, changes: &[TitleChange]) -> (String, usize, Vec<String>) {
+    let mut entries = edit::source_entries(source);
+    let mut replacements = Vec::new();
+    let mut skipped = Vec::new();
+    for change in changes {
+        // Each entry is changed once, even if cite keys are duplicated.
+        let position = entries.iter().position(|entry| {
+            entry.key == change.key
+                && entry
+                    .field("title")
+                    .is_some_and(|title| title.text(source) == change.old)
+        });
+        let replacement = position.and_then(|position| {
+            let entry = entries.swap_remove(position);
+            edit::field_replacement(source, &entry, "title", Some(&change.new))
+        });
+        match replacement {
+            Some(replacement) => replacements.push(replacement),
+            None => skipped.push(change.key.clone()),
+        }
+    }
+    let changed = replacements.len();
+    (
+        edit::apply_replacements(source, replacements),
+        changed,
+        skipped,
+    )
+}
+
+/// Write previewed title changes to a `.bib` file, see [`apply_titles`].
+///
+/// The file is only written if an entry changed. Returns the number of changed entries and the
+/// cite keys of the skipped changes.
+pub fn apply_titles_in_file(
+    path: impl AsRef<Path>,
+    encoding: Option<&'static Encoding>,
+    changes: &[TitleChange],
+) -> Result<(usize, Vec<String>)> {
+    let path = path.as_ref();
+    let source = BibSource::read(path, encoding)?;
+    let (output, changed, skipped) = apply_titles(&source.text, changes);
+    if changed > 0 {
+        source.write(path, &output)?;
+    }
+    Ok((changed, skipped))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edit(case: Option<TitleCase>, protect: bool) -> TitleEdit {
+        TitleEdit {
+            case,
+            protect,
+            dictionary: DEFAULT_PROTECTED_WORDS.map(str::to_string).to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_title_case() {
+        let title = edit(Some(TitleCase::Title), false);
+        assert_eq!(
+            title.apply("a study of the navier–stokes equations: an overview of DNA"),
+            "A Study of the Navier–Stokes Equations: An Overview of DNA"
+        );
+        assert_eq!(
+            title.apply("A FAST SOLVER FOR THE FOURIER TRANSFORM"),
+            "A Fast Solver for the Fourier Transform"
+        );
+        assert_eq!(
+            title.apply("state-of-the-art solvers on $L^2$ and {iPhone} in {\\em x}"),
+            "State-of-the-Art Solvers on $L^2$ and {iPhone} in {\\em x}"
+        );
+    }
+
+    #[test]
+    fn test_sentence_case() {
+        let sentence = edit(Some(TitleCase::Sentence), false);
+        assert_eq!(
+            sentence.apply("The Finite Element Method for 3D Fourier-Galerkin Schemes on GPUs"),
+            "The finite element method for 3D Fourier-Galerkin schemes on GPUs"
+        );
+        assert_eq!(
+            sentence.apply("Monte Carlo Methods: A Review of Schr\\\"{o}dinger Problems"),
+            "Monte Carlo methods: A review of Schr\\\"{o}dinger problems"
+        );
+    }
+
+    #[test]
+    fn test_protect() {
+        let protect = edit(None, true);
+        assert_eq!(
+            protect.apply("Fourier analysis of navier–stokes flows with {DNA} and RNA."),
+            "{Fourier} analysis of {Navier}–{Stokes} flows with {DNA} and {RNA}."
+        );
+        let both = edit(Some(TitleCase::Sentence), true);
+        assert_eq!(
+            both.apply("On The Galerkin Method"),
+            "On the {Galerkin} method"
+        );
+    }
+
+    #[test]
+    fn test_preview_and_apply() {
+        let source = "@article{a,\n  title = {ON THE GALERKIN METHOD},\n}\n\n@article{b,\n  title = {Already fine},\n}\n";
+        let changes = preview_titles(source, &["a", "b"], &edit(Some(TitleCase::Sentence), true));
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].new, "On the {Galerkin} method");
+        let (output, changed, skipped) = apply_titles(source, &changes);
+        assert_eq!(changed, 1);
+        assert!(skipped.is_empty());
+        assert!(output.contains("title = {On the {Galerkin} method}"));
+        assert!(output.contains("title = {Already fine}"));
+
+        // Titles edited since the preview are kept
+        let edited = source.replace("ON THE GALERKIN METHOD", "On Galerkin");
+        let (output, changed, skipped) = apply_titles(&edited, &changes);
+        assert_eq!(
+            (output, changed, skipped),
+            (edited, 0, vec!["a".to_string()])
+        );
+    }
+}
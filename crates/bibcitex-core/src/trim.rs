@@ -0,0 +1,420 @@
+//! Export a trimmed `.bib` file with only the cited entries.
+//!
+//! Cited keys are read from a BibTeX `.aux` file, a biber `.bcf` file or a scanned LaTeX project,
+//! see [`CitedKeys`]. [`trim_bibliography`] copies the cited entries from the source of the
+//! bibliographies as they are written, adding their `crossref` and `xdata` parents, the
+//! `@string` macros they use and all `@preamble`s.
+use crate::{BibSource, Encoding, Result, edit, project::ProjectScan};
+use fs_err as fs;
+use regex::Regex;
+use std::{
+    collections::{HashSet, VecDeque},
+    path::{Path, PathBuf},
+    sync::LazyLock,
+};
+
+/// The key of `\nocite{*}`, citing every entry.
+pub const ALL_KEYS: &str = "*";
+
+static AUX_CITATION: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\\(?:citation|abx@aux@cite\{[^}]*\})\{([^}]*)\}").unwrap());
+static AUX_BIBDATA: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\\bibdata\{([^}]*)\}").unwrap());
+static AUX_INPUT: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\\@input\{([^}]*)\}").unwrap());
+static BCF_CITEKEY: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"<bcf:citekey[^>]*>([^<]*)</bcf:citekey>").unwrap());
+static BCF_DATASOURCE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"<bcf:datasource[^>]*>([^<]*)</bcf:datasource>").unwrap());
+
+/// Order of the entries in a trimmed `.bib` file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum KeyOrder {
+    /// By first citation
+    #[default]
+    Citation,
+    /// By cite key, ignoring case
+    Alphabetical,
+}
+
+/// Cited keys in order of first citation, with the bibliographies they are looked up in.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CitedKeys {
+    /// Cite keys, [`ALL_KEYS`] for `\nocite{*}`
+    pub keys: Vec<String>,
+    /// Bibliography files named in the `.aux`, `.bcf` or `.tex` files
+    pub bibliographies: Vec<PathBuf>,
+}
+
+impl CitedKeys {
+    /// Read the cited keys of a `.aux` or `.bcf` file, or of a LaTeX project otherwise.
+    pub fn read(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("aux") => Self::from_aux(path),
+            Some("bcf") => Self::from_bcf(path),
+            _ => Ok(Self::from_project(&crate::scan_project(path)?)),
+        }
+    }
+
+    /// Read `\citation` and `\bibdata` lines of a `.aux` file, following `\@input` of the
+    /// `.aux` files of included chapters.
+    pub fn from_aux(path: impl AsRef<Path>) -> Result<Self> {
+        let mut cited = Self::default();
+        let mut visited = HashSet::new();
+        cited.read_aux(path.as_ref(), &mut visited)?;
+        Ok(cited)
+    }
+
+    fn read_aux(&mut self, path: &Path, visited: &mut HashSet<PathBuf>) -> Result<()> {
+        if !visited.insert(path.to_path_buf()) {
+            return Ok(());
+        }
+        let dir = path.parent().unwrap_or(Path::new("")).to_path_buf();
+        for line in fs::read_to_string(path)?.lines() {
+            for captures in AUX_CITATION.captures_iter(line) {
+                captures[1].split(',').for_each(|key| self.push(key));
+            }
+            for captures in AUX_BIBDATA.captures_iter(line) {
+                for name in captures[1].split(',').map(str::trim) {
+                    let mut path = dir.join(name);
+                    if path.extension().is_none() {
+                        path.set_extension("bib");
+                    }
+                    self.push_bibliography(path);
+                }
+            }
+            for captures in AUX_INPUT.captures_iter(line) {
+                let input = dir.join(captures[1].trim());
+                if input.is_file() {
+                    self.read_aux(&input, visited)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Read the cite keys and data sources of a biber `.bcf` file.
+    pub fn from_bcf(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let dir = path.parent().unwrap_or(Path::new(""));
+        let source = fs::read_to_string(path)?;
+        let mut cited = Self::default();
+        for captures in BCF_CITEKEY.captures_iter(&source) {
+            cited.push(&captures[1]);
+        }
+        for captures in BCF_DATASOURCE.captures_iter(&source) {
+            cited.push_bibliography(dir.join(captures[1].trim()));
+        }
+        Ok(cited)
+    }
+
+    /// The cited keys of a scanned LaTeX project.
+    pub fn from_project(scan: &ProjectScan) -> Self {
+        let mut cited = Self::default();
+        for citation in &scan.citations {
+            cited.push(&citation.key);
+        }
+        if scan.nocite_all {
+            cited.push(ALL_KEYS);
+        }
+        cited.bibliographies = scan.bibliographies.clone();
+        cited
+    }
+
+    fn push(&mut self, key: &str) {
+        let key = key.trim();
+        if !key.is_empty() && !self.keys.iter().any(|k| k == key) {
+            self.keys.push(key.to_string());
+        }
+    }
+
+    fn push_bibliography(&mut self, path: PathBuf) {
+        if !self.bibliographies.contains(&path) {
+            self.bibliographies.push(path);
+        }
+    }
+}
+
+/// A trimmed `.bib` file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TrimmedBibliography {
+    /// Source of the trimmed file
+    pub source: String,
+    /// Number of entries, including `crossref` and `xdata` parents
+    pub entries: usize,
+    /// Cited keys without an entry
+    pub missing: Vec<String>,
+}
+
+/// Copy the entries with the given keys from the source of a `.bib` file.
+///
+/// Keys are matched exactly, or ignoring case as BibTeX does. `crossref` and `xdata` parents
+/// follow the cited entries, as BibTeX requires. `@preamble`s and the `@string` macros used by
+/// the entries come first, in source order.
+pub fn trim_bibliography(source: &str, keys: &[String], order: KeyOrder) -> TrimmedBibliography {
+    let entries = edit::source_entries(source);
+    let find = |key: &str| {
+        entries
+            .iter()
+            .position(|entry| entry.key == key)
+            .or_else(|| {
+                entries
+                    .iter()
+                    .position(|entry| entry.key.eq_ignore_ascii_case(key))
+            })
+    };
+    let mut selected = Vec::new();
+    let mut missing = Vec::new();
+    if keys.iter().any(|key| key == ALL_KEYS) {
+        selected.extend(0..entries.len());
+    }
+    for key in keys.iter().filter(|key| *key != ALL_KEYS) {
+        match find(key) {
+            Some(index) if !selected.contains(&index) => selected.push(index),
+            Some(_) => {}
+            None => missing.push(key.clone()),
+        }
+    }
+    if order == KeyOrder::Alphabetical {
+        selected.sort_by_key(|&index| entries[index].key.to_lowercase());
+    }
+    // Parents not cited themselves, in the order they are first referenced
+    let mut parents = Vec::new();
+    let mut pending = selected.iter().copied().collect::<VecDeque<_>>();
+    while let Some(index) = pending.pop_front() {
+        let entry = &entries[index];
+        for name in ["crossref", "xdata"] {
+            let Some(field) = entry.field(name) else {
+                continue;
+            };
+            for key in field.text(source).split(',') {
+                if let Some(parent) = find(key.trim())
+                    && !selected.contains(&parent)
+                    && !parents.contains(&parent)
+                {
+                    parents.push(parent);
+                    pending.push_back(parent);
+                }
+            }
+        }
+    }
+    if order == KeyOrder::Alphabetical {
+        parents.sort_by_key(|&index| entries[index].key.to_lowercase());
+    }
+    selected.extend(parents);
+
+    let macros = edit::source_macros(source);
+    let mut used = HashSet::new();
+    let mut values = selected
+        .iter()
+        .flat_map(|&index| entries[index].fields.iter())
+        .map(|field| &source[field.value.clone()])
+        .collect::<Vec<_>>();
+    // Macros may be defined with other macros
+    while let Some(value) = values.pop() {
+        for name in macro_names(value) {
+            if used.insert(name.to_lowercase())
+                && let Some(field) = macros
+                    .iter()
+                    .filter_map(|m| m.fields.first())
+                    .find(|field| field.name.eq_ignore_ascii_case(name))
+            {
+                values.push(&source[field.value.clone()]);
+            }
+        }
+    }
+    let mut blocks = macros
+        .iter()
+        .filter(|m| {
+            m.entry_type == "preamble"
+                || m.fields
+                    .first()
+                    .is_some_and(|field| used.contains(&field.name.to_lowercase()))
+        })
+        .map(|m| &source[m.range.clone()])
+        .collect::<Vec<_>>();
+    let header = blocks.len();
+    blocks.extend(
+        selected
+            .iter()
+            .map(|&index| &source[entries[index].range.clone()]),
+    );
+    let mut output = blocks[..header].join("\n");
+    if header > 0 {
+        output.push_str("\n\n");
+    }
+    output.push_str(&blocks[header..].join("\n\n"));
+    if !output.is_empty() {
+        output.push('\n');
+    }
+    TrimmedBibliography {
+        source: output,
+        entries: selected.len(),
+        missing,
+    }
+}
+
+/// Trim the bibliographies to the cited keys and write the result to `output`.
+///
+/// The bibliographies are read in the given encoding, or in their detected encodings if `None`.
+pub fn export_trimmed(
+    bibliographies: &[PathBuf],
+    encoding: Option<&'static Encoding>,
+    keys: &[String],
+    order: KeyOrder,
+    output: impl AsRef<Path>,
+) -> Result<TrimmedBibliography> {
+    let mut source = String::new();
+    for path in bibliographies {
+        source.push_str(&BibSource::read(path, encoding)?.text);
+        source.push('\n');
+    }
+    let trimmed = trim_bibliography(&source, keys, order);
+    fs::write(output, &trimmed.source)?;
+    Ok(trimmed)
+}
+
+/// Names of macros in a field value, i.e., the bare words of a `#` concatenation.
+fn macro_names(value: &str) -> Vec<&str> {
+    let mut names = Vec::new();
+    let mut depth = 0usize;
+    let mut quoted = false;
+    let mut start = 0;
+    for (i, c) in value.char_indices().chain([(value.len(), '#')]) {
+        match c {
+            '{' => depth += 1,
+            '}' => depth = depth.saturating_sub(1),
+            '"' if depth == 0 => quoted = !quoted,
+            '#' if depth == 0 && !quoted => {
+                let part = value[start..i].trim();
+                if part
+                    .chars()
+                    .next()
+                    .is_some_and(|c| c.is_alphabetic() || c == '_')
+                {
+                    names.push(part);
+                }
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SRC: &str = r#"@preamble{"\newcommand{\noop}[1]{}"}
+@string{siam = "SIAM"}
+@string{sinum = siam # " J. Numer. Anal."}
+@string{unused = "Unused"}
+
+@article{smith2010,
+  author = {John Smith},
+  journal = sinum,
+  month = jan,
+}
+
+@inproceedings{lee2015,
+  author = {Ann Lee},
+  crossref = {proc2015},
+}
+
+@proceedings{proc2015,
+  title = {Proceedings},
+}
+
+@book{Knuth1984,
+  title = {The TeXbook},
+}
+"#;
+
+    fn keys(keys: &[&str]) -> Vec<String> {
+        keys.iter().map(|key| key.to_string()).collect()
+    }
+
+    #[test]
+    fn test_trim() {
+        let trimmed = trim_bibliography(
+            SRC,
+            &keys(&["lee2015", "smith2010", "ghost"]),
+            KeyOrder::Citation,
+        );
+        assert_eq!(trimmed.entries, 3);
+        assert_eq!(trimmed.missing, ["ghost"]);
+        let source = &trimmed.source;
+        assert!(source.starts_with("@preamble{"));
+        assert!(source.contains("@string{siam = \"SIAM\"}\n@string{sinum"));
+        assert!(!source.contains("Unused") && !source.contains("Knuth"));
+        let position = |key: &str| source.find(key).unwrap();
+        assert!(position("@inproceedings{lee2015") < position("@article{smith2010"));
+        assert!(position("@article{smith2010") < position("@proceedings{proc2015"));
+        let parsed = biblatex::Bibliography::parse(source).unwrap();
+        assert_eq!(parsed.len(), 3);
+    }
+
+    #[test]
+    fn test_parent_order() {
+        let source = SRC.to_string()
+            + r#"
+@inproceedings{park2016,
+  author = {Min Park},
+  crossref = {proc2016},
+}
+
+@proceedings{proc2016,
+  title = {Proceedings},
+}
+"#;
+        let trimmed =
+            trim_bibliography(&source, &keys(&["lee2015", "park2016"]), KeyOrder::Citation);
+        assert_eq!(trimmed.entries, 4);
+        let position = |key: &str| trimmed.source.find(key).unwrap();
+        assert!(position("@inproceedings{park2016") < position("@proceedings{proc2015"));
+        assert!(position("@proceedings{proc2015") < position("@proceedings{proc2016"));
+    }
+
+    #[test]
+    fn test_order_and_all() {
+        let trimmed = trim_bibliography(
+            SRC,
+            &keys(&["smith2010", "knuth1984"]),
+            KeyOrder::Alphabetical,
+        );
+        assert!(trimmed.missing.is_empty());
+        assert!(trimmed.source.find("Knuth1984") < trimmed.source.find("smith2010"));
+        let all = trim_bibliography(SRC, &keys(&[ALL_KEYS]), KeyOrder::Citation);
+        assert_eq!(all.entries, 4);
+    }
+
+    #[test]
+    fn test_cited_keys() {
+        let dir = std::env::temp_dir().join(format!("bibcitex-trim-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("main.aux"),
+            "\\relax\n\\citation{b,a}\n\\@input{chapter.aux}\n\\bibdata{refs,more}\n",
+        )
+        .unwrap();
+        fs::write(dir.join("chapter.aux"), "\\citation{a}\n\\citation{c}\n").unwrap();
+        let cited = CitedKeys::read(dir.join("main.aux")).unwrap();
+        assert_eq!(cited.keys, ["b", "a", "c"]);
+        assert_eq!(
+            cited.bibliographies,
+            [dir.join("refs.bib"), dir.join("more.bib")]
+        );
+        fs::write(
+            dir.join("main.bcf"),
+            r#"<bcf:datasource type="file" datatype="bibtex">refs.bib</bcf:datasource>
+<bcf:citekey order="1" intorder="1">knuth</bcf:citekey>
+<bcf:citekey order="2" intorder="1" nocite="1">*</bcf:citekey>"#,
+        )
+        .unwrap();
+        let cited = CitedKeys::read(dir.join("main.bcf")).unwrap();
+        assert_eq!(cited.keys, ["knuth", ALL_KEYS]);
+        assert_eq!(cited.bibliographies, [dir.join("refs.bib")]);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,381 @@
+//! A local log of cited references.
+//!
+//! Every copy or paste of a cite key is appended to a JSON Lines file in the config directory,
+//! see [`UsageLog::file_path`]. The log gives the recently cited references and a boost to rank
+//! frequently and recently cited ones higher, see [`UsageLog::boosts`].
+//!
+//! Records older than [`USAGE_RETENTION_DAYS`] no longer add to a boost and are dropped when the
+//! log is loaded or rewritten, except the last record of the [`USAGE_RECENT_KEPT`] most recently
+//! cited keys. So the file holds about the citations of the last ten months.
+use crate::{Result, bib::Reference};
+use chrono::{DateTime, Duration, Local};
+use fs_err as fs;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+/// Days after which a citation counts half as much in [`UsageLog::boosts`].
+pub const USAGE_HALF_LIFE_DAYS: f64 = 30.0;
+
+/// Highest boost added to the score of a reference, below a matching key prefix.
+pub const MAX_USAGE_BOOST: u32 = 60;
+
+/// Boost of a citation made just now
+const BOOST_PER_USE: f64 = 15.0;
+
+/// Days after which a record is dropped from the log, ten half-lives, when a citation counts
+/// less than a thousandth of a new one.
+pub const USAGE_RETENTION_DAYS: i64 = 10 * USAGE_HALF_LIFE_DAYS as i64;
+
+/// Number of most recently cited keys, across libraries, whose last record is kept however old,
+/// so that [`UsageLog::recent`] still lists them.
+pub const USAGE_RECENT_KEPT: usize = 100;
+
+/// How a cite key was used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageAction {
+    /// Copied to the clipboard
+    Copy,
+    /// Copied and pasted into the previous window by the helper
+    Paste,
+}
+
+impl Display for UsageAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Copy => write!(f, "copy"),
+            Self::Paste => write!(f, "paste"),
+        }
+    }
+}
+
+/// A use of a cite key.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsageRecord {
+    pub time: DateTime<Local>,
+    pub key: String,
+    /// Name of the library the reference belongs to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub library: Option<String>,
+    /// Name of the citation template or style used
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    pub action: UsageAction,
+}
+
+impl UsageRecord {
+    /// A use of a cite key made now.
+    pub fn new(
+        key: &str,
+        library: Option<&str>,
+        template: Option<&str>,
+        action: UsageAction,
+    ) -> Self {
+        Self {
+            time: Local::now(),
+            key: key.to_string(),
+            library: library.map(str::to_string),
+            template: template.map(str::to_string),
+            action,
+        }
+    }
+
+    fn in_library(&self, library: Option<&str>) -> bool {
+        library.is_none_or(|library| self.library.as_deref() == Some(library))
+    }
+}
+
+/// The log of cited references, oldest first.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UsageLog {
+    path: PathBuf,
+    records: Vec<UsageRecord>,
+}
+
+impl UsageLog {
+    /// The log file, e.g., `$HOME/.config/BibCiTeX/usage.jsonl` on Linux, next to
+    /// [`crate::Setting::config_file_path`].
+    pub fn file_path() -> PathBuf {
+        dirs::config_dir()
+            .unwrap()
+            .join("BibCiTeX")
+            .join("usage.jsonl")
+    }
+
+    /// Load the log from [`Self::file_path`], empty if it does not exist or fails to read.
+    pub fn load() -> Self {
+        let path = Self::file_path();
+        Self::open(&path).unwrap_or(Self {
+            path,
+            records: Vec::new(),
+        })
+    }
+
+    /// Load a log file, empty if it does not exist.
+    ///
+    /// Malformed lines, e.g., from an interrupted write, are skipped. Expired records are
+    /// dropped and the file is rewritten without them, see [`USAGE_RETENTION_DAYS`].
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let records = if path.exists() {
+            fs::read_to_string(&path)?
+                .lines()
+                .filter_map(|line| serde_json::from_str(line).ok())
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let mut log = Self { path, records };
+        if log.prune(Local::now()) {
+            log.write()?;
+        }
+        Ok(log)
+    }
+
+    /// Drop the records older than [`USAGE_RETENTION_DAYS`] before `now`, keeping the last
+    /// record of the [`USAGE_RECENT_KEPT`] most recently cited keys.
+    ///
+    /// Returns whether a record was dropped.
+    fn prune(&mut self, now: DateTime<Local>) -> bool {
+        let expiry = now - Duration::days(USAGE_RETENTION_DAYS);
+        let mut seen = HashSet::new();
+        let mut keep = vec![false; self.records.len()];
+        for (i, record) in self.records.iter().enumerate().rev() {
+            let last = seen.len() < USAGE_RECENT_KEPT
+                && seen.insert((record.library.as_deref(), record.key.as_str()));
+            keep[i] = last || record.time >= expiry;
+        }
+        if keep.iter().all(|&keep| keep) {
+            return false;
+        }
+        let mut keep = keep.into_iter();
+        self.records.retain(|_| keep.next().unwrap_or(true));
+        true
+    }
+
+    /// Rewrite the file with the records.
+    fn write(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut lines = String::new();
+        for record in &self.records {
+            lines.push_str(&serde_json::to_string(record)?);
+            lines.push('\n');
+        }
+        fs::write(&self.path, lines)?;
+        Ok(())
+    }
+
+    /// All records, oldest first.
+    pub fn records(&self) -> &[UsageRecord] {
+        &self.records
+    }
+
+    /// Append records to the log and its file.
+    pub fn record(&mut self, records: impl IntoIterator<Item = UsageRecord>) -> Result<()> {
+        let records = records.into_iter().collect::<Vec<_>>();
+        if records.is_empty() {
+            return Ok(());
+        }
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut lines = String::new();
+        for record in &records {
+            lines.push_str(&serde_json::to_string(record)?);
+            lines.push('\n');
+        }
+        fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?
+            .write_all(lines.as_bytes())?;
+        self.records.extend(records);
+        Ok(())
+    }
+
+    /// Move the records of a reference in a library to its new cite key, rewriting the file
+    /// without expired records.
+    ///
+    /// Returns whether there was a record to move.
+    pub fn rename_key(&mut self, library: &str, old: &str, new: &str) -> Result<bool> {
+        let mut renamed = false;
+        for record in &mut self.records {
+            if record.key == old && record.in_library(Some(library)) {
+                record.key = new.to_string();
+                renamed = true;
+            }
+        }
+        if renamed {
+            self.prune(Local::now());
+            self.write()?;
+        }
+        Ok(renamed)
+    }
+
+    /// Cite keys of the last cited references, most recent first, in the library if given.
+    pub fn recent(&self, library: Option<&str>, limit: usize) -> Vec<&str> {
+        let mut seen = HashSet::new();
+        self.records
+            .iter()
+            .rev()
+            .filter(|record| record.in_library(library))
+            .map(|record| record.key.as_str())
+            .filter(|key| seen.insert(*key))
+            .take(limit)
+            .collect()
+    }
+
+    /// The last cited references found in `references`, most recent first.
+    pub fn recent_references(
+        &self,
+        references: &[Reference],
+        library: Option<&str>,
+        limit: usize,
+    ) -> Vec<Reference> {
+        let by_key = references
+            .iter()
+            .map(|reference| (reference.cite_key.as_str(), reference))
+            .collect::<HashMap<_, _>>();
+        let mut seen = HashSet::new();
+        self.records
+            .iter()
+            .rev()
+            .filter(|record| record.in_library(library))
+            .filter_map(|record| by_key.get(record.key.as_str()))
+            .filter(|reference| seen.insert(reference.cite_key.as_str()))
+            .take(limit)
+            .map(|reference| (*reference).clone())
+            .collect()
+    }
+
+    /// Boosts of the cited references in the library if given, by cite key.
+    ///
+    /// Each citation counts 15 points, halved every [`USAGE_HALF_LIFE_DAYS`] before `now`, and
+    /// the sum is capped at [`MAX_USAGE_BOOST`], so that frequent and recent citations rank
+    /// higher among comparable matches without outranking a better match.
+    pub fn boosts(&self, library: Option<&str>, now: DateTime<Local>) -> HashMap<String, u32> {
+        let mut weights = HashMap::<&str, f64>::new();
+        for record in self.records.iter().filter(|r| r.in_library(library)) {
+            let days = (now - record.time).num_seconds().max(0) as f64 / 86400.0;
+            *weights.entry(&record.key).or_default() += 0.5f64.powf(days / USAGE_HALF_LIFE_DAYS);
+        }
+        weights
+            .into_iter()
+            .map(|(key, weight)| {
+                let boost = (weight * BOOST_PER_USE).round() as u32;
+                (key.to_string(), boost.min(MAX_USAGE_BOOST))
+            })
+            .filter(|(_, boost)| *boost > 0)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(key: &str, library: &str, days_ago: i64) -> UsageRecord {
+        UsageRecord {
+            time: Local::now() - Duration::days(days_ago),
+            ..UsageRecord::new(key, Some(library), None, UsageAction::Copy)
+        }
+    }
+
+    #[test]
+    fn test_record() {
+        let path =
+            std::env::temp_dir().join(format!("bibcitex-usage-{}.jsonl", std::process::id()));
+        let mut log = UsageLog::open(&path).unwrap();
+        log.record([record("knuth1984", "papers", 1)]).unwrap();
+        log.record([UsageRecord::new(
+            "lamport1994",
+            Some("papers"),
+            Some("LaTeX"),
+            UsageAction::Paste,
+        )])
+        .unwrap();
+        fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(b"{\"key\": \n")
+            .unwrap();
+        let loaded = UsageLog::open(&path).unwrap();
+        assert_eq!(loaded.records(), log.records());
+        assert_eq!(loaded.records()[1].template.as_deref(), Some("LaTeX"));
+
+        let mut log = loaded;
+        assert!(log.rename_key("papers", "knuth1984", "knuth84").unwrap());
+        assert!(
+            !log.rename_key("thesis", "lamport1994", "lamport94")
+                .unwrap()
+        );
+        assert_eq!(
+            UsageLog::open(&path).unwrap().recent(None, 2),
+            ["lamport1994", "knuth84"]
+        );
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_recent_and_boosts() {
+        let log = UsageLog {
+            path: PathBuf::new(),
+            records: vec![
+                record("a", "papers", 100),
+                record("b", "papers", 10),
+                record("c", "thesis", 5),
+                record("a", "papers", 0),
+                record("a", "papers", 0),
+                record("a", "papers", 0),
+                record("a", "papers", 0),
+                record("a", "papers", 0),
+            ],
+        };
+        assert_eq!(log.recent(Some("papers"), 10), ["a", "b"]);
+        assert_eq!(log.recent(None, 2), ["a", "c"]);
+        let boosts = log.boosts(Some("papers"), Local::now());
+        assert_eq!(boosts["a"], MAX_USAGE_BOOST);
+        assert!(boosts["b"] > 0 && boosts["b"] < 15);
+        assert!(!boosts.contains_key("c"));
+    }
+
+    #[test]
+    fn test_prune() {
+        let path =
+            std::env::temp_dir().join(format!("bibcitex-usage-prune-{}.jsonl", std::process::id()));
+        let mut log = UsageLog::open(&path).unwrap();
+        let expired = USAGE_RETENTION_DAYS + 1;
+        log.record([
+            record("a", "papers", expired + 10),
+            record("b", "papers", expired + 5),
+            record("a", "papers", expired),
+            record("b", "papers", 1),
+        ])
+        .unwrap();
+        let log = UsageLog::open(&path).unwrap();
+        // The expired record of `b` is dropped, the last one of `a` is still recent.
+        assert_eq!(log.records().len(), 2);
+        assert_eq!(log.recent(None, 10), ["b", "a"]);
+        assert_eq!(UsageLog::open(&path).unwrap(), log);
+
+        let mut log = UsageLog {
+            path: PathBuf::new(),
+            records: (0..USAGE_RECENT_KEPT + 1)
+                .map(|i| record(&i.to_string(), "papers", expired))
+                .collect(),
+        };
+        assert!(log.prune(Local::now()));
+        assert_eq!(log.records().len(), USAGE_RECENT_KEPT);
+        assert_eq!(log.records()[0].key, "1");
+        assert!(!log.prune(Local::now()));
+        fs::remove_file(path).unwrap();
+    }
+}
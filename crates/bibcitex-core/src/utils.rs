@@ -1,4 +1,4 @@
-use crate::bib::Reference;
+use crate::{bib::Reference, cjk};
 use biblatex::{Bibliography, Chunk, Chunks};
 use rayon::prelude::*;
 
@@ -33,6 +33,23 @@ pub fn read_bibliography(bibliography: Bibliography) -> Vec<Reference> {
     }
 }
 
+/// Read a bibliography like [`read_bibliography`], splitting the `keywords` field into tags on
+/// any of `tag_separators`.
+pub fn read_bibliography_with(bibliography: Bibliography, tag_separators: &str) -> Vec<Reference> {
+    let entries = bibliography.into_vec();
+    if entries.len() > 80 {
+        entries
+            .par_iter()
+            .map(|entry| Reference::from_entry(entry, tag_separators))
+            .collect()
+    } else {
+        entries
+            .iter()
+            .map(|entry| Reference::from_entry(entry, tag_separators))
+            .collect()
+    }
+}
+
 /// Merge [`Chunk::Normal`] and [`Chunk::Verbatim`] chunks.
 pub fn merge_chunks(chunks: Chunks) -> Vec<Chunk> {
     let mut chunks_iter = chunks.into_iter();
@@ -56,6 +73,22 @@ pub fn merge_chunks(chunks: Chunks) -> Vec<Chunk> {
     merged
 }
 
+/// Concatenate chunks into a plain string, keeping math chunks wrapped in `$`.
+pub fn chunks_to_string(chunks: &[Chunk]) -> String {
+    chunks
+        .iter()
+        .map(|chunk| match chunk {
+            Chunk::Math(math) => format!("${math}$"),
+            Chunk::Normal(txt) | Chunk::Verbatim(txt) => txt.clone(),
+        })
+        .collect()
+}
+
+/// Whether the string contains any CJK character, see [`cjk::is_cjk`].
+pub fn contains_cjk(s: &str) -> bool {
+    s.chars().any(cjk::is_cjk)
+}
+
 /// Abbreviate a path string to a maximum length.
 pub fn abbr_path(path_str: &str, max_length: usize) -> String {
     if path_str.len() <= max_length {
@@ -1,22 +1,45 @@
 use crate::{
-    ADD_ICON, CURRENT_REF, DELETE_ICON, ERR_ICON, OK_ICON, STATE,
+    ADD_ICON, CURRENT_BIB, CURRENT_REF, DELETE_ICON, ERR_ICON, OK_ICON, STATE,
+    components::{TidyBibliography, apply_journal_style, journal_style_name, load_annotations},
+    drop_search_index,
     route::Route,
+    sync_search_index,
     views::{get_helper_bib, set_helper_bib},
 };
 use bibcitex_core::{
-    bib::parse,
-    utils::{abbr_path, read_bibliography},
+    JournalStyle,
+    utils::{abbr_path, read_bibliography_with},
 };
 use dioxus::prelude::*;
 use itertools::Itertools;
 use rfd::FileDialog;
 use std::{path::PathBuf, time::Duration};
 
+/// 可为文献库指定的文件编码
+const ENCODINGS: [(&str, &str); 6] = [
+    ("utf-8", "UTF-8"),
+    ("gbk", "GBK"),
+    ("gb18030", "GB18030"),
+    ("big5", "Big5"),
+    ("latin1", "Latin-1"),
+    ("shift_jis", "Shift_JIS"),
+];
+
 #[component]
-pub fn Bibliographies(mut show_modal: Signal<bool>) -> Element {
+pub fn Bibliographies(
+    mut show_modal: Signal<bool>,
+    mut show_templates: Signal<bool>,
+    mut show_journals: Signal<bool>,
+) -> Element {
     let open_modal = move |_| {
         show_modal.set(true);
     };
+    let open_templates = move |_| {
+        show_templates.set(true);
+    };
+    let open_journals = move |_| {
+        show_journals.set(true);
+    };
     let mut error_message = use_context_provider(|| Signal::new(None::<String>));
     let mut is_fading_out = use_signal(|| false);
     let show_error = use_memo(move || error_message().is_some() || is_fading_out());
@@ -52,13 +75,17 @@ pub fn Bibliographies(mut show_modal: Signal<bool>) -> Element {
                     h2 { class: "text-3xl font-bold gradient-text", "Bibliographies" }
                     p { class: "text-base-content/60 text-sm mt-1", "管理你的文献库" }
                 }
-                button { class: "btn btn-modern gap-2", onclick: open_modal,
-                    img {
-                        width: 16,
-                        src: ADD_ICON,
-                        class: "invert brightness-0",
+                div { class: "flex items-center gap-2",
+                    button { class: "btn btn-ghost gap-2", onclick: open_templates, "引用模板" }
+                    button { class: "btn btn-ghost gap-2", onclick: open_journals, "期刊缩写" }
+                    button { class: "btn btn-modern gap-2", onclick: open_modal,
+                        img {
+                            width: 16,
+                            src: ADD_ICON,
+                            class: "invert brightness-0",
+                        }
+                        "新建文献库"
                     }
-                    "新建文献库"
                 }
             }
             BibliographyTable {}
@@ -255,6 +282,7 @@ pub fn AddBibliography(mut show: Signal<bool>) -> Element {
 pub fn BibliographyTable() -> Element {
     let mut error_message = use_context::<Signal<Option<String>>>();
     let navigator = use_navigator();
+    let mut tidying = use_signal(|| None::<String>);
     let pairs = use_memo(|| {
         let state = STATE.read();
         state
@@ -266,23 +294,38 @@ pub fn BibliographyTable() -> Element {
                     name.clone(),
                     name.clone(),
                     info.path.as_os_str().to_str().unwrap().to_string(),
-                    info.path.as_os_str().to_str().unwrap().to_string(),
+                    info.encoding.clone(),
                     info.path.as_os_str().to_str().unwrap().to_string(),
                     info.updated_at.format("%Y-%m-%d %H:%M:%S").to_string(),
                     info.description.clone(),
                     info.path.exists(),
+                    info.template.clone(),
+                    info.journal_style,
                 )
             })
             .collect::<Vec<_>>()
     });
+    let templates = use_memo(|| {
+        STATE
+            .read()
+            .templates
+            .iter()
+            .map(|t| t.name.clone())
+            .collect::<Vec<_>>()
+    });
 
-    let mut open_bib = move |path: String| {
+    let mut open_bib = move |name: String| {
         error_message.set(None);
-        match parse(&path) {
+        let parsed = STATE.read().parse(&name);
+        match parsed {
             Ok(bib) => {
-                let refs = read_bibliography(bib);
+                let mut refs = read_bibliography_with(bib, &STATE.read().tag_separators);
+                load_annotations(&name, &mut refs);
+                apply_journal_style(&name, &mut refs);
+                sync_search_index(&name, &refs);
                 let mut current_ref = CURRENT_REF.write();
                 *current_ref = Some(refs);
+                *CURRENT_BIB.write() = Some(name);
                 navigator.push(Route::References {});
             }
             Err(e) => {
@@ -294,6 +337,7 @@ pub fn BibliographyTable() -> Element {
     let mut delete_bib = move |bib_name: String| {
         let mut state = STATE.write();
         state.remove_bibliography(&bib_name);
+        drop_search_index(&bib_name);
         let result = state.update_file();
         if let Err(e) = result {
             error_message.set(Some(e.to_string()));
@@ -305,6 +349,37 @@ pub fn BibliographyTable() -> Element {
         }
     };
 
+    let mut set_template = move |bib_name: String, template: String| {
+        let mut state = STATE.write();
+        let template = (!template.is_empty()).then_some(template);
+        let result = state
+            .set_bibliography_template(&bib_name, template.as_deref())
+            .and_then(|_| state.update_file());
+        if let Err(e) = result {
+            error_message.set(Some(e.to_string()));
+        }
+    };
+
+    let mut set_journal_style = move |bib_name: String, style: String| {
+        let mut state = STATE.write();
+        if let Some(info) = state.bibliographies.get_mut(&bib_name) {
+            info.journal_style = style.parse().ok();
+        }
+        if let Err(e) = state.update_file() {
+            error_message.set(Some(e.to_string()));
+        }
+    };
+
+    let mut set_encoding = move |bib_name: String, encoding: String| {
+        let mut state = STATE.write();
+        if let Some(info) = state.bibliographies.get_mut(&bib_name) {
+            info.encoding = (!encoding.is_empty()).then_some(encoding);
+        }
+        if let Err(e) = state.update_file() {
+            error_message.set(Some(e.to_string()));
+        }
+    };
+
     let mut open_bib_file = move |path: String| {
         let result = opener::open(&path);
         if let Err(e) = result {
@@ -322,7 +397,7 @@ pub fn BibliographyTable() -> Element {
                 }
             } else {
                 div { class: "grid grid-cols-1 md:grid-cols-2 lg:grid-cols-3 gap-8 p-4",
-                    for (name , name_clone , path , path_clone , path_clone_2 , updated_at , description , is_exist) in pairs() {
+                    for (name , name_clone , path , encoding , path_clone_2 , updated_at , description , is_exist , template , journal_style) in pairs() {
                         div { class: "card-modern card-shine group relative overflow-hidden flex flex-col h-full min-h-[200px] transition-all duration-500 hover:-translate-y-2 hover:shadow-primary/10 border-white/5",
                             // Decorative Background Elements
                             div { class: "absolute -top-20 -right-20 w-40 h-40 bg-primary/5 rounded-full blur-3xl group-hover:bg-primary/10 transition-all duration-700 animate-blob" }
@@ -391,11 +466,77 @@ pub fn BibliographyTable() -> Element {
                                             span { class: "opacity-50", "🕒" }
                                             span { class: "font-mono opacity-80", "{updated_at}" }
                                         }
+                                        div { class: "flex items-center gap-2",
+                                            span { class: "opacity-50", "✒️" }
+                                            select {
+                                                class: "select select-ghost select-xs font-mono",
+                                                title: "引用模板",
+                                                onchange: {
+                                                    let bib_name = name.clone();
+                                                    move |e: Event<FormData>| set_template(bib_name.clone(), e.value())
+                                                },
+                                                option { value: "", selected: template.is_none(), "默认模板" }
+                                                for t in templates() {
+                                                    option {
+                                                        value: "{t}",
+                                                        selected: template.as_deref() == Some(t.as_str()),
+                                                        "{t}"
+                                                    }
+                                                }
+                                            }
+                                        }
+                                        div { class: "flex items-center gap-2",
+                                            span { class: "opacity-50", "📰" }
+                                            select {
+                                                class: "select select-ghost select-xs",
+                                                title: "期刊名",
+                                                onchange: {
+                                                    let bib_name = name.clone();
+                                                    move |e: Event<FormData>| set_journal_style(bib_name.clone(), e.value())
+                                                },
+                                                option { value: "", selected: journal_style.is_none(), "期刊名原样" }
+                                                for style in JournalStyle::all().iter().copied() {
+                                                    option {
+                                                        value: "{style}",
+                                                        selected: journal_style == Some(style),
+                                                        "{journal_style_name(style)}"
+                                                    }
+                                                }
+                                            }
+                                        }
+                                        div { class: "flex items-center gap-2",
+                                            span { class: "opacity-50", "🔤" }
+                                            select {
+                                                class: "select select-ghost select-xs font-mono",
+                                                title: "文件编码",
+                                                onchange: {
+                                                    let bib_name = name.clone();
+                                                    move |e: Event<FormData>| set_encoding(bib_name.clone(), e.value())
+                                                },
+                                                option { value: "", selected: encoding.is_none(), "自动检测编码" }
+                                                for (label , text) in ENCODINGS {
+                                                    option {
+                                                        value: "{label}",
+                                                        selected: encoding.as_deref() == Some(label),
+                                                        "{text}"
+                                                    }
+                                                }
+                                            }
+                                        }
                                     }
                                 }
 
                                 // Actions overlay (visible on hover or always visible but styled)
                                 div { class: "absolute bottom-4 right-4 flex gap-2 opacity-0 group-hover:opacity-100 translate-y-2 group-hover:translate-y-0 transition-all duration-300",
+                                    button {
+                                        class: "btn btn-sm btn-ghost rounded-full shadow-sm border border-transparent hover:border-primary/20",
+                                        disabled: !is_exist,
+                                        onclick: {
+                                            let bib_name = name.clone();
+                                            move |_| tidying.set(Some(bib_name.clone()))
+                                        },
+                                        "整理文件"
+                                    }
                                     button {
                                         class: "btn btn-sm btn-circle btn-ghost text-error hover:bg-error/10 tooltip tooltip-left shadow-sm border border-transparent hover:border-error/20",
                                         "data-tip": "删除",
@@ -408,7 +549,10 @@ pub fn BibliographyTable() -> Element {
                                     }
                                     button {
                                         class: "btn btn-sm btn-primary shadow-lg shadow-primary/30 hover:shadow-primary/50 border-none animate-gradient-x bg-linear-to-r from-primary to-secondary text-white gap-2 px-4 rounded-full",
-                                        onclick: move |_| open_bib(path_clone.clone()),
+                                        onclick: {
+                                            let bib_name = name.clone();
+                                            move |_| open_bib(bib_name.clone())
+                                        },
                                         span { "打开" }
                                         span { class: "group-hover:translate-x-1 transition-transform",
                                             "→"
@@ -420,6 +564,9 @@ pub fn BibliographyTable() -> Element {
                     }
                 }
             }
+            if let Some(library) = tidying() {
+                TidyBibliography { library, tidying }
+            }
         }
     }
 }
@@ -0,0 +1,384 @@
+use crate::{COPY_ICON, DELETE_ICON, ERR_ICON, OK_ICON, STATE};
+use bibcitex_core::{
+    BuiltinStyle, CitationTemplate, CslStyle, GbtStyle, OutputFormat, bib::Reference,
+    format_gbt7714, format_reference,
+};
+use dioxus::prelude::*;
+use rfd::FileDialog;
+
+/// Render the citation template of a bibliography with the given keys.
+pub fn cite_command<S: AsRef<str>>(
+    keys: &[S],
+    locator: Option<&str>,
+    bibliography: Option<&str>,
+) -> String {
+    STATE
+        .read()
+        .citation_template(bibliography)
+        .render(keys, locator)
+}
+
+/// A citation style that can be selected.
+#[derive(Debug, Clone, PartialEq)]
+enum Style {
+    Gbt(GbtStyle),
+    Csl(CslStyle),
+}
+
+/// Resolve a style name to a GB/T 7714 style, a bundled style or a user-provided style.
+///
+/// User-provided styles are read and parsed from their files.
+fn load_style(name: &str) -> Result<Style, String> {
+    if let Some(style) = [GbtStyle::Numeric, GbtStyle::AuthorYear]
+        .into_iter()
+        .find(|style| style.to_string() == name)
+    {
+        Ok(Style::Gbt(style))
+    } else if let Some(builtin) = BuiltinStyle::all()
+        .iter()
+        .find(|style| style.to_string() == name)
+    {
+        Ok(Style::Csl(CslStyle::builtin(*builtin)))
+    } else {
+        STATE
+            .peek()
+            .load_csl_style(name)
+            .map(Style::Csl)
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Format a reference with a style, returning the formatted text and, for HTML, its plain text.
+///
+/// GB/T 7714 styles only produce plain text.
+fn render(
+    entry: &Reference,
+    style: &Style,
+    format: OutputFormat,
+) -> Result<(String, Option<String>), String> {
+    let style = match style {
+        Style::Gbt(style) => return Ok((format_gbt7714(entry, *style), None)),
+        Style::Csl(style) => style,
+    };
+    let text = format_reference(entry, style, format).map_err(|e| e.to_string())?;
+    // HTML is copied as rich text with the plain rendering as the fallback.
+    let plain = if format == OutputFormat::Html {
+        format_reference(entry, style, OutputFormat::Text).ok()
+    } else {
+        None
+    };
+    Ok((text, plain))
+}
+
+/// 格式化引用组件
+#[component]
+pub fn FormattedCitation(entry: Reference) -> Element {
+    let mut style_name = use_signal(|| BuiltinStyle::Apa.to_string());
+    let mut format = use_signal(OutputFormat::default);
+    let mut copy_success = use_signal(|| true);
+    let mut copied = use_signal(|| false);
+    let mut error_message = use_signal(|| None::<String>);
+    let custom_styles = use_memo(|| STATE.read().csl_styles.keys().cloned().collect::<Vec<_>>());
+    // 样式只在切换或重新加载时读取，自定义样式不必每次渲染都解析文件
+    let style = use_memo(move || load_style(&style_name()));
+    let formatted = match &*style.read() {
+        Ok(style) => render(&entry, style, format()),
+        Err(e) => Err(e.clone()),
+    };
+
+    let on_style_change = move |e: Event<FormData>| {
+        style_name.set(e.value());
+    };
+    let on_format_change = move |e: Event<FormData>| {
+        let new_format = match e.value().as_str() {
+            "HTML" => OutputFormat::Html,
+            "Markdown" => OutputFormat::Markdown,
+            _ => OutputFormat::Text,
+        };
+        format.set(new_format);
+    };
+
+    let load_csl = move |_| {
+        let file = FileDialog::new()
+            .add_filter("csl", &["csl", "xml"])
+            .set_title("选择 CSL 样式文件")
+            .pick_file();
+        if let Some(file) = file {
+            let name = file
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .unwrap_or("custom")
+                .to_string();
+            let mut state = STATE.write();
+            match state.add_csl_style(&name, file) {
+                Ok(_) => {
+                    if let Err(e) = state.update_file() {
+                        error_message.set(Some(e.to_string()));
+                    } else {
+                        error_message.set(None);
+                        style_name.set(name);
+                    }
+                }
+                Err(e) => error_message.set(Some(e.to_string())),
+            }
+        }
+    };
+
+    let copy = {
+        let formatted = formatted.clone();
+        move |_| {
+            copied.set(true);
+            let success = match (&formatted, arboard::Clipboard::new()) {
+                (Ok((text, plain)), Ok(mut clipboard)) => {
+                    if format() == OutputFormat::Html && plain.is_some() {
+                        clipboard.set_html(text.as_str(), plain.as_deref()).is_ok()
+                    } else {
+                        clipboard.set_text(text.as_str()).is_ok()
+                    }
+                }
+                _ => false,
+            };
+            copy_success.set(success);
+            spawn(async move {
+                tokio::time::sleep(tokio::time::Duration::from_millis(1500)).await;
+                copied.set(false);
+            });
+        }
+    };
+
+    rsx! {
+        div { class: "collapse collapse-arrow bg-base-200/30 hover:bg-base-200/50 transition-colors rounded-box mt-2",
+            input { r#type: "checkbox" }
+            div { class: "collapse-title font-medium", "Citation" }
+            div { class: "collapse-content space-y-2",
+                div { class: "join w-full",
+                    select {
+                        class: "select select-bordered select-sm join-item flex-1",
+                        onchange: on_style_change,
+                        for builtin in BuiltinStyle::all() {
+                            option {
+                                value: "{builtin}",
+                                selected: style_name() == builtin.to_string(),
+                                "{builtin}"
+                            }
+                        }
+                        for gbt in [GbtStyle::Numeric, GbtStyle::AuthorYear] {
+                            option {
+                                value: "{gbt}",
+                                selected: style_name() == gbt.to_string(),
+                                "{gbt}"
+                            }
+                        }
+                        for name in custom_styles() {
+                            option {
+                                value: "{name}",
+                                selected: style_name() == name,
+                                "{name}"
+                            }
+                        }
+                    }
+                    select {
+                        class: "select select-bordered select-sm join-item w-28",
+                        onchange: on_format_change,
+                        for output in [OutputFormat::Text, OutputFormat::Html, OutputFormat::Markdown] {
+                            option {
+                                value: "{output}",
+                                selected: format() == output,
+                                "{output}"
+                            }
+                        }
+                    }
+                    button {
+                        class: "btn btn-sm join-item tooltip tooltip-left",
+                        "data-tip": "加载 CSL 样式",
+                        onclick: load_csl,
+                        "CSL"
+                    }
+                }
+                match formatted {
+                    Ok((text, _)) => rsx! {
+                        div { class: "flex items-start gap-2",
+                            p { class: "flex-1 text-sm break-all select-all", "{text}" }
+                            button {
+                                class: "btn btn-ghost btn-sm btn-circle tooltip tooltip-left",
+                                "data-tip": "Copy",
+                                onclick: copy,
+                                if !copied() {
+                                    img {
+                                        width: 18,
+                                        src: COPY_ICON,
+                                        class: "opacity-70",
+                                    }
+                                } else if copy_success() {
+                                    img { width: 18, src: OK_ICON }
+                                } else {
+                                    img { width: 18, src: ERR_ICON }
+                                }
+                            }
+                        }
+                    },
+                    Err(e) => rsx! {
+                        p { class: "text-sm text-error", "{e}" }
+                    },
+                }
+                if let Some(error) = error_message() {
+                    p { class: "text-sm text-error", "{error}" }
+                }
+            }
+        }
+    }
+}
+
+/// 引用模板管理组件
+#[component]
+pub fn CitationTemplates(mut show: Signal<bool>) -> Element {
+    let templates = use_memo(|| STATE.read().templates.clone());
+    let default_template = use_memo(|| STATE.read().default_template.clone());
+    let mut name = use_signal(String::new);
+    let mut pattern = use_signal(String::new);
+    let mut separator = use_signal(|| ",".to_string());
+    let mut error_message = use_signal(|| None::<String>);
+    let save_available = use_memo(move || !name().trim().is_empty() && !pattern().is_empty());
+    let preview = use_memo(move || {
+        CitationTemplate::new(&name(), &pattern(), &separator())
+            .render(&["key1", "key2"], Some("12"))
+    });
+
+    let close_modal = move |_| {
+        show.set(false);
+    };
+
+    let save = move |_| {
+        let mut state = STATE.write();
+        let template = CitationTemplate::new(name().trim(), &pattern(), &separator());
+        state.add_update_template(template);
+        if let Err(e) = state.update_file() {
+            error_message.set(Some(e.to_string()));
+        } else {
+            error_message.set(None);
+            name.set(String::new());
+            pattern.set(String::new());
+            separator.set(",".to_string());
+        }
+    };
+
+    let mut set_default = move |template: Option<String>| {
+        let mut state = STATE.write();
+        let result = state
+            .set_default_template(template.as_deref())
+            .and_then(|_| state.update_file());
+        if let Err(e) = result {
+            error_message.set(Some(e.to_string()));
+        }
+    };
+
+    let mut remove = move |template: String| {
+        let mut state = STATE.write();
+        state.remove_template(&template);
+        if let Err(e) = state.update_file() {
+            error_message.set(Some(e.to_string()));
+        }
+    };
+
+    rsx! {
+        div { class: if show() { "modal modal-open backdrop-blur-sm" } else { "modal" },
+            div { class: "modal-box w-1/2 max-w-2xl glass-panel shadow-2xl",
+                h3 { class: "text-2xl font-bold mb-2 gradient-text", "引用模板" }
+                p { class: "text-sm text-base-content/60 mb-6",
+                    "%k 为引用键，%p 为页码等定位符，%% 为百分号"
+                }
+
+                table { class: "table table-sm mb-6",
+                    thead {
+                        tr {
+                            th { "默认" }
+                            th { "名称" }
+                            th { "模板" }
+                            th { "分隔符" }
+                            th {}
+                        }
+                    }
+                    tbody {
+                        for template in templates() {
+                            tr {
+                                td {
+                                    input {
+                                        r#type: "radio",
+                                        class: "radio radio-primary radio-xs",
+                                        name: "default-template",
+                                        checked: default_template().as_deref().unwrap_or("Key") == template.name,
+                                        onchange: {
+                                            let template_name = template.name.clone();
+                                            move |_| set_default(Some(template_name.clone()))
+                                        },
+                                    }
+                                }
+                                td { class: "font-medium", "{template.name}" }
+                                td { class: "font-mono", "{template.pattern}" }
+                                td { class: "font-mono", "\"{template.separator}\"" }
+                                td {
+                                    button {
+                                        class: "btn btn-ghost btn-xs btn-circle",
+                                        onclick: {
+                                            let template_name = template.name.clone();
+                                            move |_| remove(template_name.clone())
+                                        },
+                                        img { width: 14, src: DELETE_ICON }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+
+                div { class: "join w-full mb-2",
+                    input {
+                        class: "input input-bordered input-sm join-item w-1/4",
+                        placeholder: "名称",
+                        value: "{name}",
+                        oninput: move |e| name.set(e.value()),
+                    }
+                    input {
+                        class: "input input-bordered input-sm join-item grow font-mono",
+                        placeholder: "\\cite{{%k}}",
+                        value: "{pattern}",
+                        oninput: move |e| pattern.set(e.value()),
+                    }
+                    input {
+                        class: "input input-bordered input-sm join-item w-16 font-mono",
+                        placeholder: ",",
+                        value: "{separator}",
+                        oninput: move |e| separator.set(e.value()),
+                    }
+                    button {
+                        class: "btn btn-primary btn-sm join-item",
+                        disabled: !save_available(),
+                        onclick: save,
+                        "保存"
+                    }
+                }
+                if !pattern().is_empty() {
+                    p { class: "text-xs text-base-content/50 font-mono", "预览: {preview}" }
+                }
+
+                if let Some(error) = error_message() {
+                    div {
+                        role: "alert",
+                        class: "alert alert-error mt-4 shadow-sm",
+                        img { width: 20, src: ERR_ICON }
+                        span { "{error}" }
+                    }
+                }
+
+                div { class: "modal-action mt-8",
+                    button {
+                        class: "btn btn-ghost hover:bg-base-content/10",
+                        onclick: close_modal,
+                        "关闭"
+                    }
+                }
+            }
+            div { class: "modal-backdrop bg-base-300/30", onclick: close_modal }
+        }
+    }
+}
@@ -1,5 +1,5 @@
-use crate::components::ChunksComp;
-use bibcitex_core::bib::Reference;
+use crate::components::{ChunksComp, Highlighted};
+use bibcitex_core::{QueryField, bib::Reference};
 use dioxus::prelude::*;
 
 #[component]
@@ -12,7 +12,11 @@ pub fn ArticleHelper(entry: Reference) -> Element {
                     div { class: "badge badge-info badge-soft badge-sm font-bold", "Article" }
                     if let Some(title) = entry.title {
                         span { class: "text-gray-900 dark:text-gray-100 font-serif font-medium",
-                            ChunksComp { chunks: title, cite_key: key.clone() }
+                            ChunksComp {
+                                chunks: title,
+                                cite_key: key.clone(),
+                                field: QueryField::Title,
+                            }
                         }
                     } else {
                         span { class: "text-gray-900 dark:text-gray-100 font-serif italic",
@@ -29,7 +33,7 @@ pub fn ArticleHelper(entry: Reference) -> Element {
                     if authors.len() > 3 {
                         for author in authors.iter().take(3) {
                             span { class: "badge badge-ghost badge-xs hover:badge-info transition-colors cursor-default",
-                                "{author}"
+                                Highlighted { text: author.clone(), field: QueryField::Author }
                             }
                         }
                         span { class: "badge badge-ghost badge-xs hover:badge-info transition-colors cursor-default",
@@ -38,7 +42,7 @@ pub fn ArticleHelper(entry: Reference) -> Element {
                     } else {
                         for author in authors {
                             span { class: "badge badge-ghost badge-xs hover:badge-info transition-colors cursor-default",
-                                "{author}"
+                                Highlighted { text: author.clone(), field: QueryField::Author }
                             }
                         }
                     }
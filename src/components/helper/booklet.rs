@@ -1,7 +1,7 @@
-use bibcitex_core::bib::Reference;
+use bibcitex_core::{QueryField, bib::Reference};
 use dioxus::prelude::*;
 
-use crate::components::ChunksComp;
+use crate::components::{ChunksComp, Highlighted};
 
 #[component]
 pub fn BookletHelper(entry: Reference) -> Element {
@@ -13,7 +13,11 @@ pub fn BookletHelper(entry: Reference) -> Element {
                     div { class: "badge badge-info badge-soft badge-sm font-bold", "Booklet" }
                     if let Some(title) = entry.title {
                         span { class: "text-gray-900 dark:text-gray-100 font-serif font-medium",
-                            ChunksComp { chunks: title, cite_key: key.clone() }
+                            ChunksComp {
+                                chunks: title,
+                                cite_key: key.clone(),
+                                field: QueryField::Title,
+                            }
                         }
                     } else {
                         span { class: "text-gray-900 dark:text-gray-100 font-serif italic",
@@ -30,7 +34,7 @@ pub fn BookletHelper(entry: Reference) -> Element {
                     if authors.len() > 3 {
                         for author in authors.iter().take(3) {
                             span { class: "badge badge-ghost badge-xs hover:badge-info transition-colors cursor-default",
-                                "{author}"
+                                Highlighted { text: author.clone(), field: QueryField::Author }
                             }
                         }
                         span { class: "badge badge-ghost badge-xs hover:badge-info transition-colors cursor-default",
@@ -39,7 +43,7 @@ pub fn BookletHelper(entry: Reference) -> Element {
                     } else {
                         for author in authors {
                             span { class: "badge badge-ghost badge-xs hover:badge-info transition-colors cursor-default",
-                                "{author}"
+                                Highlighted { text: author.clone(), field: QueryField::Author }
                             }
                         }
                     }
@@ -1,7 +1,7 @@
-use bibcitex_core::bib::Reference;
+use bibcitex_core::{QueryField, bib::Reference};
 use dioxus::prelude::*;
 
-use crate::components::ChunksComp;
+use crate::components::{ChunksComp, Highlighted};
 
 #[component]
 pub fn InProceedingsHelper(entry: Reference) -> Element {
@@ -24,7 +24,11 @@ pub fn InProceedingsHelper(entry: Reference) -> Element {
                     }
                     if let Some(title) = entry.title {
                         span { class: "text-gray-900 dark:text-gray-100 font-serif font-medium",
-                            ChunksComp { chunks: title, cite_key: key.clone() }
+                            ChunksComp {
+                                chunks: title,
+                                cite_key: key.clone(),
+                                field: QueryField::Title,
+                            }
                         }
                     } else {
                         span { class: "text-gray-900 dark:text-gray-100 font-serif italic",
@@ -41,7 +45,7 @@ pub fn InProceedingsHelper(entry: Reference) -> Element {
                     if authors.len() > 3 {
                         for author in authors.iter().take(3) {
                             span { class: "badge badge-ghost badge-xs hover:badge-primary transition-colors cursor-default",
-                                "{author}"
+                                Highlighted { text: author.clone(), field: QueryField::Author }
                             }
                         }
                         span { class: "badge badge-ghost badge-xs hover:badge-primary transition-colors cursor-default",
@@ -50,7 +54,7 @@ pub fn InProceedingsHelper(entry: Reference) -> Element {
                     } else {
                         for author in authors {
                             span { class: "badge badge-ghost badge-xs hover:badge-primary transition-colors cursor-default",
-                                "{author}"
+                                Highlighted { text: author.clone(), field: QueryField::Author }
                             }
                         }
                     }
@@ -64,6 +68,7 @@ pub fn InProceedingsHelper(entry: Reference) -> Element {
                         ChunksComp {
                             chunks: booktitle,
                             cite_key: format!("booktitle-helper-{key}"),
+                            field: QueryField::BookTitle,
                         }
                     }
                 }
@@ -1,7 +1,7 @@
-use bibcitex_core::bib::Reference;
+use bibcitex_core::{QueryField, bib::Reference};
 use dioxus::prelude::*;
 
-use crate::components::ChunksComp;
+use crate::components::{ChunksComp, Highlighted};
 
 #[component]
 pub fn ArXivHelper(entry: Reference) -> Element {
@@ -22,7 +22,11 @@ pub fn ArXivHelper(entry: Reference) -> Element {
                     }
                     if let Some(title) = entry.title {
                         span { class: "text-gray-900 dark:text-gray-100 font-serif font-medium",
-                            ChunksComp { chunks: title, cite_key: key.clone() }
+                            ChunksComp {
+                                chunks: title,
+                                cite_key: key.clone(),
+                                field: QueryField::Title,
+                            }
                         }
                     } else {
                         span { class: "text-gray-900 dark:text-gray-100 font-serif italic",
@@ -39,7 +43,7 @@ pub fn ArXivHelper(entry: Reference) -> Element {
                     if authors.len() > 3 {
                         for author in authors.iter().take(3) {
                             span { class: "badge badge-ghost badge-xs hover:badge-error transition-colors cursor-default",
-                                "{author}"
+                                Highlighted { text: author.clone(), field: QueryField::Author }
                             }
                         }
                         span { class: "badge badge-ghost badge-xs hover:badge-error transition-colors cursor-default",
@@ -48,7 +52,7 @@ pub fn ArXivHelper(entry: Reference) -> Element {
                     } else {
                         for author in authors {
                             span { class: "badge badge-ghost badge-xs hover:badge-error transition-colors cursor-default",
-                                "{author}"
+                                Highlighted { text: author.clone(), field: QueryField::Author }
                             }
                         }
                     }
@@ -91,7 +95,11 @@ pub fn MiscHelper(entry: Reference) -> Element {
                         }
                         if let Some(title) = entry.title {
                             span { class: "text-gray-900 dark:text-gray-100 font-serif font-medium",
-                                ChunksComp { chunks: title, cite_key: key.clone() }
+                                ChunksComp {
+                                    chunks: title,
+                                    cite_key: key.clone(),
+                                    field: QueryField::Title,
+                                }
                             }
                         } else {
                             span { class: "text-gray-900 dark:text-gray-100 font-serif italic",
@@ -108,7 +116,7 @@ pub fn MiscHelper(entry: Reference) -> Element {
                         if authors.len() > 3 {
                             for author in authors.iter().take(3) {
                                 span { class: "badge badge-ghost badge-xs hover:badge-neutral transition-colors cursor-default",
-                                    "{author}"
+                                    Highlighted { text: author.clone(), field: QueryField::Author }
                                 }
                             }
                             span { class: "badge badge-ghost badge-xs hover:badge-neutral transition-colors cursor-default",
@@ -117,7 +125,7 @@ pub fn MiscHelper(entry: Reference) -> Element {
                         } else {
                             for author in authors {
                                 span { class: "badge badge-ghost badge-xs hover:badge-neutral transition-colors cursor-default",
-                                    "{author}"
+                                    Highlighted { text: author.clone(), field: QueryField::Author }
                                 }
                             }
                         }
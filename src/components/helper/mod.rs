@@ -1,4 +1,5 @@
-use bibcitex_core::bib::Reference;
+use crate::components::{Highlight, Highlighted};
+use bibcitex_core::{QueryField, bib::Reference, snippet};
 use biblatex::EntryType;
 use dioxus::prelude::*;
 
@@ -27,8 +28,34 @@ pub use thesis::*;
 mod unimplemented;
 pub use unimplemented::*;
 
+/// 摘要片段前后保留的字符数
+const SNIPPET_CONTEXT: usize = 40;
+
+/// 仅摘要匹配搜索时，显示摘要中匹配处附近的片段
+#[component]
+fn AbstractSnippet(entry: Reference) -> Element {
+    let snippet = try_use_context::<Highlight>()
+        .and_then(|highlight| highlight.0())
+        .and_then(|query| {
+            let matches = query.match_spans(&entry);
+            if matches.iter().any(|m| m.field != QueryField::Abstract) {
+                return None;
+            }
+            let first = matches.first()?;
+            Some(snippet(&first.value, first.spans[0].clone(), SNIPPET_CONTEXT).0)
+        });
+    rsx! {
+        if let Some(snippet) = snippet {
+            p { class: "mt-1 text-xs text-base-content/60 italic",
+                Highlighted { text: snippet, field: QueryField::Abstract }
+            }
+        }
+    }
+}
+
 #[component]
 pub fn HelperComponent(entry: Reference) -> Element {
+    let abstract_entry = entry.clone();
     rsx! {
         match entry.type_ {
             EntryType::Article => rsx! {
@@ -64,5 +91,6 @@ pub fn HelperComponent(entry: Reference) -> Element {
                 UnimplementedHelper { entry }
             },
         }
+        AbstractSnippet { entry: abstract_entry }
     }
 }
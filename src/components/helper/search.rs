@@ -1,13 +1,18 @@
 use crate::{
     LOGO,
-    components::{BibliographySelector, HelperComponent},
-    views::{HELPER_BIB, HELPER_WINDOW, MAX_HEIGHT, MIN_HEIGHT, set_helper_bib},
+    components::{
+        BibliographySelector, HelperComponent, Highlight, apply_journal_style, cite_command,
+        load_annotations,
+    },
+    views::{
+        HELPER_BIB, HELPER_WINDOW, MAX_HEIGHT, MIN_HEIGHT, get_helper_bib, get_helper_index,
+        recent_helper_references, record_usage, set_helper_bib, usage_boosts,
+    },
 };
 use arboard::Clipboard;
 use bibcitex_core::{
-    bib::{Reference, parse},
-    search_references,
-    utils::read_bibliography,
+    GbtStyle, Query, UsageAction, bib::Reference, format_gbt7714, format_gbt7714_list,
+    parse_jabref_groups, rank_references_boosted, split_shortcut, utils::read_bibliography_with,
 };
 use biblatex::EntryType;
 use dioxus::{desktop::use_window, prelude::*};
@@ -62,7 +67,7 @@ pub fn SearchInput(
             input {
                 class: "w-full h-full pl-12 pr-36 text-lg bg-transparent border-none! shadow-none! outline-none! ring-0! focus:border-none! focus:shadow-none! focus:outline-none! focus:ring-0! placeholder:text-base-content/30 text-base-content",
                 r#type: "text",
-                placeholder: if is_selecting_bib() { "选择文献库..." } else { "搜索文献、作者、标题..." },
+                placeholder: if is_selecting_bib() { "选择文献库..." } else { "搜索文献、作者、标题，如 author:knuth year:2010.." },
                 value: "{query}",
                 oninput: on_input,
                 onkeydown: on_keydown,
@@ -98,6 +103,105 @@ pub fn SearchInput(
     }
 }
 
+/// 拆分查询与定位符，`|` 之后的内容作为引用模板中的 `%p`
+fn split_locator(query: &str) -> (&str, Option<&str>) {
+    match query.split_once('|') {
+        Some((search, locator)) => (search.trim(), Some(locator.trim())),
+        None => (query, None),
+    }
+}
+
+/// 搜索当前文献库，以 `#分组名` 开头时在该智能分组的结果中搜索
+///
+/// 常引用和最近引用的文献排在前面，查询为空时显示最近引用的文献。
+fn search_helper(search: &str) -> Vec<Reference> {
+    if search.trim().is_empty() {
+        return recent_helper_references();
+    }
+    let boosts = get_helper_bib()
+        .map(|(name, _)| usage_boosts(&name))
+        .unwrap_or_default();
+    let boost = |r: &Reference| boosts.get(&r.cite_key).copied().unwrap_or_default();
+    let (shortcut, rest) = split_shortcut(search);
+    let group = shortcut.and_then(|shortcut| {
+        crate::STATE
+            .read()
+            .saved_search_by_shortcut(shortcut)
+            .cloned()
+    });
+    if let Some(group) = group {
+        let Some((name, refs)) = get_helper_bib() else {
+            return Vec::new();
+        };
+        // 只有分组限定了 JabRef 分组时才读取 .bib 文件中的分组树
+        let tree = group.jabref_group.as_ref().and_then(|_| {
+            let source = crate::STATE
+                .read()
+                .bibliographies
+                .get(&name)?
+                .read_source()
+                .ok()?;
+            parse_jabref_groups(&source.text)
+        });
+        let refs = group.run(&refs, Some(&name), tree.as_ref());
+        return if rest.is_empty() {
+            refs
+        } else {
+            rank_references_boosted(&refs, rest, boost)
+        };
+    }
+    get_helper_index()
+        .map(|index| {
+            index
+                .rank_references_boosted(search, boost)
+                .into_iter()
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// 生成一条引用多篇文献的引用命令，保持标记顺序并跳过重复项
+fn cite_marked(marked: &[Reference], locator: Option<&str>, bibliography: Option<&str>) -> String {
+    let keys = marked
+        .iter()
+        .map(|entry| entry.cite_key.as_str())
+        .unique()
+        .collect::<Vec<_>>();
+    cite_command(&keys, locator, bibliography)
+}
+
+/// 标记或取消标记一篇文献
+fn toggle_mark(mut marked: Signal<Vec<Reference>>, entry: &Reference) {
+    let mut marked = marked.write();
+    if let Some(index) = marked.iter().position(|e| e.cite_key == entry.cite_key) {
+        marked.remove(index);
+    } else {
+        marked.push(entry.clone());
+    }
+}
+
+/// 复制文本到剪贴板，关闭助手窗口并回到之前的窗口，并记录引用的文献
+///
+/// 复制失败时不关闭窗口，也不记录引用
+fn copy_and_close(
+    text: String,
+    keys: &[&str],
+    library: Option<&str>,
+    style: Option<GbtStyle>,
+) -> Result<(), String> {
+    Clipboard::new()
+        .and_then(|mut clipboard| clipboard.set_text(text))
+        .map_err(|e| format!("复制到剪贴板失败: {e}"))?;
+    let style = style.map(|style| style.to_string());
+    record_usage(keys, library, style.as_deref(), UsageAction::Paste);
+    let window = use_window();
+    window.close();
+    HELPER_WINDOW.write().take();
+    let _ = focus_previous_window();
+    Ok(())
+}
+
 // 搜索结果组件
 #[component]
 fn SearchResults(
@@ -105,6 +209,7 @@ fn SearchResults(
     result: Signal<Vec<Reference>>,
     keys: Memo<Vec<(String, EntryType)>>,
     selected_index: Signal<Option<usize>>,
+    marked: Signal<Vec<Reference>>,
     item_elements: Signal<std::collections::HashMap<usize, MountedEvent>>,
     scrollable_container: Signal<Option<MountedEvent>>,
     on_item_click: EventHandler<String>,
@@ -134,6 +239,9 @@ fn SearchResults(
                 class: "overflow-y-auto p-2 space-y-2 h-fit",
                 style: format!("scroll-behavior: smooth; max-height: {}px;", MAX_HEIGHT - MIN_HEIGHT),
                 onmounted: on_container_mounted,
+                if query().is_empty() {
+                    div { class: "px-4 pt-1 text-xs font-medium text-base-content/50", "最近引用" }
+                }
                 for (index , ((cite_key , kind) , bib)) in keys().into_iter().zip(result()).enumerate() {
                     div {
                         key: "{index}",
@@ -167,12 +275,24 @@ fn SearchResults(
                                 item_elements.write().insert(index, event);
                             }
                         },
-                        onclick: move |_| on_item_click.call(cite_key.clone()),
+                        onclick: {
+                            let cite_key = cite_key.clone();
+                            move |_| on_item_click.call(cite_key.clone())
+                        },
+                        if let Some(order) = marked().iter().position(|e| e.cite_key == cite_key) {
+                            div { class: "absolute right-3 top-3 badge badge-primary badge-sm", "✓ {order + 1}" }
+                        }
                         div { class: "py-3 px-3",
                             HelperComponent { entry: bib }
                         }
                     }
                 }
+                div { class: "px-4 pb-1 text-xs text-base-content/40 text-right",
+                    if !marked().is_empty() {
+                        span { class: "text-primary font-medium mr-2", "已选 {marked().len()} 篇" }
+                    }
+                    "#分组 搜索智能分组 · Tab 标记 · Enter 复制引用 · | 后输入页码 · Shift+Enter GB/T 7714 顺序编码 · Alt+Enter GB/T 7714 著者-出版年"
+                }
             }
         }
     }
@@ -193,7 +313,13 @@ pub fn Search() -> Element {
 
     let item_elements = use_signal(std::collections::HashMap::<usize, MountedEvent>::new);
     let mut selected_index = use_signal(|| None::<usize>);
+    let mut marked = use_signal(Vec::<Reference>::new);
+    // 方向键选择后，空格用于标记而不是输入
+    let mut navigating = use_signal(|| false);
     let mut bib_selected_index = use_signal(|| None::<usize>);
+    let highlight =
+        use_memo(move || Query::parse(split_shortcut(split_locator(&query()).0).1).ok());
+    use_context_provider(|| Highlight(highlight));
 
     // 获取文献库列表
     let bibs = use_memo(|| {
@@ -220,6 +346,15 @@ pub fn Search() -> Element {
         is_selecting_bib.set(!has_bib);
     });
 
+    // 切换文献库后，查询为空时显示最近引用的文献
+    use_effect(move || {
+        let _ = current_bib();
+        if query.peek().is_empty() {
+            result.set(search_helper(""));
+            selected_index.set(Some(0));
+        }
+    });
+
     let keys = use_memo(move || {
         result()
             .iter()
@@ -232,12 +367,12 @@ pub fn Search() -> Element {
         if !is_selecting_bib() {
             let new_query = e.value();
             query.set(new_query.clone());
+            navigating.set(false);
+            error_message.set(None);
 
-            if let Some((_, refs)) = current_bib() {
-                let filtered_refs = search_references(&refs, &new_query);
-                result.set(filtered_refs);
-                selected_index.set(Some(0));
-            }
+            let (search, _) = split_locator(&new_query);
+            result.set(search_helper(search));
+            selected_index.set(Some(0));
         }
     };
 
@@ -249,6 +384,7 @@ pub fn Search() -> Element {
             selected_index.set(None);
             bib_selected_index.set(Some(0)); // 默认选中第一项
             error_message.set(None);
+            marked.set(Vec::new());
 
             // 立即尝试设置焦点
             spawn(async move {
@@ -260,10 +396,14 @@ pub fn Search() -> Element {
         }
     };
 
-    let handle_bib_click = move |(bib_name, bib_path): (String, String)| {
-        match parse(&bib_path) {
+    let handle_bib_click = move |(bib_name, _): (String, String)| {
+        let parsed = crate::STATE.read().parse(&bib_name);
+        match parsed {
             Ok(parse_bib) => {
-                let refs = read_bibliography(parse_bib);
+                let mut refs =
+                    read_bibliography_with(parse_bib, &crate::STATE.read().tag_separators);
+                load_annotations(&bib_name, &mut refs);
+                apply_journal_style(&bib_name, &mut refs);
                 set_helper_bib(Some((bib_name, refs)));
                 is_selecting_bib.set(false);
                 error_message.set(None);
@@ -283,13 +423,24 @@ pub fn Search() -> Element {
     };
 
     let handle_item_click = move |cite_key: String| {
-        HELPER_WINDOW.write().take();
-        let mut clipboard = Clipboard::new().unwrap();
-        clipboard.set_text(cite_key).unwrap();
-        let window = use_window();
-        window.close();
-        HELPER_WINDOW.write().take();
-        let _ = focus_previous_window();
+        // 已有标记时，点击用于标记或取消标记
+        if !marked().is_empty() {
+            if let Some(entry) = result().iter().find(|e| e.cite_key == cite_key) {
+                toggle_mark(marked, entry);
+            }
+            return;
+        }
+        let query = query();
+        let (_, locator) = split_locator(&query);
+        let bib_name = current_bib().map(|(name, _)| name);
+        if let Err(e) = copy_and_close(
+            cite_command(&[&cite_key], locator, bib_name.as_deref()),
+            &[cite_key.as_str()],
+            bib_name.as_deref(),
+            None,
+        ) {
+            error_message.set(Some(e));
+        }
     };
 
     let handle_container_mounted = move |event: MountedEvent| {
@@ -298,12 +449,7 @@ pub fn Search() -> Element {
 
     let _search = move |e: Event<FormData>| {
         query.set(e.value());
-        if let Some(bib) = current_bib() {
-            let res = search_references(&bib.1, &query());
-            result.set(res);
-        } else {
-            result.set(Vec::new());
-        }
+        result.set(search_helper(split_locator(&query()).0));
         // 重置选中索引
         selected_index.set(None);
     };
@@ -319,11 +465,17 @@ pub fn Search() -> Element {
                 match evt.key() {
                     Key::Enter => {
                         if let Some(index) = bib_selected_index() {
-                            let (name, path, _, _, _) = &bib_list[index];
-                            // 解析bib
-                            match parse(path) {
+                            let (name, _, _, _, _) = &bib_list[index];
+                            // 按文献库设置的编码解析bib
+                            let parsed = crate::STATE.read().parse(name);
+                            match parsed {
                                 Ok(parsed_bib) => {
-                                    let refs = read_bibliography(parsed_bib);
+                                    let mut refs = read_bibliography_with(
+                                        parsed_bib,
+                                        &crate::STATE.read().tag_separators,
+                                    );
+                                    load_annotations(name, &mut refs);
+                                    apply_journal_style(name, &mut refs);
                                     set_helper_bib(Some((name.clone(), refs)));
                                     is_selecting_bib.set(false);
                                     error_message.set(None);
@@ -356,24 +508,89 @@ pub fn Search() -> Element {
                     _ => {}
                 }
             }
-        } else if !query().is_empty() {
-            // 搜索模式
+        } else if !query().is_empty() || !result().is_empty() {
+            // 搜索模式，查询为空时在最近引用中选择
             match evt.key() {
+                Key::Tab => {
+                    evt.prevent_default(); // 阻止焦点切换
+                    if let Some(index) = selected_index()
+                        && let Some(entry) = result().get(index)
+                    {
+                        toggle_mark(marked, entry);
+                    }
+                }
+                Key::Character(c) if c == " " && navigating() => {
+                    evt.prevent_default(); // 阻止输入空格
+                    if let Some(index) = selected_index()
+                        && let Some(entry) = result().get(index)
+                    {
+                        toggle_mark(marked, entry);
+                    }
+                }
+                Key::Enter if !marked().is_empty() => {
+                    let entries = marked();
+                    let modifiers = evt.modifiers();
+                    let bib_name = current_bib().map(|(name, _)| name);
+                    let style = if modifiers.contains(Modifiers::SHIFT) {
+                        Some(GbtStyle::Numeric)
+                    } else if modifiers.contains(Modifiers::ALT) {
+                        Some(GbtStyle::AuthorYear)
+                    } else {
+                        None
+                    };
+                    let text = match style {
+                        Some(style) => format_gbt7714_list(&entries, style).join("\n"),
+                        None => {
+                            let query = query();
+                            let (_, locator) = split_locator(&query);
+                            cite_marked(&entries, locator, bib_name.as_deref())
+                        }
+                    };
+                    let keys = entries
+                        .iter()
+                        .map(|entry| entry.cite_key.as_str())
+                        .unique()
+                        .collect::<Vec<_>>();
+                    if let Err(e) = copy_and_close(text, &keys, bib_name.as_deref(), style) {
+                        error_message.set(Some(e));
+                    }
+                }
                 Key::Enter => {
-                    // TODO: 错误处理
-                    if let Some(index) = selected_index() {
-                        let text = result()[index].cite_key.clone();
-                        let mut clipboard = Clipboard::new().unwrap();
-                        clipboard.set_text(text.to_string()).unwrap();
-
-                        let window = use_window();
-                        window.close();
-                        HELPER_WINDOW.write().take();
-                        let _ = focus_previous_window();
+                    // 结果为空或已变化时选中项可能越界
+                    if let Some(index) = selected_index()
+                        && let Some(entry) = result().get(index)
+                    {
+                        // Shift+Enter: GB/T 7714 顺序编码, Alt+Enter: GB/T 7714 著者-出版年
+                        let modifiers = evt.modifiers();
+                        let bib_name = current_bib().map(|(name, _)| name);
+                        let style = if modifiers.contains(Modifiers::SHIFT) {
+                            Some(GbtStyle::Numeric)
+                        } else if modifiers.contains(Modifiers::ALT) {
+                            Some(GbtStyle::AuthorYear)
+                        } else {
+                            None
+                        };
+                        let text = match style {
+                            Some(style) => format_gbt7714(entry, style),
+                            None => {
+                                let query = query();
+                                let (_, locator) = split_locator(&query);
+                                cite_command(&[&entry.cite_key], locator, bib_name.as_deref())
+                            }
+                        };
+                        if let Err(e) = copy_and_close(
+                            text,
+                            &[entry.cite_key.as_str()],
+                            bib_name.as_deref(),
+                            style,
+                        ) {
+                            error_message.set(Some(e));
+                        }
                     }
                 }
                 Key::ArrowDown => {
                     evt.prevent_default(); // 阻止默认行为，防止光标移动
+                    navigating.set(true);
                     if let Some(index) = selected_index() {
                         let update_index = (index + 1).min(max_index());
                         selected_index.set(Some(update_index));
@@ -383,6 +600,7 @@ pub fn Search() -> Element {
                 }
                 Key::ArrowUp => {
                     evt.prevent_default(); // 阻止默认行为，防止光标移动
+                    navigating.set(true);
                     if let Some(index) = selected_index() {
                         let update_index = if index > 0 { index - 1 } else { 0 };
                         selected_index.set(Some(update_index));
@@ -547,18 +765,27 @@ pub fn Search() -> Element {
                         on_bib_click: handle_bib_click,
                         error_message,
                     }
-                } else if !query().is_empty() {
+                } else if !query().is_empty() || !result().is_empty() {
                     SearchResults {
                         query,
                         result,
                         keys,
                         selected_index,
+                        marked,
                         item_elements,
                         scrollable_container,
                         on_item_click: handle_item_click,
                         on_container_mounted: handle_container_mounted,
                     }
                 }
+                // 文献库选择模式下由选择器显示错误，如复制失败
+                if !is_selecting_bib() {
+                    if let Some(error) = error_message() {
+                        div { class: "alert alert-error shadow-lg m-2",
+                            span { "{error}" }
+                        }
+                    }
+                }
             }
         }
     }
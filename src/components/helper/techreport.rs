@@ -1,7 +1,7 @@
-use bibcitex_core::bib::Reference;
+use bibcitex_core::{QueryField, bib::Reference};
 use dioxus::prelude::*;
 
-use crate::components::ChunksComp;
+use crate::components::{ChunksComp, Highlighted};
 
 #[component]
 pub fn TechReportHelper(entry: Reference) -> Element {
@@ -16,7 +16,11 @@ pub fn TechReportHelper(entry: Reference) -> Element {
                     }
                     if let Some(title) = entry.title {
                         span { class: "text-gray-900 dark:text-gray-100 font-serif",
-                            ChunksComp { chunks: title, cite_key: key.clone() }
+                            ChunksComp {
+                                chunks: title,
+                                cite_key: key.clone(),
+                                field: QueryField::Title,
+                            }
                         }
                     } else {
                         span { class: "text-gray-900 dark:text-gray-100 font-serif",
@@ -35,7 +39,7 @@ pub fn TechReportHelper(entry: Reference) -> Element {
                     if authors.len() > 3 {
                         for author in authors.iter().take(3) {
                             span { class: "badge badge-outline text-blue-700 dark:text-blue-300 font-semibold mr-2",
-                                "{author} "
+                                Highlighted { text: author.clone(), field: QueryField::Author }
                             }
                         }
                         span { class: "badge badge-outline text-blue-700 dark:text-blue-300 font-semibold mr-2",
@@ -44,7 +48,7 @@ pub fn TechReportHelper(entry: Reference) -> Element {
                     } else {
                         for author in authors {
                             span { class: "badge badge-outline text-blue-700 dark:text-blue-300 font-semibold mr-2",
-                                "{author} "
+                                Highlighted { text: author.clone(), field: QueryField::Author }
                             }
                         }
                     }
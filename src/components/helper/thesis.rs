@@ -1,8 +1,8 @@
-use bibcitex_core::bib::Reference;
+use bibcitex_core::{QueryField, bib::Reference};
 use biblatex::EntryType;
 use dioxus::prelude::*;
 
-use crate::components::ChunksComp;
+use crate::components::{ChunksComp, Highlighted};
 
 #[component]
 pub fn ThesisHelper(entry: Reference) -> Element {
@@ -34,7 +34,11 @@ pub fn ThesisHelper(entry: Reference) -> Element {
                     }
                     if let Some(title) = entry.title {
                         span { class: "text-gray-900 dark:text-gray-100 font-serif",
-                            ChunksComp { chunks: title, cite_key: key.clone() }
+                            ChunksComp {
+                                chunks: title,
+                                cite_key: key.clone(),
+                                field: QueryField::Title,
+                            }
                         }
                     } else {
                         span { class: "text-gray-900 dark:text-gray-100 font-serif",
@@ -53,7 +57,7 @@ pub fn ThesisHelper(entry: Reference) -> Element {
                     if authors.len() > 3 {
                         for author in authors.iter().take(3) {
                             span { class: "badge badge-outline text-blue-700 dark:text-blue-300 font-semibold mr-2",
-                                "{author} "
+                                Highlighted { text: author.clone(), field: QueryField::Author }
                             }
                         }
                         span { class: "badge badge-outline text-blue-700 dark:text-blue-300 font-semibold mr-2",
@@ -62,7 +66,7 @@ pub fn ThesisHelper(entry: Reference) -> Element {
                     } else {
                         for author in authors {
                             span { class: "badge badge-outline text-blue-700 dark:text-blue-300 font-semibold mr-2",
-                                "{author} "
+                                Highlighted { text: author.clone(), field: QueryField::Author }
                             }
                         }
                     }
@@ -1,5 +1,5 @@
-use crate::components::ChunksComp;
-use bibcitex_core::bib::Reference;
+use crate::components::{ChunksComp, Highlighted};
+use bibcitex_core::{QueryField, bib::Reference};
 use dioxus::prelude::*;
 
 #[component]
@@ -14,7 +14,11 @@ pub fn UnimplementedHelper(entry: Reference) -> Element {
                     }
                     if let Some(title) = entry.title {
                         span { class: "text-gray-900 dark:text-gray-100 font-serif font-medium",
-                            ChunksComp { chunks: title, cite_key: key.clone() }
+                            ChunksComp {
+                                chunks: title,
+                                cite_key: key.clone(),
+                                field: QueryField::Title,
+                            }
                         }
                     } else {
                         span { class: "text-gray-900 dark:text-gray-100 font-serif italic",
@@ -31,7 +35,7 @@ pub fn UnimplementedHelper(entry: Reference) -> Element {
                     if authors.len() > 3 {
                         for author in authors.iter().take(3) {
                             span { class: "badge badge-ghost badge-xs hover:badge-neutral transition-colors cursor-default",
-                                "{author}"
+                                Highlighted { text: author.clone(), field: QueryField::Author }
                             }
                         }
                         span { class: "badge badge-ghost badge-xs hover:badge-neutral transition-colors cursor-default",
@@ -40,7 +44,7 @@ pub fn UnimplementedHelper(entry: Reference) -> Element {
                     } else {
                         for author in authors {
                             span { class: "badge badge-ghost badge-xs hover:badge-neutral transition-colors cursor-default",
-                                "{author}"
+                                Highlighted { text: author.clone(), field: QueryField::Author }
                             }
                         }
                     }
@@ -0,0 +1,312 @@
+use crate::{
+    CURRENT_BIB, DELETE_ICON, ERR_ICON, STATE,
+    components::{current_bib_file, reload_current_bib},
+};
+use bibcitex_core::{
+    JournalAbbreviation, JournalReport, JournalStyle, bib::Reference, fill_journals_in_file,
+    journal_report, utils::read_bibliography_with,
+};
+use dioxus::prelude::*;
+
+/// 期刊名输出方式的中文名称
+pub fn journal_style_name(style: JournalStyle) -> &'static str {
+    match style {
+        JournalStyle::Full => "期刊全称",
+        JournalStyle::Abbreviated => "期刊缩写",
+    }
+}
+
+/// 按文献库的设置将期刊名替换为全称或缩写，不修改 .bib 文件
+pub(crate) fn apply_journal_style(library: &str, refs: &mut [Reference]) {
+    let state = STATE.read();
+    if let Some(style) = state
+        .bibliographies
+        .get(library)
+        .and_then(|info| info.journal_style)
+    {
+        bibcitex_core::apply_journal_style(refs, &state.journal_database(), style);
+    }
+}
+
+/// 补全文献库的 fjournal 和期刊缩写，写回 .bib 文件
+fn fill_library(name: &str) -> Result<usize, String> {
+    let (path, encoding, database) = {
+        let state = STATE.read();
+        let info = state
+            .bibliographies
+            .get(name)
+            .ok_or(format!("文献库 {name} 不存在"))?;
+        let encoding = info.encoding().map_err(|e| e.to_string())?;
+        (info.path.clone(), encoding, state.journal_database())
+    };
+    let changed = fill_journals_in_file(&path, encoding, &database).map_err(|e| e.to_string())?;
+    if changed > 0 && CURRENT_BIB().as_deref() == Some(name) {
+        let (_, _, separators) = current_bib_file()?;
+        reload_current_bib(&separators)?;
+    }
+    Ok(changed)
+}
+
+/// 检查文献库中期刊名的一致性
+fn check_library(name: &str) -> Result<JournalReport, String> {
+    let state = STATE.read();
+    let info = state
+        .bibliographies
+        .get(name)
+        .ok_or(format!("文献库 {name} 不存在"))?;
+    let bib = info.parse().map_err(|e| e.to_string())?;
+    let refs = read_bibliography_with(bib, &state.tag_separators);
+    Ok(journal_report(&refs, &state.journal_database()))
+}
+
+/// 期刊缩写管理组件：自定义缩写、补全 fjournal 和缩写、检查期刊名的一致性
+#[component]
+pub fn JournalAbbreviations(mut show: Signal<bool>) -> Element {
+    let abbreviations = use_memo(|| STATE.read().journal_abbreviations.clone());
+    let libraries = use_memo(|| {
+        STATE
+            .read()
+            .bibliographies
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>()
+    });
+    let mut full = use_signal(String::new);
+    let mut abbreviation = use_signal(String::new);
+    let mut lookup = use_signal(String::new);
+    let mut library = use_signal(|| CURRENT_BIB().unwrap_or_default());
+    let mut message = use_signal(|| None::<String>);
+    let mut error_message = use_signal(|| None::<String>);
+    let mut report = use_signal(|| None::<JournalReport>);
+    let save_available =
+        use_memo(move || !full().trim().is_empty() && !abbreviation().trim().is_empty());
+    let found = use_memo(move || {
+        let name = lookup();
+        (!name.trim().is_empty())
+            .then(|| STATE.read().journal_database().lookup(&name).cloned())
+            .flatten()
+    });
+
+    let close_modal = move |_| {
+        show.set(false);
+    };
+
+    let save = move |_| {
+        let mut state = STATE.write();
+        state.add_update_journal_abbreviation(JournalAbbreviation::new(&full(), &abbreviation()));
+        if let Err(e) = state.update_file() {
+            error_message.set(Some(e.to_string()));
+        } else {
+            error_message.set(None);
+            full.set(String::new());
+            abbreviation.set(String::new());
+        }
+    };
+
+    let mut remove = move |full: String| {
+        let mut state = STATE.write();
+        state.remove_journal_abbreviation(&full);
+        if let Err(e) = state.update_file() {
+            error_message.set(Some(e.to_string()));
+        }
+    };
+
+    let fill = move |_| {
+        report.set(None);
+        match fill_library(&library()) {
+            Ok(changed) => {
+                error_message.set(None);
+                message.set(Some(format!("已补全 {changed} 条文献的期刊名")));
+            }
+            Err(e) => error_message.set(Some(e)),
+        }
+    };
+
+    let check = move |_| {
+        message.set(None);
+        match check_library(&library()) {
+            Ok(result) => {
+                error_message.set(None);
+                report.set(Some(result));
+            }
+            Err(e) => error_message.set(Some(e)),
+        }
+    };
+
+    rsx! {
+        div { class: if show() { "modal modal-open backdrop-blur-sm" } else { "modal" },
+            div { class: "modal-box w-2/3 max-w-3xl glass-panel shadow-2xl",
+                h3 { class: "text-2xl font-bold mb-2 gradient-text", "期刊缩写" }
+                p { class: "text-sm text-base-content/60 mb-6",
+                    "内置 ISO 4 (MathSciNet) 期刊缩写，自定义缩写优先。journal 保存缩写，fjournal 保存全称"
+                }
+
+                input {
+                    class: "input input-bordered input-sm w-full mb-2",
+                    placeholder: "输入期刊全称或缩写查询",
+                    value: "{lookup}",
+                    oninput: move |e| lookup.set(e.value()),
+                }
+                if !lookup().trim().is_empty() {
+                    match found() {
+                        Some(journal) => rsx! {
+                            p { class: "text-sm mb-6",
+                                "{journal.full}"
+                                span { class: "mx-2 text-base-content/40", "↔" }
+                                span { class: "font-mono", "{journal.abbreviation}" }
+                            }
+                        },
+                        None => rsx! {
+                            p { class: "text-sm text-base-content/50 mb-6", "未找到该期刊" }
+                        },
+                    }
+                }
+
+                if !abbreviations().is_empty() {
+                    table { class: "table table-sm mb-4",
+                        thead {
+                            tr {
+                                th { "全称" }
+                                th { "缩写" }
+                                th {}
+                            }
+                        }
+                        tbody {
+                            for journal in abbreviations() {
+                                tr { key: "{journal.full}",
+                                    td { "{journal.full}" }
+                                    td { class: "font-mono", "{journal.abbreviation}" }
+                                    td {
+                                        button {
+                                            class: "btn btn-ghost btn-xs btn-circle",
+                                            onclick: {
+                                                let full = journal.full.clone();
+                                                move |_| remove(full.clone())
+                                            },
+                                            img { width: 14, src: DELETE_ICON }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+                div { class: "join w-full mb-6",
+                    input {
+                        class: "input input-bordered input-sm join-item grow",
+                        placeholder: "期刊全称",
+                        value: "{full}",
+                        oninput: move |e| full.set(e.value()),
+                    }
+                    input {
+                        class: "input input-bordered input-sm join-item w-1/3 font-mono",
+                        placeholder: "缩写",
+                        value: "{abbreviation}",
+                        oninput: move |e| abbreviation.set(e.value()),
+                    }
+                    button {
+                        class: "btn btn-primary btn-sm join-item",
+                        disabled: !save_available(),
+                        onclick: save,
+                        "保存"
+                    }
+                }
+
+                div { class: "flex items-center gap-2",
+                    select {
+                        class: "select select-sm grow",
+                        onchange: move |e| {
+                            library.set(e.value());
+                            report.set(None);
+                            message.set(None);
+                        },
+                        option { value: "", selected: library().is_empty(), disabled: true, "选择文献库" }
+                        for name in libraries() {
+                            option { value: "{name}", selected: library() == name, "{name}" }
+                        }
+                    }
+                    button {
+                        class: "btn btn-sm btn-outline",
+                        disabled: library().is_empty(),
+                        onclick: fill,
+                        "补全 fjournal 和缩写"
+                    }
+                    button {
+                        class: "btn btn-sm btn-outline",
+                        disabled: library().is_empty(),
+                        onclick: check,
+                        "检查期刊名"
+                    }
+                }
+                if let Some(text) = message() {
+                    p { class: "text-sm text-success mt-2", "{text}" }
+                }
+                if let Some(report) = report() {
+                    JournalReportView { report }
+                }
+
+                if let Some(error) = error_message() {
+                    div {
+                        role: "alert",
+                        class: "alert alert-error mt-4 shadow-sm",
+                        img { width: 20, src: ERR_ICON }
+                        span { "{error}" }
+                    }
+                }
+
+                div { class: "modal-action mt-8",
+                    button {
+                        class: "btn btn-ghost hover:bg-base-content/10",
+                        onclick: close_modal,
+                        "关闭"
+                    }
+                }
+            }
+            div { class: "modal-backdrop bg-base-300/30", onclick: close_modal }
+        }
+    }
+}
+
+#[component]
+fn JournalReportView(report: JournalReport) -> Element {
+    rsx! {
+        div { class: "mt-4 flex flex-col gap-3 text-sm max-h-80 overflow-y-auto",
+            if report.is_empty() {
+                p { class: "text-success", "期刊名一致" }
+            }
+            for variants in report.variants.iter() {
+                div { key: "{variants.journal.full}",
+                    p { class: "font-semibold", "{variants.journal.full} 有 {variants.spellings.len()} 种写法" }
+                    for (spelling , keys) in variants.spellings.iter() {
+                        p { class: "pl-4 text-base-content/70",
+                            span { class: "font-mono", "{spelling}" }
+                            span { class: "text-xs text-base-content/50 ml-2", "{keys.join(\", \")}" }
+                        }
+                    }
+                }
+            }
+            if !report.mismatches.is_empty() {
+                div {
+                    p { class: "font-semibold", "journal 与 fjournal 不是同一期刊" }
+                    for mismatch in report.mismatches.iter() {
+                        p { class: "pl-4 text-base-content/70",
+                            code { "{mismatch.key}" }
+                            ": {mismatch.journal} / {mismatch.full_journal}"
+                        }
+                    }
+                }
+            }
+            if !report.unknown.is_empty() {
+                div {
+                    p { class: "font-semibold text-base-content/60", "未收录的期刊，可在上方添加缩写" }
+                    for (name , keys) in report.unknown.iter() {
+                        p { class: "pl-4 text-base-content/50",
+                            "{name}"
+                            span { class: "text-xs ml-2", "({keys.len()})" }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
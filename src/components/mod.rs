@@ -5,9 +5,15 @@ pub mod math;
 pub use math::*;
 pub mod bibliography;
 pub use bibliography::*;
+pub mod citation;
+pub use citation::*;
+pub mod journal;
+pub use journal::*;
 pub mod reference;
 pub use reference::*;
 pub mod helper;
 pub use helper::*;
+pub mod tidy;
+pub use tidy::*;
 pub mod updater;
 pub use updater::*;
@@ -1,8 +1,9 @@
 use crate::{
-    COPY_ICON, DETAILS_ICON, DRAWER_OPEN, DRAWER_REFERENCE, ERR_ICON, OK_ICON,
-    components::ChunksComp,
+    COPY_ICON, CURRENT_BIB, DETAILS_ICON, DRAWER_OPEN, DRAWER_REFERENCE, ERR_ICON, OK_ICON,
+    components::{ChunksComp, Highlighted, TagBadges, cite_command},
+    views::record_usage,
 };
-use bibcitex_core::bib::Reference;
+use bibcitex_core::{QueryField, UsageAction, bib::Reference};
 use dioxus::prelude::*;
 
 #[component]
@@ -16,7 +17,16 @@ pub fn Article(entry: Reference) -> Element {
         move |_| {
             copied.set(true);
             if let Ok(mut clipboard) = arboard::Clipboard::new() {
-                if clipboard.set_text(&key_clone).is_ok() {
+                if clipboard
+                    .set_text(cite_command(&[&key_clone], None, CURRENT_BIB().as_deref()))
+                    .is_ok()
+                {
+                    record_usage(
+                        &[&key_clone],
+                        CURRENT_BIB().as_deref(),
+                        None,
+                        UsageAction::Copy,
+                    );
                     copy_success.set(true);
                 } else {
                     copy_success.set(false);
@@ -59,7 +69,11 @@ pub fn Article(entry: Reference) -> Element {
                         }
                         if let Some(title) = entry.title {
                             h3 { class: "text-xl font-bold leading-snug gradient-text",
-                                ChunksComp { chunks: title, cite_key: key.clone() }
+                                ChunksComp {
+                                    chunks: title,
+                                    cite_key: key.clone(),
+                                    field: QueryField::Title,
+                                }
                             }
                         } else {
                             span { class: "text-lg text-base-content/50 italic", "No title available" }
@@ -69,7 +83,7 @@ pub fn Article(entry: Reference) -> Element {
                     div { class: "flex items-center gap-1 opacity-0 group-hover:opacity-100 transition-opacity duration-200",
                         button {
                             class: "btn btn-ghost btn-sm btn-circle tooltip tooltip-left",
-                            "data-tip": "Copy Key",
+                            "data-tip": "Copy Citation",
                             onclick: copy_key,
                             if !copied() {
                                 img {
@@ -111,7 +125,7 @@ pub fn Article(entry: Reference) -> Element {
                     if let Some(authors) = entry.author {
                         for author in authors {
                             span { class: "badge badge-ghost hover:badge-info transition-colors cursor-default bg-base-200/50",
-                                "{author}"
+                                Highlighted { text: author.clone(), field: QueryField::Author }
                             }
                         }
                     } else {
@@ -119,6 +133,9 @@ pub fn Article(entry: Reference) -> Element {
                     }
                 }
 
+                // Tags
+                TagBadges { cite_key: key.clone(), tags: entry.keywords.clone() }
+
                 // Metadata Row
                 div { class: "mt-4 flex flex-wrap items-center gap-4 text-sm text-base-content/70 border-t border-base-content/5 pt-3",
                     if let Some(journal) = &entry.journal {
@@ -215,6 +232,7 @@ pub fn ArticleDrawer(entry: Reference) -> Element {
                                         ChunksComp {
                                             chunks: title,
                                             cite_key: format!("ArticleDrawer-{key}"),
+                                            field: QueryField::Title,
                                         }
                                     }
                                 } else {
@@ -352,6 +370,7 @@ pub fn ArticleDrawer(entry: Reference) -> Element {
                         ChunksComp {
                             chunks: abstract_chunks,
                             cite_key: format!("{key}-abstract"),
+                            field: QueryField::Abstract,
                         }
                     }
                 }
@@ -361,7 +380,11 @@ pub fn ArticleDrawer(entry: Reference) -> Element {
                 div { class: "collapse-title font-medium", "Note" }
                 div { class: "collapse-content",
                     if let Some(note) = entry.note {
-                        ChunksComp { chunks: note, cite_key: format!("{key}-note") }
+                        ChunksComp {
+                            chunks: note,
+                            cite_key: format!("{key}-note"),
+                            field: QueryField::Note,
+                        }
                     }
                 }
             }
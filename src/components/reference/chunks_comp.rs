@@ -1,19 +1,53 @@
 use crate::components::InlineMath;
+use bibcitex_core::{Query, QueryField};
 use biblatex::Chunk;
 use dioxus::prelude::*;
 
+/// 当前搜索的查询，用于高亮匹配的文本
+#[derive(Clone, Copy)]
+pub struct Highlight(pub Memo<Option<Query>>);
+
+/// 高亮搜索匹配的文本，没有 [`Highlight`] 上下文时原样显示
+#[component]
+pub fn Highlighted(text: String, field: QueryField) -> Element {
+    let spans = try_use_context::<Highlight>()
+        .and_then(|highlight| highlight.0())
+        .map(|query| query.spans(field, &text))
+        .unwrap_or_default();
+    let mut pieces = Vec::with_capacity(spans.len() * 2 + 1);
+    let mut last = 0;
+    for span in spans {
+        pieces.push((text[last..span.start].to_string(), false));
+        pieces.push((text[span.clone()].to_string(), true));
+        last = span.end;
+    }
+    pieces.push((text[last..].to_string(), false));
+    rsx! {
+        for (piece , matched) in pieces.into_iter().filter(|(piece, _)| !piece.is_empty()) {
+            if matched {
+                mark { class: "bg-warning/40 text-inherit rounded-sm", "{piece}" }
+            } else {
+                span { "{piece}" }
+            }
+        }
+    }
+}
+
 #[component]
-pub fn ChunksComp(chunks: Vec<Chunk>, cite_key: String) -> Element {
+pub fn ChunksComp(chunks: Vec<Chunk>, cite_key: String, field: Option<QueryField>) -> Element {
     rsx! {
         for (i , chunk) in chunks.into_iter().enumerate() {
-            match chunk {
-                Chunk::Normal(txt) => rsx! {
+            match (chunk, field) {
+                (Chunk::Normal(txt) | Chunk::Verbatim(txt), Some(field)) => rsx! {
+                    Highlighted { key: "{cite_key}-{i}", text: txt, field }
+                },
+                (Chunk::Normal(txt), None) => rsx! {
                     span { key: "{cite_key}-{i}", "{txt}" }
                 },
-                Chunk::Verbatim(txt) => rsx! {
+                (Chunk::Verbatim(txt), None) => rsx! {
                     span { key: "{cite_key}-{i}", "{txt}" }
                 },
-                Chunk::Math(txt) => rsx! {
+                (Chunk::Math(txt), _) => rsx! {
                     InlineMath { key: "{cite_key}-{i}", content: txt }
                 },
             }
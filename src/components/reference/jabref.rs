@@ -0,0 +1,141 @@
+use crate::{
+    CURRENT_REF,
+    components::{Selection, current_bib_file, reload_current_bib},
+};
+use bibcitex_core::{JabRefGroup, edit_group_members_in_file};
+use dioxus::prelude::*;
+use std::collections::HashMap;
+
+/// 当前打开的 JabRef 分组名
+#[derive(Clone, Copy)]
+pub struct ActiveJabRefGroup(pub Signal<Option<String>>);
+
+/// 将选中的文献加入或移出 JabRef 静态分组，写回 .bib 文件的 `groups` 字段
+fn edit_members(keys: &[String], group: &str, add: bool) -> Result<usize, String> {
+    let (path, encoding, separators) = current_bib_file()?;
+    let keys = keys.iter().map(String::as_str).collect::<Vec<_>>();
+    let changed = edit_group_members_in_file(&path, encoding, &keys, group, add)
+        .map_err(|e| e.to_string())?;
+    if changed > 0 {
+        reload_current_bib(&separators)?;
+    }
+    Ok(changed)
+}
+
+/// JabRef 分组树，没有分组时不显示
+#[component]
+pub fn JabRefGroups(tree: Memo<Option<JabRefGroup>>) -> Element {
+    let error_message = use_signal(|| None::<String>);
+    // 一次遍历算出所有分组的文献数
+    let counts = use_memo(move || {
+        tree()
+            .map(|root| root.member_counts(&CURRENT_REF().unwrap_or_default()))
+            .unwrap_or_default()
+    });
+    let Some(root) = tree() else {
+        return rsx! {};
+    };
+    rsx! {
+        div { class: "flex flex-col gap-1",
+            h3 { class: "font-semibold", "JabRef 分组" }
+            ul { class: "menu menu-sm p-0",
+                GroupNode { group: root, counts, error_message }
+            }
+            if let Some(error) = error_message() {
+                p { class: "text-xs text-error", "{error}" }
+            }
+        }
+    }
+}
+
+#[component]
+fn GroupNode(
+    group: JabRefGroup,
+    counts: Memo<HashMap<String, usize>>,
+    error_message: Signal<Option<String>>,
+) -> Element {
+    let mut active = use_context::<ActiveJabRefGroup>().0;
+    let selected = use_context::<Selection>().0;
+    let mut expanded = use_signal(|| group.expanded);
+    let count = counts.read().get(&group.name).copied().unwrap_or_default();
+    let is_active = active().as_deref() == Some(group.name.as_str());
+    let name = group.name.clone();
+
+    let mut edit = move |name: String, add: bool| match edit_members(&selected(), &name, add) {
+        Ok(_) => error_message.set(None),
+        Err(e) => error_message.set(Some(e)),
+    };
+
+    rsx! {
+        li {
+            div {
+                class: if is_active { "flex justify-between menu-active" } else { "flex justify-between" },
+                onclick: {
+                    let name = name.clone();
+                    move |_| {
+                        if active().as_deref() == Some(name.as_str()) {
+                            active.set(None);
+                        } else {
+                            active.set(Some(name.clone()));
+                        }
+                    }
+                },
+                span { class: "flex items-center gap-1 truncate",
+                    if !group.children.is_empty() {
+                        button {
+                            class: "opacity-60 w-3",
+                            onclick: move |e: Event<MouseData>| {
+                                e.stop_propagation();
+                                expanded.toggle();
+                            },
+                            if expanded() { "▾" } else { "▸" }
+                        }
+                    }
+                    span { class: "truncate", "{group.name}" }
+                }
+                span { class: "flex items-center gap-1",
+                    // 仅静态分组可以编辑成员
+                    if group.is_explicit() && !selected().is_empty() {
+                        button {
+                            class: "opacity-40 hover:opacity-100",
+                            title: "将选中的文献加入分组",
+                            onclick: {
+                                let name = name.clone();
+                                move |e: Event<MouseData>| {
+                                    e.stop_propagation();
+                                    edit(name.clone(), true);
+                                }
+                            },
+                            "+"
+                        }
+                        button {
+                            class: "opacity-40 hover:opacity-100",
+                            title: "将选中的文献移出分组",
+                            onclick: {
+                                let name = name.clone();
+                                move |e: Event<MouseData>| {
+                                    e.stop_propagation();
+                                    edit(name.clone(), false);
+                                }
+                            },
+                            "−"
+                        }
+                    }
+                    span { class: "badge badge-xs badge-ghost", "{count}" }
+                }
+            }
+            if expanded() && !group.children.is_empty() {
+                ul {
+                    for child in group.children.iter().cloned() {
+                        GroupNode {
+                            key: "{child.name}",
+                            group: child,
+                            counts,
+                            error_message,
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,8 +1,9 @@
 use crate::{
-    COPY_ICON, DETAILS_ICON, DRAWER_OPEN, DRAWER_REFERENCE, ERR_ICON, OK_ICON,
-    components::ChunksComp,
+    COPY_ICON, CURRENT_BIB, DETAILS_ICON, DRAWER_OPEN, DRAWER_REFERENCE, ERR_ICON, OK_ICON,
+    components::{ChunksComp, Highlighted, TagBadges, cite_command},
+    views::record_usage,
 };
-use bibcitex_core::bib::Reference;
+use bibcitex_core::{QueryField, UsageAction, bib::Reference};
 use dioxus::prelude::*;
 
 /// ArXiv reference component.
@@ -31,7 +32,16 @@ pub fn ArXiv(entry: Reference) -> Element {
         move |_| {
             copied.set(true);
             if let Ok(mut clipboard) = arboard::Clipboard::new() {
-                if clipboard.set_text(&key_clone).is_ok() {
+                if clipboard
+                    .set_text(cite_command(&[&key_clone], None, CURRENT_BIB().as_deref()))
+                    .is_ok()
+                {
+                    record_usage(
+                        &[&key_clone],
+                        CURRENT_BIB().as_deref(),
+                        None,
+                        UsageAction::Copy,
+                    );
                     copy_success.set(true);
                 } else {
                     copy_success.set(false);
@@ -82,7 +92,11 @@ pub fn ArXiv(entry: Reference) -> Element {
                         }
                         if let Some(title) = entry.title {
                             h3 { class: "text-xl font-bold leading-snug gradient-text",
-                                ChunksComp { chunks: title, cite_key: key.clone() }
+                                ChunksComp {
+                                    chunks: title,
+                                    cite_key: key.clone(),
+                                    field: QueryField::Title,
+                                }
                             }
                         } else {
                             span { class: "text-lg text-base-content/50 italic", "No title available" }
@@ -92,7 +106,7 @@ pub fn ArXiv(entry: Reference) -> Element {
                     div { class: "flex items-center gap-1 opacity-0 group-hover:opacity-100 transition-opacity duration-200",
                         button {
                             class: "btn btn-ghost btn-sm btn-circle tooltip tooltip-left",
-                            "data-tip": "Copy Key",
+                            "data-tip": "Copy Citation",
                             onclick: copy_key,
                             if !copied() {
                                 img {
@@ -134,7 +148,7 @@ pub fn ArXiv(entry: Reference) -> Element {
                     if let Some(authors) = entry.author {
                         for author in authors {
                             span { class: "badge badge-ghost hover:badge-error transition-colors cursor-default bg-base-200/50",
-                                "{author}"
+                                Highlighted { text: author.clone(), field: QueryField::Author }
                             }
                         }
                     } else {
@@ -142,6 +156,9 @@ pub fn ArXiv(entry: Reference) -> Element {
                     }
                 }
 
+                // Tags
+                TagBadges { cite_key: key.clone(), tags: entry.keywords.clone() }
+
                 // Metadata Row
                 div { class: "mt-4 flex flex-wrap items-center gap-4 text-sm text-base-content/70 border-t border-base-content/5 pt-3",
                     div { class: "flex items-center gap-1",
@@ -227,6 +244,7 @@ pub fn ArXivDrawer(entry: Reference) -> Element {
                                         ChunksComp {
                                             chunks: title,
                                             cite_key: format!("ArXivDrawer-{key}"),
+                                            field: QueryField::Title,
                                         }
                                     }
                                 } else {
@@ -337,6 +355,7 @@ pub fn ArXivDrawer(entry: Reference) -> Element {
                         ChunksComp {
                             chunks: abstract_chunks,
                             cite_key: format!("{key}-abstract"),
+                            field: QueryField::Abstract,
                         }
                     }
                 }
@@ -346,7 +365,11 @@ pub fn ArXivDrawer(entry: Reference) -> Element {
                 div { class: "collapse-title font-medium", "Note" }
                 div { class: "collapse-content",
                     if let Some(note) = entry.note {
-                        ChunksComp { chunks: note, cite_key: format!("{key}-note") }
+                        ChunksComp {
+                            chunks: note,
+                            cite_key: format!("{key}-note"),
+                            field: QueryField::Note,
+                        }
                     }
                 }
             }
@@ -374,7 +397,16 @@ pub fn Misc(entry: Reference) -> Element {
         move |_| {
             copied.set(true);
             if let Ok(mut clipboard) = arboard::Clipboard::new() {
-                if clipboard.set_text(&key_clone).is_ok() {
+                if clipboard
+                    .set_text(cite_command(&[&key_clone], None, CURRENT_BIB().as_deref()))
+                    .is_ok()
+                {
+                    record_usage(
+                        &[&key_clone],
+                        CURRENT_BIB().as_deref(),
+                        None,
+                        UsageAction::Copy,
+                    );
                     copy_success.set(true);
                 } else {
                     copy_success.set(false);
@@ -420,7 +452,11 @@ pub fn Misc(entry: Reference) -> Element {
                             }
                             if let Some(title) = entry.title {
                                 span { class: "badge badge-outline text-lg text-gray-900 font-serif",
-                                    ChunksComp { chunks: title, cite_key: key.clone() }
+                                    ChunksComp {
+                                        chunks: title,
+                                        cite_key: key.clone(),
+                                        field: QueryField::Title,
+                                    }
                                 }
                             } else {
                                 span { class: "badge badge-outline text-lg text-gray-900 font-serif",
@@ -576,6 +612,7 @@ pub fn MiscDrawer(entry: Reference) -> Element {
                                             ChunksComp {
                                                 chunks: title,
                                                 cite_key: format!("MiscDrawer-{key}"),
+                                                field: QueryField::Title,
                                             }
                                         }
                                     } else {
@@ -682,6 +719,7 @@ pub fn MiscDrawer(entry: Reference) -> Element {
                             ChunksComp {
                                 chunks: abstract_chunks,
                                 cite_key: format!("{key}-abstract"),
+                                field: QueryField::Abstract,
                             }
                         }
                     }
@@ -691,7 +729,11 @@ pub fn MiscDrawer(entry: Reference) -> Element {
                     div { class: "collapse-title font-medium", "Note" }
                     div { class: "collapse-content",
                         if let Some(note) = entry.note {
-                            ChunksComp { chunks: note, cite_key: format!("{key}-note") }
+                            ChunksComp {
+                                chunks: note,
+                                cite_key: format!("{key}-note"),
+                                field: QueryField::Note,
+                            }
                         }
                     }
                 }
@@ -16,14 +16,24 @@ mod incollection;
 pub use incollection::*;
 mod inproceedings;
 pub use inproceedings::*;
+mod jabref;
+pub use jabref::*;
 mod misc;
 pub use misc::*;
+mod notes;
+pub use notes::*;
 mod selector;
 pub use selector::*;
+mod smart_group;
+pub use smart_group::*;
+mod tags;
+pub use tags::*;
 mod techreport;
 pub use techreport::*;
 mod thesis;
 pub use thesis::*;
+mod title;
+pub use title::*;
 mod unimplemented;
 pub use unimplemented::*;
 
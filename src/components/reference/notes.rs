@@ -0,0 +1,282 @@
+use crate::{
+    CURRENT_BIB, CURRENT_REF, DRAWER_REFERENCE,
+    components::{current_bib_file, reload_current_bib},
+    sync_search_index,
+    views::rename_usage_key,
+};
+use bibcitex_core::{
+    Annotation, Filter, MAX_RATING, NoteStore, ReadingStatus, bib::Reference,
+    edit::rename_cite_key_in_file,
+};
+use dioxus::prelude::*;
+use pulldown_cmark::Event;
+
+/// 按阅读状态和评分筛选
+#[derive(Clone, Copy, Default, PartialEq)]
+pub struct ReadingFilter {
+    /// 阅读状态，`None` 表示不限
+    pub status: Option<ReadingStatus>,
+    /// 最低评分，0 表示不限
+    pub min_rating: u8,
+}
+
+impl ReadingFilter {
+    pub fn to_filter(self) -> Filter {
+        let status = self.status.map_or(Filter::All, Filter::Status);
+        if self.min_rating > 0 {
+            status.and(Filter::Rating {
+                min: self.min_rating,
+            })
+        } else {
+            status
+        }
+    }
+}
+
+impl std::fmt::Display for ReadingFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.status, self.min_rating) {
+            (None, 0) => write!(f, "阅读"),
+            (Some(status), 0) => write!(f, "{}", status_name(status)),
+            (None, min) => write!(f, "≥{min}★"),
+            (Some(status), min) => write!(f, "{} ≥{min}★", status_name(status)),
+        }
+    }
+}
+
+/// 阅读状态的中文名称
+pub fn status_name(status: ReadingStatus) -> &'static str {
+    match status {
+        ReadingStatus::Unread => "未读",
+        ReadingStatus::Reading => "在读",
+        ReadingStatus::Read => "已读",
+    }
+}
+
+/// 读取文献库的笔记并附加到文献上，笔记文件损坏时忽略
+pub(crate) fn load_annotations(library: &str, refs: &mut [Reference]) {
+    if let Ok(store) = NoteStore::load(library) {
+        store.annotate(refs);
+    }
+}
+
+/// 保存文献的笔记，并更新当前文献库和抽屉中的文献
+fn save_annotation(key: &str, annotation: Annotation) -> Result<(), String> {
+    let name = CURRENT_BIB().ok_or("未打开文献库")?;
+    let mut store = NoteStore::load(&name).map_err(|e| e.to_string())?;
+    store.set(key, annotation);
+    store.save().map_err(|e| e.to_string())?;
+    let annotation = store.get(key).cloned();
+    if let Some(refs) = CURRENT_REF.write().as_mut()
+        && let Some(reference) = refs.iter_mut().find(|r| r.cite_key == key)
+    {
+        reference.annotation = annotation.clone();
+        sync_search_index(&name, refs);
+    }
+    if let Some(reference) = DRAWER_REFERENCE.write().as_mut()
+        && reference.cite_key == key
+    {
+        reference.annotation = annotation;
+    }
+    Ok(())
+}
+
+/// 重命名引用键，写回 .bib 文件，笔记和引用记录随之移动
+fn rename_key(old: &str, new: &str) -> Result<(), String> {
+    let name = CURRENT_BIB().ok_or("未打开文献库")?;
+    let (path, encoding, separators) = current_bib_file()?;
+    let mut store = NoteStore::load(&name).map_err(|e| e.to_string())?;
+    if !rename_cite_key_in_file(&path, encoding, old, new).map_err(|e| e.to_string())? {
+        return Err(format!("无法重命名为 {new}：引用键无效或已存在"));
+    }
+    if store.rename_key(old, new) {
+        store.save().map_err(|e| e.to_string())?;
+    }
+    rename_usage_key(&name, old, new)?;
+    reload_current_bib(&separators)?;
+    *DRAWER_REFERENCE.write() = CURRENT_REF()
+        .unwrap_or_default()
+        .into_iter()
+        .find(|r| r.cite_key == new);
+    Ok(())
+}
+
+/// 阅读状态和评分筛选
+#[component]
+pub fn ReadingFilterSelector() -> Element {
+    let mut reading_filter = use_context::<Signal<ReadingFilter>>();
+    rsx! {
+        div { class: "dropdown join-item",
+            div {
+                tabindex: 0,
+                role: "button",
+                class: "btn join-item w-28 shadow-sm bg-base-100 hover:bg-base-200 border-base-300 font-normal justify-between",
+                "{reading_filter}"
+                span { class: "text-xs opacity-60", "▾" }
+            }
+            div {
+                tabindex: 0,
+                class: "dropdown-content bg-base-100 rounded-box z-10 w-48 p-3 shadow-lg flex flex-col gap-2",
+                select {
+                    class: "select select-sm",
+                    onchange: move |e| reading_filter.write().status = e.value().parse().ok(),
+                    option {
+                        value: "",
+                        selected: reading_filter().status.is_none(),
+                        "全部状态"
+                    }
+                    for status in ReadingStatus::all().iter().copied() {
+                        option {
+                            value: "{status}",
+                            selected: reading_filter().status == Some(status),
+                            "{status_name(status)}"
+                        }
+                    }
+                }
+                select {
+                    class: "select select-sm",
+                    onchange: move |e| reading_filter.write().min_rating = e.value().parse().unwrap_or_default(),
+                    option { value: "0", selected: reading_filter().min_rating == 0, "不限评分" }
+                    for min in 1..=MAX_RATING {
+                        option {
+                            value: "{min}",
+                            selected: reading_filter().min_rating == min,
+                            "≥ {min} ★"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// 抽屉中的笔记编辑器：阅读状态、评分和 Markdown 笔记，保存在配置目录的笔记文件中
+#[component]
+pub fn NotesEditor(entry: Reference) -> Element {
+    let annotation = entry.annotation.clone().unwrap_or_default();
+    let key = entry.cite_key.clone();
+    let mut notes = use_signal(|| annotation.notes.clone());
+    let mut preview = use_signal(|| false);
+    let mut new_key = use_signal(|| key.clone());
+    let mut message = use_signal(|| None::<Result<String, String>>);
+    let dirty = notes() != annotation.notes;
+
+    let mut save = {
+        let key = key.clone();
+        move |annotation: Annotation| {
+            message.set(save_annotation(&key, annotation).err().map(Err));
+        }
+    };
+    let html = use_memo(move || {
+        // 笔记中的 HTML 按文本显示，不插入页面
+        let notes = notes();
+        let events = pulldown_cmark::Parser::new(&notes).map(|event| match event {
+            Event::Html(html) | Event::InlineHtml(html) => Event::Text(html),
+            event => event,
+        });
+        let mut html = String::new();
+        pulldown_cmark::html::push_html(&mut html, events);
+        html
+    });
+
+    rsx! {
+        div { class: "mt-4 flex flex-col gap-3 border-t border-base-content/5 pt-4",
+            div { class: "flex items-center justify-between",
+                h4 { class: "font-semibold", "笔记" }
+                // 评分，再次点击当前评分清除
+                div { class: "flex",
+                    for star in 1..=MAX_RATING {
+                        button {
+                            class: if annotation.rating.is_some_and(|r| r >= star) { "text-warning text-lg" } else { "text-base-content/30 text-lg" },
+                            title: "{star} 星",
+                            onclick: {
+                                let annotation = annotation.clone();
+                                move |_| {
+                                    let rating = (annotation.rating != Some(star)).then_some(star);
+                                    save(Annotation { rating, ..annotation.clone() });
+                                }
+                            },
+                            "★"
+                        }
+                    }
+                }
+            }
+            div { class: "join",
+                for status in ReadingStatus::all().iter().copied() {
+                    button {
+                        class: if annotation.status == status { "btn btn-xs join-item btn-primary" } else { "btn btn-xs join-item" },
+                        onclick: {
+                            let annotation = annotation.clone();
+                            move |_| save(Annotation { status, ..annotation.clone() })
+                        },
+                        "{status_name(status)}"
+                    }
+                }
+            }
+            div { class: "tabs tabs-box tabs-xs w-fit",
+                button {
+                    class: if preview() { "tab" } else { "tab tab-active" },
+                    onclick: move |_| preview.set(false),
+                    "编辑"
+                }
+                button {
+                    class: if preview() { "tab tab-active" } else { "tab" },
+                    onclick: move |_| preview.set(true),
+                    "预览"
+                }
+            }
+            if preview() {
+                div {
+                    class: "prose prose-sm max-w-none min-h-24 rounded-box bg-base-200/50 p-3",
+                    dangerous_inner_html: "{html}",
+                }
+            } else {
+                textarea {
+                    class: "textarea textarea-bordered w-full min-h-40 font-mono text-sm",
+                    placeholder: "使用 Markdown 记录笔记，仅保存在本机，不写入 .bib 文件",
+                    value: "{notes}",
+                    oninput: move |e| notes.set(e.value()),
+                }
+            }
+            button {
+                class: "btn btn-sm btn-primary self-end",
+                disabled: !dirty,
+                onclick: {
+                    let annotation = annotation.clone();
+                    move |_| save(Annotation { notes: notes(), ..annotation.clone() })
+                },
+                "保存笔记"
+            }
+            // 重命名引用键，笔记随之移动
+            div { class: "join w-full",
+                input {
+                    r#type: "text",
+                    class: "input input-sm input-bordered join-item flex-1",
+                    value: "{new_key}",
+                    oninput: move |e| new_key.set(e.value()),
+                }
+                button {
+                    class: "btn btn-sm join-item",
+                    disabled: new_key().trim().is_empty() || new_key().trim() == key,
+                    onclick: {
+                        let key = key.clone();
+                        move |_| {
+                            let new = new_key().trim().to_string();
+                            message.set(Some(rename_key(&key, &new).map(|_| format!("已重命名为 {new}"))));
+                        }
+                    },
+                    "重命名引用键"
+                }
+            }
+            match message() {
+                Some(Ok(text)) => rsx! {
+                    p { class: "text-xs text-success", "{text}" }
+                },
+                Some(Err(error)) => rsx! {
+                    p { class: "text-xs text-error", "{error}" }
+                },
+                None => rsx! {},
+            }
+        }
+    }
+}
@@ -1,98 +1,62 @@
 use crate::views::{FilterField, FilterType};
-use bibcitex_core::{
-    bib::Reference, search_references, search_references_by_author, search_references_by_journal,
-    search_references_by_title, search_references_by_year,
-};
+use bibcitex_core::{QueryField, bib::Reference, rank_references_in};
 use dioxus::prelude::*;
 
 #[component]
 pub fn FilterFieldSelector(refs: Memo<Vec<Reference>>) -> Element {
-    let filter_type = use_context::<Signal<FilterType>>();
     let mut filter_field = use_context::<Signal<FilterField>>();
     let query = use_context::<Signal<String>>();
     let mut search_result = use_context::<Signal<Vec<Reference>>>();
-    let on_filter_change = move |e: Event<FormData>| {
-        let selected_value = e.value();
-        let new_filter = match selected_value.as_str() {
-            "Author" => FilterField::Author,
-            "Title" => FilterField::Title,
-            "Journal" => FilterField::Journal,
-            "Year" => FilterField::Year,
-            _ => FilterField::All,
-        };
-        filter_field.set(new_filter);
-
-        // Re-run search if there's a query
+    // Re-run search if there's a query
+    let mut rerun = move || {
         if !query().is_empty() {
-            let result = match new_filter {
-                FilterField::Author => search_references_by_author(&refs(), &query()),
-                FilterField::Title => search_references_by_title(&refs(), &query()),
-                FilterField::Journal => search_references_by_journal(&refs(), &query()),
-                FilterField::Year => search_references_by_year(&refs(), &query()),
-                FilterField::All => search_references(&refs(), &query()),
-            };
+            let result = rank_references_in(&refs(), &query(), filter_field().fields());
             search_result.set(result);
         }
     };
     rsx! {
-        match filter_type() {
-            FilterType::All | FilterType::Article => rsx! {
-                select {
-                    class: "select select-bordered join-item w-28 max-w-xs shadow-sm bg-base-100 hover:bg-base-200 transition-colors focus:outline-none focus:ring-2 focus:ring-primary/20",
-                    onchange: on_filter_change,
-                    option {
-                        value: "{FilterField::All}",
-                        selected: filter_field() == FilterField::All,
-                        "{FilterField::All}"
-                    }
-                    option {
-                        value: "{FilterField::Author}",
-                        selected: filter_field() == FilterField::Author,
-                        "{FilterField::Author}"
-                    }
-                    option {
-                        value: "{FilterField::Journal}",
-                        selected: filter_field() == FilterField::Journal,
-                        "{FilterField::Journal}"
-                    }
-                    option {
-                        value: "{FilterField::Title}",
-                        selected: filter_field() == FilterField::Title,
-                        "{FilterField::Title}"
-                    }
-                    option {
-                        value: "{FilterField::Year}",
-                        selected: filter_field() == FilterField::Year,
-                        "{FilterField::Year}"
+        div { class: "dropdown join-item",
+            div {
+                tabindex: 0,
+                role: "button",
+                class: "btn join-item w-28 shadow-sm bg-base-100 hover:bg-base-200 border-base-300 font-normal justify-between",
+                "{filter_field}"
+                span { class: "text-xs opacity-60", "▾" }
+            }
+            ul {
+                tabindex: 0,
+                class: "dropdown-content menu bg-base-100 rounded-box z-10 w-48 p-2 shadow-lg max-h-96 overflow-y-auto flex-nowrap",
+                li {
+                    label { class: "label cursor-pointer justify-start gap-2",
+                        input {
+                            r#type: "checkbox",
+                            class: "checkbox checkbox-primary checkbox-xs",
+                            checked: filter_field().is_all(),
+                            onchange: move |_| {
+                                filter_field.write().select_all();
+                                rerun();
+                            },
+                        }
+                        span { "All" }
                     }
                 }
-            },
-            _ => rsx! {
-                select {
-                    class: "select select-bordered join-item w-28 max-w-xs shadow-sm bg-base-100 hover:bg-base-200 transition-colors focus:outline-none focus:ring-2 focus:ring-primary/20",
-                    onchange: on_filter_change,
-                    option {
-                        value: "{FilterField::All}",
-                        selected: filter_field() == FilterField::All,
-                        "{FilterField::All}"
-                    }
-                    option {
-                        value: "{FilterField::Author}",
-                        selected: filter_field() == FilterField::Author,
-                        "{FilterField::Author}"
-                    }
-                    option {
-                        value: "{FilterField::Title}",
-                        selected: filter_field() == FilterField::Title,
-                        "{FilterField::Title}"
-                    }
-                    option {
-                        value: "{FilterField::Year}",
-                        selected: filter_field() == FilterField::Year,
-                        "{FilterField::Year}"
+                for field in QueryField::defaults().iter().copied() {
+                    li {
+                        label { class: "label cursor-pointer justify-start gap-2",
+                            input {
+                                r#type: "checkbox",
+                                class: "checkbox checkbox-primary checkbox-xs",
+                                checked: filter_field().contains(field),
+                                onchange: move |_| {
+                                    filter_field.write().toggle(field);
+                                    rerun();
+                                },
+                            }
+                            span { "{field}" }
+                        }
                     }
                 }
-            },
+            }
         }
     }
 }
@@ -103,87 +67,58 @@ pub fn FilterTypeSelector(refs: Memo<Vec<Reference>>) -> Element {
     let filter_field = use_context::<Signal<FilterField>>();
     let query = use_context::<Signal<String>>();
     let mut search_result = use_context::<Signal<Vec<Reference>>>();
-
-    let on_type_change = move |e: Event<FormData>| {
-        let selected_value = e.value();
-        let new_type = match selected_value.as_str() {
-            "Article" => FilterType::Article,
-            "Book" => FilterType::Book,
-            "Thesis" => FilterType::Thesis,
-            "TechReport" => FilterType::TechReport,
-            "Misc" => FilterType::Misc,
-            "Booklet" => FilterType::Booklet,
-            "InBook" => FilterType::InBook,
-            "InCollection" => FilterType::InCollection,
-            "InProceedings" => FilterType::InProceedings,
-            _ => FilterType::All,
-        };
-        filter_type.set(new_type);
-        // Re-run search if there's a query
+    // Re-run search if there's a query
+    let mut rerun = move || {
         if !query().is_empty() {
-            let result = match filter_field() {
-                FilterField::Author => search_references_by_author(&refs(), &query()),
-                FilterField::Title => search_references_by_title(&refs(), &query()),
-                FilterField::Journal => search_references_by_journal(&refs(), &query()),
-                FilterField::Year => search_references_by_year(&refs(), &query()),
-                FilterField::All => search_references(&refs(), &query()),
-            };
+            let result = rank_references_in(&refs(), &query(), filter_field().fields());
             search_result.set(result);
         }
     };
     rsx! {
-        select {
-            class: "select select-bordered join-item w-40 max-w-xs shadow-sm bg-base-100 hover:bg-base-200 transition-colors focus:outline-none focus:ring-2 focus:ring-primary/20",
-            onchange: on_type_change,
-            option {
-                value: "{FilterType::All}",
-                selected: filter_type() == FilterType::All,
-                "{FilterType::All}"
-            }
-            option {
-                value: "{FilterType::Book}",
-                selected: filter_type() == FilterType::Book,
-                "{FilterType::Book}"
+        div { class: "dropdown join-item",
+            div {
+                tabindex: 0,
+                role: "button",
+                class: "btn join-item w-40 shadow-sm bg-base-100 hover:bg-base-200 border-base-300 font-normal justify-between",
+                "{filter_type}"
+                span { class: "text-xs opacity-60", "▾" }
             }
-            option {
-                value: "{FilterType::Article}",
-                selected: filter_type() == FilterType::Article,
-                "{FilterType::Article}"
-            }
-            option {
-                value: "{FilterType::Thesis}",
-                selected: filter_type() == FilterType::Thesis,
-                "{FilterType::Thesis}"
-            }
-            option {
-                value: "{FilterType::TechReport}",
-                selected: filter_type() == FilterType::TechReport,
-                "{FilterType::TechReport}"
-            }
-            option {
-                value: "{FilterType::Misc}",
-                selected: filter_type() == FilterType::Misc,
-                "{FilterType::Misc}"
-            }
-            option {
-                value: "{FilterType::Booklet}",
-                selected: filter_type() == FilterType::Booklet,
-                "{FilterType::Booklet}"
-            }
-            option {
-                value: "{FilterType::InBook}",
-                selected: filter_type() == FilterType::InBook,
-                "{FilterType::InBook}"
-            }
-            option {
-                value: "{FilterType::InCollection}",
-                selected: filter_type() == FilterType::InCollection,
-                "{FilterType::InCollection}"
-            }
-            option {
-                value: "{FilterType::InProceedings}",
-                selected: filter_type() == FilterType::InProceedings,
-                "{FilterType::InProceedings}"
+            ul {
+                tabindex: 0,
+                class: "dropdown-content menu bg-base-100 rounded-box z-10 w-48 p-2 shadow-lg max-h-96 overflow-y-auto flex-nowrap",
+                li {
+                    label { class: "label cursor-pointer justify-start gap-2",
+                        input {
+                            r#type: "checkbox",
+                            class: "checkbox checkbox-primary checkbox-xs",
+                            checked: filter_type().is_all(),
+                            onchange: move |_| {
+                                filter_type.write().select_all();
+                                rerun();
+                            },
+                        }
+                        span { "All" }
+                    }
+                }
+                for entry_type in FilterType::TYPES {
+                    li {
+                        label { class: "label cursor-pointer justify-start gap-2",
+                            input {
+                                r#type: "checkbox",
+                                class: "checkbox checkbox-primary checkbox-xs",
+                                checked: filter_type().contains(&entry_type),
+                                onchange: {
+                                    let entry_type = entry_type.clone();
+                                    move |_| {
+                                        filter_type.write().toggle(&entry_type);
+                                        rerun();
+                                    }
+                                },
+                            }
+                            span { "{FilterType::name(&entry_type)}" }
+                        }
+                    }
+                }
             }
         }
     }
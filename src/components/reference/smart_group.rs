@@ -0,0 +1,173 @@
+use crate::{
+    CURRENT_BIB, CURRENT_REF, STATE,
+    components::{ActiveJabRefGroup, ReadingFilter, TagFilter},
+    views::{FilterField, FilterType, view_filter},
+};
+use bibcitex_core::{Filter, JabRefGroup, SavedSearch, SortOrder};
+use dioxus::prelude::*;
+
+/// 当前打开的智能分组名
+#[derive(Clone, Copy)]
+pub struct ActiveGroup(pub Signal<Option<String>>);
+
+/// 智能分组栏，点击分组显示保存的搜索结果，结果随文献库实时更新
+#[component]
+pub fn SmartGroups(tree: Memo<Option<JabRefGroup>>) -> Element {
+    let mut active = use_context::<ActiveGroup>().0;
+    let mut query = use_context::<Signal<String>>();
+    let filter_type = use_context::<Signal<FilterType>>();
+    let filter_field = use_context::<Signal<FilterField>>();
+    let reading_filter = use_context::<Signal<ReadingFilter>>();
+    let tag_filter = use_context::<TagFilter>().0;
+    let jabref_group = use_context::<ActiveJabRefGroup>().0;
+    let mut is_saving = use_signal(|| false);
+    let mut name = use_signal(String::new);
+    let mut sort = use_signal(SortOrder::default);
+    // 是否只在当前文献库中显示该分组的结果
+    let mut library_only = use_signal(|| false);
+    let mut error_message = use_signal(|| None::<String>);
+    // 每个分组当前的文献数量
+    let groups = use_memo(move || {
+        let refs = CURRENT_REF().unwrap_or_default();
+        let library = CURRENT_BIB();
+        let tree = tree();
+        STATE
+            .read()
+            .saved_searches
+            .iter()
+            .map(|search| {
+                let count = search.run(&refs, library.as_deref(), tree.as_ref()).len();
+                (search.name.clone(), search.query.clone(), count)
+            })
+            .collect::<Vec<_>>()
+    });
+
+    // 保存与当前列表相同的筛选条件、搜索字段和 JabRef 分组
+    let save = move |_| {
+        let mut filter = view_filter(&filter_type(), reading_filter(), &tag_filter());
+        if let Some(library) = CURRENT_BIB().filter(|_| library_only()) {
+            filter = filter.and(Filter::Library(library));
+        }
+        let fields = filter_field();
+        let fields = if fields.is_all() {
+            &[][..]
+        } else {
+            fields.fields()
+        };
+        let search = SavedSearch::new(name().trim(), query().trim())
+            .with_filter(filter)
+            .with_fields(fields)
+            .with_jabref_group(jabref_group())
+            .with_sort(sort());
+        let mut state = STATE.write();
+        state.add_update_saved_search(search);
+        if let Err(e) = state.update_file() {
+            error_message.set(Some(e.to_string()));
+        } else {
+            error_message.set(None);
+            is_saving.set(false);
+            name.set(String::new());
+            library_only.set(false);
+        }
+    };
+
+    let mut remove = move |group: String| {
+        let mut state = STATE.write();
+        state.remove_saved_search(&group);
+        if let Err(e) = state.update_file() {
+            error_message.set(Some(e.to_string()));
+        }
+        if active().as_deref() == Some(group.as_str()) {
+            active.set(None);
+        }
+    };
+
+    rsx! {
+        div { class: "flex flex-wrap items-center gap-2 mt-2",
+            for (group , group_query , count) in groups() {
+                div {
+                    key: "{group}",
+                    class: if active().as_deref() == Some(group.as_str()) { "badge badge-primary gap-1 cursor-pointer" } else { "badge badge-outline gap-1 cursor-pointer hover:bg-base-200" },
+                    title: "{group_query}",
+                    onclick: {
+                        let group = group.clone();
+                        move |_| {
+                            if active().as_deref() == Some(group.as_str()) {
+                                active.set(None);
+                            } else {
+                                query.set(String::new());
+                                active.set(Some(group.clone()));
+                            }
+                        }
+                    },
+                    "{group}"
+                    span { class: "opacity-60", "{count}" }
+                    button {
+                        class: "opacity-40 hover:opacity-100",
+                        onclick: {
+                            let group = group.clone();
+                            move |e: Event<MouseData>| {
+                                e.stop_propagation();
+                                remove(group.clone());
+                            }
+                        },
+                        "×"
+                    }
+                }
+            }
+            if is_saving() {
+                input {
+                    r#type: "text",
+                    class: "input input-xs input-bordered w-40",
+                    placeholder: "分组名称",
+                    value: "{name}",
+                    oninput: move |e| name.set(e.value()),
+                }
+                select {
+                    class: "select select-xs select-bordered w-28",
+                    onchange: move |e: Event<FormData>| {
+                        if let Some(&order) = SortOrder::all().iter().find(|o| o.to_string() == e.value()) {
+                            sort.set(order);
+                        }
+                    },
+                    for order in SortOrder::all().iter().copied() {
+                        option { value: "{order}", selected: sort() == order, "{order}" }
+                    }
+                }
+                label {
+                    class: "label text-xs gap-1 cursor-pointer",
+                    title: "在其他文献库中该分组为空",
+                    input {
+                        r#type: "checkbox",
+                        class: "checkbox checkbox-xs",
+                        checked: library_only(),
+                        disabled: CURRENT_BIB().is_none(),
+                        onchange: move |e| library_only.set(e.checked()),
+                    }
+                    "仅当前文献库"
+                }
+                button {
+                    class: "btn btn-xs btn-primary",
+                    disabled: name().trim().is_empty(),
+                    onclick: save,
+                    "保存"
+                }
+                button {
+                    class: "btn btn-xs btn-ghost",
+                    onclick: move |_| is_saving.set(false),
+                    "取消"
+                }
+            } else {
+                button {
+                    class: "btn btn-xs btn-ghost text-base-content/60",
+                    title: "将当前的搜索和筛选条件保存为智能分组",
+                    onclick: move |_| is_saving.set(true),
+                    "+ 保存搜索"
+                }
+            }
+            if let Some(error) = error_message() {
+                span { class: "text-xs text-error", "{error}" }
+            }
+        }
+    }
+}
@@ -0,0 +1,283 @@
+use crate::{
+    CURRENT_BIB, CURRENT_REF, STATE,
+    components::{apply_journal_style, load_annotations},
+    sync_search_index,
+};
+use bibcitex_core::{
+    Encoding, TagEdit, bib::Reference, edit_tags_in_file, same_tag, tag_counts,
+    utils::read_bibliography_with,
+};
+use dioxus::prelude::*;
+use std::path::PathBuf;
+
+/// 筛选用的标签，文献需包含所有选中的标签
+#[derive(Clone, Copy)]
+pub struct TagFilter(pub Signal<Vec<String>>);
+
+/// 批量编辑标签时选中的文献引用键
+#[derive(Clone, Copy)]
+pub struct Selection(pub Signal<Vec<String>>);
+
+/// 当前文献库的路径、编码和标签分隔符
+pub(crate) fn current_bib_file() -> Result<(PathBuf, Option<&'static Encoding>, String), String> {
+    let name = CURRENT_BIB().ok_or("未打开文献库")?;
+    let state = STATE.read();
+    let info = state
+        .bibliographies
+        .get(&name)
+        .ok_or(format!("文献库 {name} 不存在"))?;
+    let encoding = info.encoding().map_err(|e| e.to_string())?;
+    Ok((info.path.clone(), encoding, state.tag_separators.clone()))
+}
+
+/// 修改 .bib 文件后按文献库设置的编码重新读取当前文献库
+pub(crate) fn reload_current_bib(separators: &str) -> Result<(), String> {
+    let name = CURRENT_BIB().ok_or("未打开文献库")?;
+    let bib = STATE.read().parse(&name).map_err(|e| e.to_string())?;
+    let mut refs = read_bibliography_with(bib, separators);
+    load_annotations(&name, &mut refs);
+    apply_journal_style(&name, &mut refs);
+    sync_search_index(&name, &refs);
+    *CURRENT_REF.write() = Some(refs);
+    Ok(())
+}
+
+/// 修改当前文献库中指定文献的标签，写回 .bib 文件并重新读取文献库
+fn apply_tag_edit(keys: &[String], edit: &TagEdit) -> Result<usize, String> {
+    let (path, encoding, separators) = current_bib_file()?;
+    let keys = keys.iter().map(String::as_str).collect::<Vec<_>>();
+    let changed =
+        edit_tags_in_file(&path, encoding, &keys, edit, &separators).map_err(|e| e.to_string())?;
+    if changed > 0 {
+        reload_current_bib(&separators)?;
+    }
+    Ok(changed)
+}
+
+/// 文献的标签，点击标签按其筛选
+#[component]
+pub fn TagBadges(cite_key: String, tags: Option<Vec<String>>) -> Element {
+    let tag_filter = try_use_context::<TagFilter>();
+    let selection = try_use_context::<Selection>();
+    let tags = tags.unwrap_or_default();
+    if tags.is_empty() && selection.is_none() {
+        return rsx! {};
+    }
+    rsx! {
+        div { class: "mt-2 flex flex-wrap items-center gap-1",
+            if let Some(Selection(mut selected)) = selection {
+                input {
+                    r#type: "checkbox",
+                    class: "checkbox checkbox-xs checkbox-primary mr-1",
+                    title: "选择以批量编辑标签",
+                    checked: selected().contains(&cite_key),
+                    onchange: {
+                        let cite_key = cite_key.clone();
+                        move |_| {
+                            let mut selected = selected.write();
+                            if let Some(pos) = selected.iter().position(|k| *k == cite_key) {
+                                selected.remove(pos);
+                            } else {
+                                selected.push(cite_key.clone());
+                            }
+                        }
+                    },
+                }
+            }
+            for tag in tags {
+                button {
+                    key: "{tag}",
+                    class: if tag_filter.is_some_and(|TagFilter(filter)| filter().iter().any(|t| same_tag(t, &tag))) { "badge badge-sm badge-accent" } else { "badge badge-sm badge-accent badge-soft hover:badge-accent" },
+                    onclick: {
+                        let tag = tag.clone();
+                        move |_| {
+                            if let Some(TagFilter(mut filter)) = tag_filter {
+                                let mut filter = filter.write();
+                                if let Some(pos) = filter.iter().position(|t| same_tag(t, &tag)) {
+                                    filter.remove(pos);
+                                } else {
+                                    filter.push(tag.clone());
+                                }
+                            }
+                        }
+                    },
+                    "#{tag}"
+                }
+            }
+        }
+    }
+}
+
+/// 标签侧栏，显示标签及其文献数量，可按标签筛选，并批量添加、删除或重命名标签
+#[component]
+pub fn TagSidebar(shown: Memo<Vec<Reference>>) -> Element {
+    let mut tag_filter = use_context::<TagFilter>().0;
+    let mut selected = use_context::<Selection>().0;
+    let mut new_tag = use_signal(String::new);
+    let mut renaming = use_signal(|| None::<String>);
+    let mut rename_to = use_signal(String::new);
+    let mut message = use_signal(|| None::<Result<String, String>>);
+    let counts = use_memo(|| tag_counts(&CURRENT_REF().unwrap_or_default()));
+
+    let mut report = move |result: Result<usize, String>| {
+        message.set(Some(result.map(|n| format!("已修改 {n} 篇文献"))));
+    };
+
+    let mut bulk = move |edit: TagEdit| {
+        if selected().is_empty() {
+            return;
+        }
+        report(apply_tag_edit(&selected(), &edit));
+        new_tag.set(String::new());
+    };
+
+    // 重命名选中文献中的标签，未选中文献时重命名整个文献库中的标签
+    let mut rename = move |from: String| {
+        let to = rename_to().trim().to_string();
+        renaming.set(None);
+        if to.is_empty() {
+            return;
+        }
+        let keys = if selected().is_empty() {
+            CURRENT_REF()
+                .unwrap_or_default()
+                .into_iter()
+                .map(|r| r.cite_key)
+                .collect()
+        } else {
+            selected()
+        };
+        let edit = TagEdit::Rename {
+            from: from.clone(),
+            to: to.clone(),
+        };
+        report(apply_tag_edit(&keys, &edit));
+        let mut filter = tag_filter.write();
+        if let Some(pos) = filter.iter().position(|t| same_tag(t, &from)) {
+            filter[pos] = to;
+        }
+    };
+
+    rsx! {
+        div { class: "flex flex-col gap-3",
+            div { class: "flex items-center justify-between",
+                h3 { class: "font-semibold", "标签" }
+                if !tag_filter().is_empty() {
+                    button {
+                        class: "btn btn-xs btn-ghost",
+                        onclick: move |_| tag_filter.set(Vec::new()),
+                        "清除筛选"
+                    }
+                }
+            }
+            if counts().is_empty() {
+                p { class: "text-sm text-base-content/50", "暂无标签" }
+            }
+            ul { class: "menu menu-sm p-0",
+                for (tag , count) in counts() {
+                    li { key: "{tag}",
+                        if renaming().as_deref() == Some(tag.as_str()) {
+                            input {
+                                r#type: "text",
+                                class: "input input-xs input-bordered",
+                                value: "{rename_to}",
+                                autofocus: true,
+                                oninput: move |e| rename_to.set(e.value()),
+                                onkeydown: {
+                                    let tag = tag.clone();
+                                    move |e: Event<KeyboardData>| match e.key() {
+                                        Key::Enter => rename(tag.clone()),
+                                        Key::Escape => renaming.set(None),
+                                        _ => {}
+                                    }
+                                },
+                            }
+                        } else {
+                            div {
+                                class: if tag_filter().iter().any(|t| same_tag(t, &tag)) { "flex justify-between menu-active" } else { "flex justify-between" },
+                                onclick: {
+                                    let tag = tag.clone();
+                                    move |_| {
+                                        let mut filter = tag_filter.write();
+                                        if let Some(pos) = filter.iter().position(|t| same_tag(t, &tag)) {
+                                            filter.remove(pos);
+                                        } else {
+                                            filter.push(tag.clone());
+                                        }
+                                    }
+                                },
+                                span { class: "truncate", "#{tag}" }
+                                span { class: "flex items-center gap-1",
+                                    span { class: "badge badge-xs badge-ghost", "{count}" }
+                                    button {
+                                        class: "opacity-40 hover:opacity-100",
+                                        title: "重命名",
+                                        onclick: {
+                                            let tag = tag.clone();
+                                            move |e: Event<MouseData>| {
+                                                e.stop_propagation();
+                                                rename_to.set(tag.clone());
+                                                renaming.set(Some(tag.clone()));
+                                            }
+                                        },
+                                        "✎"
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            // 批量编辑
+            div { class: "border-t border-base-300 pt-3 flex flex-col gap-2",
+                div { class: "flex items-center justify-between text-sm",
+                    span { "已选 {selected().len()} 篇" }
+                    span { class: "flex gap-1",
+                        button {
+                            class: "btn btn-xs btn-ghost",
+                            onclick: move |_| selected.set(shown().into_iter().map(|r| r.cite_key).collect()),
+                            "全选"
+                        }
+                        button {
+                            class: "btn btn-xs btn-ghost",
+                            disabled: selected().is_empty(),
+                            onclick: move |_| selected.set(Vec::new()),
+                            "清空"
+                        }
+                    }
+                }
+                input {
+                    r#type: "text",
+                    class: "input input-xs input-bordered",
+                    placeholder: "标签",
+                    value: "{new_tag}",
+                    oninput: move |e| new_tag.set(e.value()),
+                }
+                div { class: "flex gap-1",
+                    button {
+                        class: "btn btn-xs btn-primary flex-1",
+                        disabled: selected().is_empty() || new_tag().trim().is_empty(),
+                        onclick: move |_| bulk(TagEdit::Add(new_tag())),
+                        "添加"
+                    }
+                    button {
+                        class: "btn btn-xs btn-outline flex-1",
+                        disabled: selected().is_empty() || new_tag().trim().is_empty(),
+                        onclick: move |_| bulk(TagEdit::Remove(new_tag())),
+                        "移除"
+                    }
+                }
+                p { class: "text-xs text-base-content/50", "未选择文献时，重命名作用于整个文献库" }
+                match message() {
+                    Some(Ok(text)) => rsx! {
+                        p { class: "text-xs text-success", "{text}" }
+                    },
+                    Some(Err(error)) => rsx! {
+                        p { class: "text-xs text-error", "{error}" }
+                    },
+                    None => rsx! {},
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,202 @@
+use crate::{
+    STATE,
+    components::{Selection, current_bib_file, reload_current_bib},
+};
+use bibcitex_core::{
+    TitleCase, TitleChange, TitleEdit, apply_titles_in_file, preview_titles_in_file,
+};
+use dioxus::prelude::*;
+
+/// 标题大小写的中文名称
+fn case_name(case: TitleCase) -> &'static str {
+    match case {
+        TitleCase::Title => "标题大小写",
+        TitleCase::Sentence => "句子大小写",
+    }
+}
+
+/// 预览当前文献库中选中文献的标题修改
+fn preview(keys: &[String], title_edit: &TitleEdit) -> Result<Vec<TitleChange>, String> {
+    let (path, encoding, _) = current_bib_file()?;
+    let keys = keys.iter().map(String::as_str).collect::<Vec<_>>();
+    preview_titles_in_file(&path, encoding, &keys, title_edit).map_err(|e| e.to_string())
+}
+
+/// 将预览的标题修改写回 .bib 文件并重新读取文献库，返回修改的文献数和预览后标题已变动而跳过的引用键
+fn apply(changes: &[TitleChange]) -> Result<(usize, Vec<String>), String> {
+    let (path, encoding, separators) = current_bib_file()?;
+    let (changed, skipped) =
+        apply_titles_in_file(&path, encoding, changes).map_err(|e| e.to_string())?;
+    if changed > 0 {
+        reload_current_bib(&separators)?;
+    }
+    Ok((changed, skipped))
+}
+
+/// 修改保护词典并保存设置
+fn update_protected_words(edit: impl FnOnce(&mut Vec<String>)) -> Result<(), String> {
+    let mut state = STATE.write();
+    edit(&mut state.protected_words);
+    state.update_file().map_err(|e| e.to_string())
+}
+
+/// 标题工具：转换选中文献标题的大小写，用花括号保护缩写词和专有名词，预览后写回 .bib 文件
+#[component]
+pub fn TitleTool() -> Element {
+    let selected = use_context::<Selection>().0;
+    let mut case = use_signal(|| Some(TitleCase::Sentence));
+    let mut protect = use_signal(|| true);
+    let mut changes = use_signal(|| None::<Vec<TitleChange>>);
+    let mut new_word = use_signal(String::new);
+    let mut show_words = use_signal(|| false);
+    let mut message = use_signal(|| None::<Result<String, String>>);
+    let words = use_memo(|| STATE.read().protected_words.clone());
+
+    let open_preview = move |_| {
+        let title_edit = TitleEdit {
+            case: case(),
+            protect: protect(),
+            dictionary: STATE.read().protected_words.clone(),
+        };
+        match preview(&selected(), &title_edit) {
+            Ok(previewed) if previewed.is_empty() => {
+                message.set(Some(Ok("标题无需修改".to_string())));
+            }
+            Ok(previewed) => {
+                message.set(None);
+                changes.set(Some(previewed));
+            }
+            Err(e) => message.set(Some(Err(e))),
+        }
+    };
+    let write = move |_| {
+        let result = apply(&changes().unwrap_or_default());
+        message.set(Some(result.map(|(n, skipped)| {
+            if skipped.is_empty() {
+                format!("已修改 {n} 篇文献的标题")
+            } else {
+                format!(
+                    "已修改 {n} 篇文献的标题，以下文献的标题在预览后已变动，未修改：{}",
+                    skipped.join(", ")
+                )
+            }
+        })));
+        changes.set(None);
+    };
+    let mut add_word = move || {
+        let word = new_word().trim().to_string();
+        if word.is_empty() {
+            return;
+        }
+        let result = update_protected_words(|words| {
+            if !words.contains(&word) {
+                words.push(word.clone());
+            }
+        });
+        message.set(result.err().map(Err));
+        new_word.set(String::new());
+    };
+
+    rsx! {
+        div { class: "flex flex-col gap-2 border-t border-base-300 pt-3",
+            h3 { class: "font-semibold", "标题" }
+            select {
+                class: "select select-xs",
+                onchange: move |e| case.set(e.value().parse().ok()),
+                option { value: "", selected: case().is_none(), "不改大小写" }
+                for c in TitleCase::all().iter().copied() {
+                    option { value: "{c}", selected: case() == Some(c), "{case_name(c)}" }
+                }
+            }
+            label { class: "label text-xs gap-2 cursor-pointer",
+                input {
+                    r#type: "checkbox",
+                    class: "checkbox checkbox-xs",
+                    checked: protect(),
+                    onchange: move |e| protect.set(e.checked()),
+                }
+                "保护缩写词和专有名词"
+            }
+            button {
+                class: "btn btn-xs btn-primary",
+                disabled: selected().is_empty() || (case().is_none() && !protect()),
+                onclick: open_preview,
+                "预览选中文献"
+            }
+            button {
+                class: "btn btn-xs btn-ghost justify-start",
+                onclick: move |_| show_words.set(!show_words()),
+                if show_words() { "▾ 专有名词词典" } else { "▸ 专有名词词典" }
+            }
+            if show_words() {
+                div { class: "flex flex-wrap gap-1",
+                    for word in words() {
+                        span { key: "{word}", class: "badge badge-sm badge-ghost gap-1",
+                            "{word}"
+                            button {
+                                class: "opacity-50 hover:opacity-100",
+                                onclick: {
+                                    let word = word.clone();
+                                    move |_| {
+                                        let result = update_protected_words(|words| words.retain(|w| *w != word));
+                                        message.set(result.err().map(Err));
+                                    }
+                                },
+                                "×"
+                            }
+                        }
+                    }
+                }
+                input {
+                    r#type: "text",
+                    class: "input input-xs input-bordered",
+                    placeholder: "添加专有名词，回车保存",
+                    value: "{new_word}",
+                    oninput: move |e| new_word.set(e.value()),
+                    onkeydown: move |e: Event<KeyboardData>| {
+                        if e.key() == Key::Enter {
+                            add_word();
+                        }
+                    },
+                }
+            }
+            match message() {
+                Some(Ok(text)) => rsx! {
+                    p { class: "text-xs text-success", "{text}" }
+                },
+                Some(Err(error)) => rsx! {
+                    p { class: "text-xs text-error", "{error}" }
+                },
+                None => rsx! {},
+            }
+        }
+        if let Some(previewed) = changes() {
+            div { class: "modal modal-open backdrop-blur-sm",
+                div { class: "modal-box w-2/3 max-w-4xl glass-panel shadow-2xl",
+                    h3 { class: "text-xl font-bold mb-4", "标题修改预览（{previewed.len()} 篇）" }
+                    div { class: "max-h-[60vh] overflow-y-auto flex flex-col gap-3",
+                        for change in previewed.iter() {
+                            div { key: "{change.key}", class: "text-sm",
+                                code { class: "text-xs text-base-content/60", "{change.key}" }
+                                p { class: "line-through text-base-content/50", "{change.old}" }
+                                p { class: "text-success", "{change.new}" }
+                            }
+                        }
+                    }
+                    div { class: "modal-action",
+                        button {
+                            class: "btn btn-ghost",
+                            onclick: move |_| changes.set(None),
+                            "取消"
+                        }
+                        button { class: "btn btn-primary", onclick: write, "写入 .bib 文件" }
+                    }
+                }
+                div {
+                    class: "modal-backdrop bg-base-300/30",
+                    onclick: move |_| changes.set(None),
+                }
+            }
+        }
+    }
+}
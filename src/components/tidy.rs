@@ -0,0 +1,308 @@
+use crate::{
+    CURRENT_BIB, ERR_ICON, STATE,
+    components::{current_bib_file, reload_current_bib},
+};
+use bibcitex_core::{Delimiter, DiffLine, SortBy, TidyOptions, TidyReport, diff_lines, tidy_file};
+use dioxus::prelude::*;
+
+/// 字段值定界符的中文名称
+fn delimiter_name(delimiter: Delimiter) -> &'static str {
+    match delimiter {
+        Delimiter::Braces => "花括号 {}",
+        Delimiter::Quotes => "双引号 \"\"",
+        Delimiter::Keep => "保持原样",
+    }
+}
+
+/// 条目排序方式的中文名称
+fn sort_name(sort: SortBy) -> &'static str {
+    match sort {
+        SortBy::Key => "按引用键",
+        SortBy::Year => "按年份（新到旧）",
+        SortBy::Type => "按条目类型",
+    }
+}
+
+/// 整理文献库的 .bib 文件，`dry_run` 时只生成预览
+fn tidy_library(name: &str, dry_run: bool) -> Result<TidyReport, String> {
+    let (path, encoding, options) = {
+        let state = STATE.read();
+        let info = state
+            .bibliographies
+            .get(name)
+            .ok_or(format!("文献库 {name} 不存在"))?;
+        let encoding = info.encoding().map_err(|e| e.to_string())?;
+        (info.path.clone(), encoding, state.tidy.clone())
+    };
+    let report = tidy_file(&path, encoding, &options, dry_run).map_err(|e| e.to_string())?;
+    if !dry_run && !report.is_unchanged() && CURRENT_BIB().as_deref() == Some(name) {
+        let (_, _, separators) = current_bib_file()?;
+        reload_current_bib(&separators)?;
+    }
+    Ok(report)
+}
+
+/// 修改整理选项并保存设置
+fn update_options(edit: impl FnOnce(&mut TidyOptions)) -> Result<(), String> {
+    let mut state = STATE.write();
+    edit(&mut state.tidy);
+    state.update_file().map_err(|e| e.to_string())
+}
+
+/// 整理文件：按设置统一 .bib 文件的格式，预览差异后写回
+#[component]
+pub fn TidyBibliography(library: String, mut tidying: Signal<Option<String>>) -> Element {
+    let options = use_memo(|| STATE.read().tidy.clone());
+    let mut report = use_signal(|| None::<TidyReport>);
+    let mut message = use_signal(|| None::<String>);
+    let mut error_message = use_signal(|| None::<String>);
+
+    let mut set = move |edit: Box<dyn FnOnce(&mut TidyOptions)>| {
+        report.set(None);
+        message.set(None);
+        error_message.set(update_options(edit).err());
+    };
+
+    let preview = {
+        let library = library.clone();
+        move |_| {
+            message.set(None);
+            match tidy_library(&library, true) {
+                Ok(result) if result.is_unchanged() => {
+                    error_message.set(None);
+                    report.set(None);
+                    message.set(Some("文件格式已整齐，无需修改".to_string()));
+                }
+                Ok(result) => {
+                    error_message.set(None);
+                    report.set(Some(result));
+                }
+                Err(e) => error_message.set(Some(e)),
+            }
+        }
+    };
+
+    let write = {
+        let library = library.clone();
+        move |_| match tidy_library(&library, false) {
+            Ok(result) => {
+                error_message.set(None);
+                report.set(None);
+                message.set(Some(format!("已整理 {} 条文献", result.changes.len())));
+            }
+            Err(e) => error_message.set(Some(e)),
+        }
+    };
+
+    let close_modal = move |_| {
+        tidying.set(None);
+    };
+
+    rsx! {
+        div { class: "modal modal-open backdrop-blur-sm",
+            div { class: "modal-box w-2/3 max-w-4xl glass-panel shadow-2xl",
+                h3 { class: "text-2xl font-bold mb-2 gradient-text", "整理文件" }
+                p { class: "text-sm text-base-content/60 mb-6",
+                    "统一 {library} 的字段顺序、缩进、定界符和页码格式，注释和 @string 保持不变"
+                }
+
+                div { class: "grid grid-cols-2 gap-3 text-sm",
+                    label { class: "flex items-center gap-2",
+                        span { class: "w-20 text-base-content/60", "缩进" }
+                        select {
+                            class: "select select-sm grow",
+                            onchange: move |e| {
+                                let indent = e.value();
+                                set(Box::new(move |options| options.indent = indent));
+                            },
+                            for (value , name) in [("  ", "2 个空格"), ("    ", "4 个空格"), ("\t", "Tab")] {
+                                option {
+                                    value: "{value}",
+                                    selected: options().indent == value,
+                                    "{name}"
+                                }
+                            }
+                        }
+                    }
+                    label { class: "flex items-center gap-2",
+                        span { class: "w-20 text-base-content/60", "定界符" }
+                        select {
+                            class: "select select-sm grow",
+                            onchange: move |e| {
+                                let delimiter = match e.value().as_str() {
+                                    "quotes" => Delimiter::Quotes,
+                                    "keep" => Delimiter::Keep,
+                                    _ => Delimiter::Braces,
+                                };
+                                set(Box::new(move |options| options.delimiter = delimiter));
+                            },
+                            for (value , delimiter) in [("braces", Delimiter::Braces), ("quotes", Delimiter::Quotes), ("keep", Delimiter::Keep)] {
+                                option {
+                                    value: "{value}",
+                                    selected: options().delimiter == delimiter,
+                                    "{delimiter_name(delimiter)}"
+                                }
+                            }
+                        }
+                    }
+                    label { class: "flex items-center gap-2",
+                        span { class: "w-20 text-base-content/60", "条目顺序" }
+                        select {
+                            class: "select select-sm grow",
+                            onchange: move |e| {
+                                let sort = e.value().parse().ok();
+                                set(Box::new(move |options| options.sort = sort));
+                            },
+                            option { value: "", selected: options().sort.is_none(), "保持原顺序" }
+                            for sort in SortBy::all().iter().copied() {
+                                option {
+                                    value: "{sort}",
+                                    selected: options().sort == Some(sort),
+                                    "{sort_name(sort)}"
+                                }
+                            }
+                        }
+                    }
+                    label { class: "flex items-center gap-2",
+                        span { class: "w-20 text-base-content/60", "字段顺序" }
+                        input {
+                            class: "input input-bordered input-sm grow font-mono",
+                            title: "逗号分隔，未列出的字段按原顺序排在后面",
+                            value: "{options().field_order.join(\", \")}",
+                            onchange: move |e| {
+                                let order = e
+                                    .value()
+                                    .split(',')
+                                    .map(|name| name.trim().to_lowercase())
+                                    .filter(|name| !name.is_empty())
+                                    .collect();
+                                set(Box::new(move |options| options.field_order = order));
+                            },
+                        }
+                    }
+                }
+                div { class: "flex flex-wrap gap-4 mt-3 text-sm",
+                    label { class: "label gap-2 cursor-pointer",
+                        input {
+                            r#type: "checkbox",
+                            class: "checkbox checkbox-sm",
+                            checked: options().align,
+                            onchange: move |e| {
+                                let checked = e.checked();
+                                set(Box::new(move |options| options.align = checked));
+                            },
+                        }
+                        "对齐等号"
+                    }
+                    label { class: "label gap-2 cursor-pointer",
+                        input {
+                            r#type: "checkbox",
+                            class: "checkbox checkbox-sm",
+                            checked: options().lowercase,
+                            onchange: move |e| {
+                                let checked = e.checked();
+                                set(Box::new(move |options| options.lowercase = checked));
+                            },
+                        }
+                        "小写类型和字段名"
+                    }
+                    label { class: "label gap-2 cursor-pointer",
+                        input {
+                            r#type: "checkbox",
+                            class: "checkbox checkbox-sm",
+                            checked: options().page_ranges,
+                            onchange: move |e| {
+                                let checked = e.checked();
+                                set(Box::new(move |options| options.page_ranges = checked));
+                            },
+                        }
+                        "页码范围用 --"
+                    }
+                    label { class: "label gap-2 cursor-pointer",
+                        input {
+                            r#type: "checkbox",
+                            class: "checkbox checkbox-sm",
+                            checked: options().remove_empty,
+                            onchange: move |e| {
+                                let checked = e.checked();
+                                set(Box::new(move |options| options.remove_empty = checked));
+                            },
+                        }
+                        "删除空字段"
+                    }
+                    label { class: "label gap-2 cursor-pointer",
+                        input {
+                            r#type: "checkbox",
+                            class: "checkbox checkbox-sm",
+                            checked: options().trailing_comma,
+                            onchange: move |e| {
+                                let checked = e.checked();
+                                set(Box::new(move |options| options.trailing_comma = checked));
+                            },
+                        }
+                        "末尾字段加逗号"
+                    }
+                }
+
+                if let Some(report) = report() {
+                    div { class: "mt-6",
+                        p { class: "text-sm font-semibold mb-2",
+                            "将修改 {report.changes.len()} 条文献"
+                            if report.reordered {
+                                span { class: "font-normal text-base-content/60 ml-2", "并重新排序" }
+                            }
+                        }
+                        div { class: "max-h-[45vh] overflow-y-auto flex flex-col gap-3 font-mono text-xs",
+                            for change in report.changes.iter() {
+                                div {
+                                    key: "{change.key}",
+                                    class: "rounded-md bg-base-200/50 p-2 whitespace-pre",
+                                    for line in diff_lines(&change.old, &change.new) {
+                                        match line {
+                                            DiffLine::Same(text) => rsx! {
+                                                div { class: "text-base-content/60", "  {text}" }
+                                            },
+                                            DiffLine::Removed(text) => rsx! {
+                                                div { class: "text-error bg-error/10", "- {text}" }
+                                            },
+                                            DiffLine::Added(text) => rsx! {
+                                                div { class: "text-success bg-success/10", "+ {text}" }
+                                            },
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+                if let Some(text) = message() {
+                    p { class: "text-sm text-success mt-4", "{text}" }
+                }
+                if let Some(error) = error_message() {
+                    div {
+                        role: "alert",
+                        class: "alert alert-error mt-4 shadow-sm",
+                        img { width: 20, src: ERR_ICON }
+                        span { "{error}" }
+                    }
+                }
+
+                div { class: "modal-action mt-8",
+                    button {
+                        class: "btn btn-ghost hover:bg-base-content/10",
+                        onclick: close_modal,
+                        "关闭"
+                    }
+                    button { class: "btn btn-outline", onclick: preview, "预览差异" }
+                    button {
+                        class: "btn btn-primary",
+                        disabled: report().is_none(),
+                        onclick: write,
+                        "写入 .bib 文件"
+                    }
+                }
+            }
+            div { class: "modal-backdrop bg-base-300/30", onclick: close_modal }
+        }
+    }
+}
@@ -1,7 +1,7 @@
 //! Collections of components, views and tests.
 
 use crate::views::{UpdateWindow, open_spotlight_window};
-use bibcitex_core::{Setting, bib::Reference};
+use bibcitex_core::{SearchIndex, Setting, bib::Reference};
 use dioxus::{
     desktop::{
         HotKeyState, WindowCloseBehaviour,
@@ -11,6 +11,10 @@ use dioxus::{
     },
     prelude::*,
 };
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, LazyLock, Mutex},
+};
 
 pub mod components;
 pub mod route;
@@ -31,9 +35,58 @@ static TRAY_ICON: &[u8] = include_bytes!("../icons/trayicon.png");
 /// global state
 pub static STATE: GlobalSignal<Setting> = Signal::global(Setting::load);
 pub static CURRENT_REF: GlobalSignal<Option<Vec<Reference>>> = Signal::global(|| None);
+pub static CURRENT_BIB: GlobalSignal<Option<String>> = Signal::global(|| None);
 pub static DRAWER_OPEN: GlobalSignal<bool> = Signal::global(|| false);
 pub static DRAWER_REFERENCE: GlobalSignal<Option<Reference>> = Signal::global(|| None);
 
+// 已打开文献库的搜索索引，按文献库名保存，主窗口和助手窗口共用
+static SEARCH_INDEXES: LazyLock<Mutex<HashMap<String, Arc<SearchIndex>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// 同步文献库的搜索索引：首次打开时建立，之后只重新索引新增、修改和删除的文献
+pub fn sync_search_index(library: &str, refs: &[Reference]) -> Arc<SearchIndex> {
+    let mut indexes = SEARCH_INDEXES.lock().unwrap();
+    let Some(index) = indexes.get_mut(library) else {
+        let index = Arc::new(SearchIndex::new(refs.to_vec()));
+        indexes.insert(library.to_string(), index.clone());
+        return index;
+    };
+    let changed = refs
+        .iter()
+        .filter(|r| index.id(&r.cite_key).and_then(|id| index.get(id)) != Some(*r))
+        .collect::<Vec<_>>();
+    let keys = refs
+        .iter()
+        .map(|r| r.cite_key.as_str())
+        .collect::<HashSet<_>>();
+    let removed = index
+        .iter()
+        .filter(|(_, r)| !keys.contains(r.cite_key.as_str()))
+        .map(|(_, r)| r.cite_key.clone())
+        .collect::<Vec<_>>();
+    if !changed.is_empty() || !removed.is_empty() {
+        // 助手窗口正在使用旧索引时复制一份再修改
+        let index = Arc::make_mut(index);
+        for key in removed {
+            index.remove(&key);
+        }
+        for reference in changed {
+            index.insert(reference.clone());
+        }
+    }
+    index.clone()
+}
+
+/// 文献库的搜索索引，未打开过时为 `None`
+pub fn search_index(library: &str) -> Option<Arc<SearchIndex>> {
+    SEARCH_INDEXES.lock().unwrap().get(library).cloned()
+}
+
+/// 删除文献库时丢弃其搜索索引
+pub fn drop_search_index(library: &str) {
+    SEARCH_INDEXES.lock().unwrap().remove(library);
+}
+
 // tailwindcss
 pub static TAILWINDCSS: Asset = asset!("assets/tailwind.css");
 
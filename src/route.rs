@@ -1,4 +1,4 @@
-use crate::views::{Home, NavBar, References};
+use crate::views::{Home, NavBar, Project, References, Statistics};
 use dioxus::prelude::*;
 
 #[derive(Debug, Clone, Routable)]
@@ -8,4 +8,8 @@ pub enum Route {
     Home {},
     #[route("/detail")]
     References {},
+    #[route("/project")]
+    Project {},
+    #[route("/statistics")]
+    Statistics {},
 }
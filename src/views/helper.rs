@@ -1,5 +1,6 @@
-use crate::{TAILWINDCSS, components::Search};
-use bibcitex_core::bib::Reference;
+use crate::{STATE, TAILWINDCSS, components::Search, search_index, sync_search_index};
+use bibcitex_core::{SearchIndex, UsageAction, UsageLog, UsageRecord, bib::Reference};
+use chrono::Local;
 use dioxus::{
     desktop::{
         Config, DesktopService, LogicalSize, WindowBuilder, WindowEvent, tao::event::Event,
@@ -9,6 +10,7 @@ use dioxus::{
     prelude::*,
 };
 use std::{
+    collections::HashMap,
     rc::{Rc, Weak},
     sync::{Arc, LazyLock, Mutex},
 };
@@ -30,6 +32,9 @@ pub static HELPER_BIB: GlobalSignal<Option<(String, Vec<Reference>)>> =
 
 // 辅助函数来设置和获取 HELPER_BIB 状态
 pub fn set_helper_bib(refs: Option<(String, Vec<Reference>)>) {
+    if let Some((name, refs)) = &refs {
+        sync_search_index(name, refs);
+    }
     *HELPER_BIB_STATE.lock().unwrap() = refs.clone();
     *HELPER_BIB.write() = refs;
 }
@@ -38,6 +43,64 @@ pub fn get_helper_bib() -> Option<(String, Vec<Reference>)> {
     HELPER_BIB_STATE.lock().unwrap().clone()
 }
 
+/// HELPER_BIB 的搜索索引，与主窗口共用，主窗口修改文献库后随之更新
+pub fn get_helper_index() -> Option<Arc<SearchIndex>> {
+    let state = HELPER_BIB_STATE.lock().unwrap();
+    let (name, refs) = state.as_ref()?;
+    Some(search_index(name).unwrap_or_else(|| sync_search_index(name, refs)))
+}
+
+// 引用记录，同样在不同 VirtualDom 实例间共享
+static USAGE_LOG: LazyLock<Mutex<UsageLog>> = LazyLock::new(|| Mutex::new(UsageLog::load()));
+
+/// 查询为空时显示的最近引用数量
+const RECENT_LIMIT: usize = 10;
+
+/// 记录一次复制或粘贴的引用，`style` 为空时记录文献库使用的引用模板
+pub fn record_usage<S: AsRef<str>>(
+    keys: &[S],
+    library: Option<&str>,
+    style: Option<&str>,
+    action: UsageAction,
+) {
+    let template = style
+        .map(str::to_string)
+        .unwrap_or_else(|| STATE.read().citation_template(library).name);
+    let records = keys
+        .iter()
+        .map(|key| UsageRecord::new(key.as_ref(), library, Some(&template), action));
+    // 记录失败不影响复制
+    let _ = USAGE_LOG.lock().unwrap().record(records);
+}
+
+/// 引用键重命名后，将文献库中该文献的引用记录移到新引用键下
+pub fn rename_usage_key(library: &str, old: &str, new: &str) -> Result<bool, String> {
+    USAGE_LOG
+        .lock()
+        .unwrap()
+        .rename_key(library, old, new)
+        .map_err(|e| e.to_string())
+}
+
+/// 文献库中各文献按引用次数和时间的加权
+pub fn usage_boosts(library: &str) -> HashMap<String, u32> {
+    USAGE_LOG
+        .lock()
+        .unwrap()
+        .boosts(Some(library), Local::now())
+}
+
+/// 助手文献库中最近引用的文献
+pub fn recent_helper_references() -> Vec<Reference> {
+    let Some((name, refs)) = get_helper_bib() else {
+        return Vec::new();
+    };
+    USAGE_LOG
+        .lock()
+        .unwrap()
+        .recent_references(&refs, Some(&name), RECENT_LIMIT)
+}
+
 pub async fn open_spotlight_window() {
     // 检查是否已经有Helper窗口打开
     let should_close = {
@@ -1,13 +1,21 @@
-use crate::components::{AddBibliography, Bibliographies};
+use crate::components::{AddBibliography, Bibliographies, CitationTemplates, JournalAbbreviations};
 use dioxus::prelude::*;
 
 #[component]
 pub fn Home() -> Element {
     let show_modal = use_signal(|| false);
+    let show_templates = use_signal(|| false);
+    let show_journals = use_signal(|| false);
     rsx! {
-        Bibliographies { show_modal }
+        Bibliographies { show_modal, show_templates, show_journals }
         if show_modal() {
             AddBibliography { show: show_modal }
         }
+        if show_templates() {
+            CitationTemplates { show: show_templates }
+        }
+        if show_journals() {
+            JournalAbbreviations { show: show_journals }
+        }
     }
 }
@@ -2,6 +2,10 @@ pub mod home;
 pub use home::*;
 pub mod nav;
 pub use nav::*;
+pub mod project;
+pub use project::*;
+pub mod statistics;
+pub use statistics::*;
 pub mod reference;
 pub use reference::*;
 pub mod helper;
@@ -1,6 +1,9 @@
 use crate::{
     DRAWER_OPEN, DRAWER_REFERENCE, LOGO,
-    components::{ChunksComp, reference::ReferenceDrawer},
+    components::{
+        ChunksComp, FormattedCitation,
+        reference::{NotesEditor, ReferenceDrawer},
+    },
     route::Route,
     views::open_spotlight_window,
 };
@@ -50,7 +53,18 @@ pub fn NavBar() -> Element {
                     }
                 }
 
-                div { class: "navbar-center", "" }
+                div { class: "navbar-center",
+                    Link {
+                        to: Route::Project {},
+                        class: "btn btn-ghost btn-sm font-normal text-base-content/70",
+                        "LaTeX 项目"
+                    }
+                    Link {
+                        to: Route::Statistics {},
+                        class: "btn btn-ghost btn-sm font-normal text-base-content/70",
+                        "文献统计"
+                    }
+                }
 
                 div { class: "navbar-end pr-4",
                     button {
@@ -105,7 +119,9 @@ pub fn NavBar() -> Element {
                         }
                         div { class: "flex-1 overflow-y-auto p-4",
                             if let Some(entry) = DRAWER_REFERENCE() {
-                                ReferenceDrawer { entry }
+                                ReferenceDrawer { entry: entry.clone() }
+                                FormattedCitation { entry: entry.clone() }
+                                NotesEditor { key: "{entry.cite_key}", entry }
                             } else {
                                 div { class: "flex flex-col items-center justify-center h-full text-base-content/50",
                                     span { class: "text-4xl mb-2", "📄" }
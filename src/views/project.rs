@@ -0,0 +1,350 @@
+use crate::STATE;
+use bibcitex_core::{
+    CitedKeys, KeyOrder, ProjectReport, ProjectScan, export_trimmed, scan_project,
+    utils::read_bibliography_with,
+};
+use dioxus::prelude::*;
+use rfd::FileDialog;
+use std::path::{Path, PathBuf};
+
+/// 与项目引用比较的文献库
+#[derive(Clone, PartialEq)]
+enum LibrarySource {
+    /// 项目中 `\bibliography` 或 `\addbibresource` 指定的 .bib 文件
+    Project,
+    /// 已添加的文献库
+    Library(String),
+}
+
+/// 扫描项目并与文献库比较
+fn scan(path: &Path, source: &LibrarySource) -> Result<(ProjectScan, ProjectReport), String> {
+    let scan = scan_project(path).map_err(|e| e.to_string())?;
+    let separators = STATE.read().tag_separators.clone();
+    let refs = match source {
+        LibrarySource::Project => scan
+            .read_bibliographies(&separators)
+            .map_err(|e| format!("读取项目文献库失败: {e}"))?,
+        LibrarySource::Library(name) => {
+            let bib = STATE.read().parse(name).map_err(|e| e.to_string())?;
+            read_bibliography_with(bib, &separators)
+        }
+    };
+    let report = scan.report(&refs);
+    Ok((scan, report))
+}
+
+/// 按引用导出精简的 .bib 文件，返回导出结果的说明
+fn export(cited: CitedKeys, source: &LibrarySource, order: KeyOrder) -> Result<String, String> {
+    // 项目中的 .bib 文件检测编码，文献库按其设置的编码读取
+    let (bibliographies, encoding) = match source {
+        LibrarySource::Project => (cited.bibliographies.clone(), None),
+        LibrarySource::Library(name) => {
+            let state = STATE.read();
+            let info = state
+                .bibliographies
+                .get(name)
+                .ok_or(format!("文献库 {name} 不存在"))?;
+            let encoding = info.encoding().map_err(|e| e.to_string())?;
+            (vec![info.path.clone()], encoding)
+        }
+    };
+    if bibliographies.is_empty() {
+        return Err("未找到 .bib 文件，请选择文献库".to_string());
+    }
+    let Some(output) = FileDialog::new()
+        .set_title("导出精简 .bib")
+        .set_file_name("references.bib")
+        .add_filter("BibTeX", &["bib"])
+        .save_file()
+    else {
+        return Ok(String::new());
+    };
+    let trimmed = export_trimmed(&bibliographies, encoding, &cited.keys, order, &output)
+        .map_err(|e| format!("导出失败: {e}"))?;
+    let mut message = format!("已导出 {} 条文献到 {}", trimmed.entries, output.display());
+    if !trimmed.missing.is_empty() {
+        message.push_str(&format!("，未找到：{}", trimmed.missing.join(", ")));
+    }
+    Ok(message)
+}
+
+/// 相对于项目根目录显示路径
+fn relative(path: &Path, root: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .display()
+        .to_string()
+}
+
+/// LaTeX、Markdown 和 Typst 项目：列出未定义和未使用的引用键，以及每个引用键的引用次数和位置
+#[component]
+pub fn Project() -> Element {
+    let mut path = use_signal(|| None::<PathBuf>);
+    let mut source = use_signal(|| LibrarySource::Project);
+    let mut result = use_signal(|| None::<Result<(ProjectScan, ProjectReport), String>>);
+    let libraries = use_memo(|| {
+        let mut names = STATE
+            .read()
+            .bibliographies
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>();
+        names.sort();
+        names
+    });
+
+    let mut rescan = move || {
+        if let Some(path) = path() {
+            result.set(Some(scan(&path, &source())));
+        }
+    };
+    let mut pick = move |folder: bool| {
+        let dialog = FileDialog::new().set_title("选择 LaTeX、Markdown 或 Typst 项目");
+        let picked = if folder {
+            dialog.pick_folder()
+        } else {
+            dialog
+                .add_filter("LaTeX", &["tex"])
+                .add_filter("Markdown", &["md", "markdown", "qmd", "rmd"])
+                .add_filter("Typst", &["typ"])
+                .pick_file()
+        };
+        if let Some(picked) = picked {
+            path.set(Some(picked));
+            rescan();
+        }
+    };
+
+    rsx! {
+        div { class: "h-full overflow-y-auto p-6 flex flex-col gap-4",
+            div { class: "flex flex-wrap items-center gap-2",
+                h2 { class: "text-xl font-bold mr-4", "LaTeX 项目" }
+                button {
+                    class: "btn btn-sm btn-primary",
+                    onclick: move |_| pick(true),
+                    "选择项目目录"
+                }
+                button {
+                    class: "btn btn-sm btn-outline",
+                    onclick: move |_| pick(false),
+                    "选择主文件"
+                }
+                select {
+                    class: "select select-sm",
+                    onchange: move |e| {
+                        let value = e.value();
+                        source.set(if value.is_empty() { LibrarySource::Project } else { LibrarySource::Library(value) });
+                        rescan();
+                    },
+                    option {
+                        value: "",
+                        selected: source() == LibrarySource::Project,
+                        "项目中的 .bib 文件"
+                    }
+                    for name in libraries() {
+                        option {
+                            value: "{name}",
+                            selected: source() == LibrarySource::Library(name.clone()),
+                            "{name}"
+                        }
+                    }
+                }
+                button {
+                    class: "btn btn-sm btn-ghost",
+                    disabled: path().is_none(),
+                    onclick: move |_| rescan(),
+                    "重新扫描"
+                }
+            }
+            if let Some(path) = path() {
+                p { class: "text-sm text-base-content/60", "{path.display()}" }
+            }
+            ExportTrimmed {
+                scan: result().and_then(|result| result.ok()).map(|(scan, _)| scan),
+                source: source(),
+            }
+            match result() {
+                None => rsx! {
+                    p { class: "text-base-content/50", "选择项目目录，或主 .tex、Markdown、Quarto、Typst 文件以检查引用" }
+                },
+                Some(Err(error)) => rsx! {
+                    div { class: "alert alert-error", "{error}" }
+                },
+                Some(Ok((scan, report))) => rsx! {
+                    ProjectReportView { scan, report }
+                },
+            }
+        }
+    }
+}
+
+/// 导出只含已引用文献的 .bib 文件，包括交叉引用的父文献和用到的 @string
+#[component]
+fn ExportTrimmed(scan: Option<ProjectScan>, source: LibrarySource) -> Element {
+    let mut order = use_signal(KeyOrder::default);
+    let mut message = use_signal(|| None::<Result<String, String>>);
+    let scanned = scan.is_some();
+    let from_project = {
+        let source = source.clone();
+        move |_| {
+            if let Some(scan) = &scan {
+                message.set(Some(export(
+                    CitedKeys::from_project(scan),
+                    &source,
+                    order(),
+                )));
+            }
+        }
+    };
+    let from_aux = move |_| {
+        let Some(path) = FileDialog::new()
+            .set_title("选择 .aux 或 .bcf 文件")
+            .add_filter("LaTeX", &["aux", "bcf"])
+            .pick_file()
+        else {
+            return;
+        };
+        message.set(Some(
+            CitedKeys::read(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|cited| export(cited, &source, order())),
+        ));
+    };
+
+    rsx! {
+        div { class: "flex flex-wrap items-center gap-2",
+            span { class: "font-semibold mr-2", "导出精简 .bib" }
+            select {
+                class: "select select-sm w-32",
+                onchange: move |e| {
+                    order.set(if e.value() == "alphabetical" { KeyOrder::Alphabetical } else { KeyOrder::Citation });
+                },
+                option {
+                    value: "citation",
+                    selected: order() == KeyOrder::Citation,
+                    "引用顺序"
+                }
+                option {
+                    value: "alphabetical",
+                    selected: order() == KeyOrder::Alphabetical,
+                    "字母顺序"
+                }
+            }
+            button {
+                class: "btn btn-sm btn-outline",
+                disabled: !scanned,
+                onclick: from_project,
+                "按项目引用导出"
+            }
+            button { class: "btn btn-sm btn-outline", onclick: from_aux, "从 .aux/.bcf 导出" }
+            match message() {
+                Some(Ok(text)) if !text.is_empty() => rsx! {
+                    span { class: "text-sm text-success", "{text}" }
+                },
+                Some(Err(error)) => rsx! {
+                    span { class: "text-sm text-error", "{error}" }
+                },
+                _ => rsx! {},
+            }
+        }
+    }
+}
+
+#[component]
+fn ProjectReportView(scan: ProjectScan, report: ProjectReport) -> Element {
+    let root = scan.root().to_path_buf();
+    rsx! {
+        div { class: "stats shadow bg-base-100",
+            div { class: "stat",
+                div { class: "stat-title", "文件" }
+                div { class: "stat-value text-2xl", "{scan.files.len()}" }
+            }
+            div { class: "stat",
+                div { class: "stat-title", "引用键" }
+                div { class: "stat-value text-2xl", "{report.counts.len()}" }
+            }
+            div { class: "stat",
+                div { class: "stat-title", "未定义" }
+                div { class: "stat-value text-2xl text-error", "{report.undefined.len()}" }
+            }
+            div { class: "stat",
+                div { class: "stat-title", "未使用" }
+                div { class: "stat-value text-2xl text-warning", "{report.unused.len()}" }
+            }
+        }
+        if !scan.missing.is_empty() {
+            div { class: "alert alert-warning flex-col items-start",
+                span { "以下文件不存在：" }
+                for file in scan.missing.iter() {
+                    code { class: "text-xs", "{relative(file, &root)}" }
+                }
+            }
+        }
+        if scan.nocite_all {
+            p { class: "text-sm text-base-content/60", "项目引用了全部文献（\\nocite{{*}}、nocite: @* 或 full: true），所有文献均视为已引用" }
+        }
+        div { class: "grid md:grid-cols-2 gap-4",
+            KeyList {
+                title: "未定义的引用键",
+                keys: report.undefined.clone(),
+                class: "badge-error",
+            }
+            KeyList {
+                title: "未引用的文献",
+                keys: report.unused.clone(),
+                class: "badge-warning",
+            }
+        }
+        div { class: "card bg-base-100 shadow-sm",
+            div { class: "card-body p-4",
+                h3 { class: "card-title text-base", "引用次数" }
+                table { class: "table table-sm",
+                    thead {
+                        tr {
+                            th { "引用键" }
+                            th { "次数" }
+                            th { "位置" }
+                        }
+                    }
+                    tbody {
+                        for (key , locations) in report.counts.iter() {
+                            tr { key: "{key}",
+                                td {
+                                    code {
+                                        class: if report.undefined.contains(key) { "text-error" } else { "" },
+                                        "{key}"
+                                    }
+                                }
+                                td { "{locations.len()}" }
+                                td { class: "text-xs text-base-content/60",
+                                    for location in locations.iter() {
+                                        div { "{relative(&location.file, &root)}:{location.line}" }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn KeyList(title: String, keys: Vec<String>, class: String) -> Element {
+    rsx! {
+        div { class: "card bg-base-100 shadow-sm",
+            div { class: "card-body p-4",
+                h3 { class: "card-title text-base", "{title} ({keys.len()})" }
+                if keys.is_empty() {
+                    p { class: "text-sm text-base-content/50", "无" }
+                }
+                div { class: "flex flex-wrap gap-1",
+                    for key in keys {
+                        span { key: "{key}", class: "badge badge-sm badge-soft {class}", "{key}" }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,61 +1,194 @@
 use crate::{
-    CURRENT_REF,
-    components::{FilterFieldSelector, FilterTypeSelector, ReferenceComponent},
+    CURRENT_BIB, CURRENT_REF, STATE,
+    components::{
+        ActiveGroup, ActiveJabRefGroup, FilterFieldSelector, FilterTypeSelector, Highlight,
+        JabRefGroups, ReadingFilter, ReadingFilterSelector, ReferenceComponent, Selection,
+        SmartGroups, TagFilter, TagSidebar, TitleTool,
+    },
+    search_index,
 };
 use bibcitex_core::{
-    bib::Reference, filter::*, search_references, search_references_by_author,
-    search_references_by_journal, search_references_by_title, search_references_by_year,
+    Query, QueryField, bib::Reference, filter::*, parse_jabref_groups, rank_references_in,
 };
+use biblatex::EntryType;
 use dioxus::prelude::*;
+use std::collections::HashSet;
 
-#[derive(Clone, Copy, PartialEq)]
-pub enum FilterField {
-    Author,
-    Title,
-    Journal,
-    Year,
-    All,
+/// 用当前文献库的搜索索引排序，只保留筛选后显示的文献
+fn rank_shown(shown: &[Reference], query: &str, fields: &[QueryField]) -> Vec<Reference> {
+    let Some(index) = CURRENT_BIB.peek().as_deref().and_then(search_index) else {
+        return rank_references_in(shown, query, fields);
+    };
+    let keys = shown
+        .iter()
+        .map(|r| r.cite_key.as_str())
+        .collect::<HashSet<_>>();
+    index
+        .rank_in(query, fields)
+        .into_iter()
+        .filter_map(|id| index.get(id))
+        .filter(|r| keys.contains(r.cite_key.as_str()))
+        .cloned()
+        .collect()
+}
+
+/// 文献列表的筛选条件：类型、阅读状态和评分、标签，智能分组保存的也是这个条件
+pub fn view_filter(filter_type: &FilterType, reading: ReadingFilter, tags: &[String]) -> Filter {
+    tags.iter().fold(
+        filter_type.to_filter().and(reading.to_filter()),
+        |filter, tag| filter.and(Filter::Tag(tag.clone())),
+    )
+}
+
+/// 搜索字段，未限定字段的搜索词只在选中的字段中查找
+#[derive(Clone, PartialEq)]
+pub struct FilterField(Vec<QueryField>);
+
+/// 文献类型，可同时选中多个，未选中任何类型时显示所有文献
+#[derive(Clone, Default, PartialEq)]
+pub struct FilterType(Vec<EntryType>);
+
+impl FilterField {
+    /// 选中的字段
+    pub fn fields(&self) -> &[QueryField] {
+        &self.0
+    }
+
+    /// 是否选中所有字段
+    pub fn is_all(&self) -> bool {
+        self.0.len() == QueryField::defaults().len()
+    }
+
+    /// 是否选中某个字段
+    pub fn contains(&self, field: QueryField) -> bool {
+        self.0.contains(&field)
+    }
+
+    /// 切换某个字段，至少保留一个字段
+    pub fn toggle(&mut self, field: QueryField) {
+        if let Some(pos) = self.0.iter().position(|&f| f == field) {
+            if self.0.len() > 1 {
+                self.0.remove(pos);
+            }
+        } else {
+            // 保持与 `QueryField::defaults` 相同的顺序
+            self.0 = QueryField::defaults()
+                .iter()
+                .copied()
+                .filter(|&f| f == field || self.0.contains(&f))
+                .collect();
+        }
+    }
+
+    /// 选中所有字段
+    pub fn select_all(&mut self) {
+        self.0 = QueryField::defaults().to_vec();
+    }
 }
 
-#[derive(Clone, Copy, PartialEq)]
-pub enum FilterType {
-    Book,
-    Article,
-    Thesis,
-    TechReport,
-    Misc,
-    Booklet,
-    InBook,
-    InCollection,
-    InProceedings,
-    All,
+impl Default for FilterField {
+    fn default() -> Self {
+        Self(QueryField::defaults().to_vec())
+    }
 }
 
 impl std::fmt::Display for FilterField {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            FilterField::Author => write!(f, "Author"),
-            FilterField::Title => write!(f, "Title"),
-            FilterField::Journal => write!(f, "Journal"),
-            FilterField::Year => write!(f, "Year"),
-            FilterField::All => write!(f, "Field"),
+        match self.0.as_slice() {
+            _ if self.is_all() => write!(f, "Field"),
+            [field] => write!(f, "{field}"),
+            fields => write!(f, "{} fields", fields.len()),
+        }
+    }
+}
+
+impl FilterType {
+    /// 可选的文献类型
+    pub const TYPES: [EntryType; 9] = [
+        EntryType::Book,
+        EntryType::Article,
+        EntryType::Thesis,
+        EntryType::TechReport,
+        EntryType::Misc,
+        EntryType::Booklet,
+        EntryType::InBook,
+        EntryType::InCollection,
+        EntryType::InProceedings,
+    ];
+
+    /// 是否显示所有类型
+    pub fn is_all(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// 是否选中某个类型
+    pub fn contains(&self, entry_type: &EntryType) -> bool {
+        self.0.contains(entry_type)
+    }
+
+    /// 切换某个类型
+    pub fn toggle(&mut self, entry_type: &EntryType) {
+        if let Some(pos) = self.0.iter().position(|t| t == entry_type) {
+            self.0.remove(pos);
+        } else {
+            // 保持与 `FilterType::TYPES` 相同的顺序
+            self.0 = Self::TYPES
+                .iter()
+                .filter(|&t| t == entry_type || self.0.contains(t))
+                .cloned()
+                .collect();
+        }
+    }
+
+    /// 显示所有类型
+    pub fn select_all(&mut self) {
+        self.0.clear();
+    }
+
+    /// 对应的过滤条件
+    pub fn to_filter(&self) -> Filter {
+        Filter::types(self.0.iter().cloned())
+    }
+
+    /// 类型名
+    pub fn name(entry_type: &EntryType) -> &'static str {
+        match entry_type {
+            EntryType::Book => "Book",
+            EntryType::Article => "Article",
+            EntryType::Thesis => "Thesis",
+            EntryType::TechReport => "TechReport",
+            EntryType::Misc => "Misc",
+            EntryType::Booklet => "Booklet",
+            EntryType::InBook => "InBook",
+            EntryType::InCollection => "InCollection",
+            EntryType::InProceedings => "InProceedings",
+            _ => "Other",
+        }
+    }
+
+    /// 列表标题
+    pub fn title(&self) -> &'static str {
+        match self.0.as_slice() {
+            [EntryType::Article] => "Articles",
+            [EntryType::Book] => "Books",
+            [EntryType::Thesis] => "Thesis",
+            [EntryType::TechReport] => "TechReports",
+            [EntryType::Misc] => "Misc",
+            [EntryType::Booklet] => "Booklets",
+            [EntryType::InBook] => "InBooks",
+            [EntryType::InCollection] => "InCollections",
+            [EntryType::InProceedings] => "InProceedings",
+            _ => "References",
         }
     }
 }
 
 impl std::fmt::Display for FilterType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            FilterType::Book => write!(f, "Book"),
-            FilterType::Article => write!(f, "Article"),
-            FilterType::Thesis => write!(f, "Thesis"),
-            FilterType::TechReport => write!(f, "TechReport"),
-            FilterType::Misc => write!(f, "Misc"),
-            FilterType::Booklet => write!(f, "Booklet"),
-            FilterType::InBook => write!(f, "InBook"),
-            FilterType::InCollection => write!(f, "InCollection"),
-            FilterType::InProceedings => write!(f, "InProceedings"),
-            FilterType::All => write!(f, "Type"),
+        match self.0.as_slice() {
+            [] => write!(f, "Type"),
+            [entry_type] => write!(f, "{}", Self::name(entry_type)),
+            types => write!(f, "{} types", types.len()),
         }
     }
 }
@@ -66,34 +199,54 @@ pub fn References() -> Element {
     let mut query = use_context_provider(|| Signal::new(String::new()));
     let mut is_input = use_signal(|| false);
     let mut search_result = use_context_provider(|| Signal::new(Vec::<Reference>::new()));
-    let filter_field = use_context_provider(|| Signal::new(FilterField::All));
-    let filter_type = use_context_provider(|| Signal::new(FilterType::All));
+    let filter_field = use_context_provider(|| Signal::new(FilterField::default()));
+    // 查询语法错误，此时按纯文本搜索
+    let query_error = use_memo(move || {
+        Query::parse_in(&query(), filter_field().fields())
+            .err()
+            .map(|e| e.to_string())
+    });
+    let highlight = use_memo(move || Query::parse_in(&query(), filter_field().fields()).ok());
+    use_context_provider(|| Highlight(highlight));
+    let filter_type = use_context_provider(|| Signal::new(FilterType::default()));
+    let reading_filter = use_context_provider(|| Signal::new(ReadingFilter::default()));
+    // JabRef 分组保存在 .bib 文件的注释中，编辑文献库后重新读取
+    let jabref_tree = use_memo(move || {
+        let _ = CURRENT_REF();
+        let name = CURRENT_BIB()?;
+        let source = STATE.read().bibliographies.get(&name)?.read_source().ok()?;
+        parse_jabref_groups(&source.text)
+    });
+    let jabref_group = use_signal(|| None::<String>);
+    use_context_provider(|| ActiveJabRefGroup(jabref_group));
+    let tag_filter = use_signal(Vec::<String>::new);
+    use_context_provider(|| TagFilter(tag_filter));
+    use_context_provider(|| Selection(Signal::new(Vec::new())));
     let refs = use_memo(move || {
         let total_refs = CURRENT_REF().unwrap_or_default();
-        match filter_type() {
-            FilterType::Book => filter_book(total_refs),
-            FilterType::Article => filter_article(total_refs),
-            FilterType::Thesis => filter_thesis(total_refs),
-            FilterType::TechReport => filter_techreport(total_refs),
-            FilterType::Misc => filter_misc(total_refs),
-            FilterType::Booklet => filter_booklet(total_refs),
-            FilterType::InBook => filter_inbook(total_refs),
-            FilterType::InCollection => filter_incollection(total_refs),
-            FilterType::InProceedings => filter_inproceedings(total_refs),
-            FilterType::All => total_refs,
+        let filter = view_filter(&filter_type(), reading_filter(), &tag_filter());
+        let refs = filter_references(&total_refs, &filter, CURRENT_BIB().as_deref());
+        match (jabref_group(), jabref_tree()) {
+            (Some(name), Some(tree)) => tree.members(&name, &refs),
+            _ => refs,
         }
     });
-    let show_type = use_memo(move || match filter_type() {
-        FilterType::All => "References".to_string(),
-        FilterType::Article => "Articles".to_string(),
-        FilterType::Book => "Books".to_string(),
-        FilterType::Thesis => "Thesis".to_string(),
-        FilterType::TechReport => "TechReports".to_string(),
-        FilterType::Misc => "Misc".to_string(),
-        FilterType::Booklet => "Booklets".to_string(),
-        FilterType::InBook => "InBooks".to_string(),
-        FilterType::InCollection => "InCollections".to_string(),
-        FilterType::InProceedings => "InProceedings".to_string(),
+    let show_type =
+        use_memo(move || jabref_group().unwrap_or_else(|| filter_type().title().to_string()));
+    let mut active_group = use_signal(|| None::<String>);
+    use_context_provider(|| ActiveGroup(active_group));
+    // 智能分组的结果，随文献库和分组设置更新
+    let group_refs = use_memo(move || {
+        let name = active_group()?;
+        let state = STATE.read();
+        let search = state.saved_searches.iter().find(|s| s.name == name)?;
+        let total_refs = CURRENT_REF().unwrap_or_default();
+        let refs = search.run(
+            &total_refs,
+            CURRENT_BIB().as_deref(),
+            jabref_tree().as_ref(),
+        );
+        Some((name, refs))
     });
 
     use_effect(move || {
@@ -101,54 +254,79 @@ pub fn References() -> Element {
             is_input.set(false);
         }
     });
+    // 筛选条件或文献库变化时重新搜索
+    use_effect(move || {
+        let refs = refs();
+        let query = query.peek().clone();
+        if !query.is_empty() {
+            search_result.set(rank_shown(&refs, &query, filter_field.peek().fields()));
+        }
+    });
+    // 当前显示的文献，用于批量选择
+    let shown = use_memo(move || match group_refs() {
+        Some((_, group)) => group,
+        None if is_input() => search_result(),
+        None => refs(),
+    });
 
     let search = move |e: Event<FormData>| {
         is_input.set(true);
+        active_group.set(None);
         query.set(e.value());
-        let result = match filter_field() {
-            FilterField::Author => search_references_by_author(&refs(), &query()),
-            FilterField::Title => search_references_by_title(&refs(), &query()),
-            FilterField::Journal => search_references_by_journal(&refs(), &query()),
-            FilterField::Year => search_references_by_year(&refs(), &query()),
-            FilterField::All => search_references(&refs(), &query()),
-        };
-        search_result.set(result);
+        search_result.set(rank_shown(&refs(), &query(), filter_field().fields()));
     };
 
     rsx! {
-        div { class: "flex flex-col h-full overflow-hidden",
-            // Fixed search bar at top
-            div { class: "shrink-0 p-4 bg-base-100 border-b border-base-300 overflow-hidden",
-                div { class: "join w-full max-w-full overflow-hidden",
-                    FilterTypeSelector { refs }
-                    FilterFieldSelector { refs }
-                    input {
-                        r#type: "search",
-                        class: "input input-primary join-item flex-1 min-w-0",
-                        placeholder: "搜索文献...",
-                        value: "{query}",
-                        oninput: search,
-                    }
-                }
+        div { class: "flex h-full overflow-hidden",
+            aside { class: "w-56 shrink-0 border-r border-base-300 overflow-y-auto p-3 flex flex-col gap-4",
+                JabRefGroups { tree: jabref_tree }
+                TagSidebar { shown }
+                TitleTool {}
             }
-
-            // Scrollable content area
-            div { class: "flex-1 overflow-y-auto overflow-x-hidden",
-                if !is_input() {
-                    h2 { class: "text-lg p-2", "{show_type()} ({refs().len()}/{total_num})" }
-                    for reference in refs() {
-                        ReferenceComponent { entry: reference }
+            div { class: "flex flex-col flex-1 min-w-0 h-full overflow-hidden",
+                // Fixed search bar at top
+                div { class: "shrink-0 p-4 bg-base-100 border-b border-base-300 overflow-hidden",
+                    div { class: "join w-full max-w-full overflow-hidden",
+                        FilterTypeSelector { refs }
+                        FilterFieldSelector { refs }
+                        ReadingFilterSelector {}
+                        input {
+                            r#type: "search",
+                            class: "input input-primary join-item flex-1 min-w-0",
+                            placeholder: "搜索文献，如 author:knuth year:2010..2020 NOT type:misc status:read",
+                            value: "{query}",
+                            oninput: search,
+                        }
                     }
-                } else {
-                    h2 { class: "text-lg p-2",
-                        "{show_type()} ({search_result().len()}/{refs().len()})"
+                    if let Some(error) = query_error() {
+                        p { class: "text-xs text-warning mt-1 px-1", "{error}" }
                     }
-                    if !search_result().is_empty() {
-                        for reference in search_result() {
+                    SmartGroups { tree: jabref_tree }
+                }
+
+                // Scrollable content area
+                div { class: "flex-1 overflow-y-auto overflow-x-hidden",
+                    if let Some((name, group)) = group_refs() {
+                        h2 { class: "text-lg p-2", "{name} ({group.len()}/{total_num})" }
+                        for reference in group {
+                            ReferenceComponent { entry: reference }
+                        }
+                    } else if !is_input() {
+                        h2 { class: "text-lg p-2", "{show_type()} ({refs().len()}/{total_num})" }
+                        for reference in refs() {
                             ReferenceComponent { entry: reference }
                         }
                     } else {
-                        p { class: "p-2 text-lg text-red-500", "No results" }
+                        h2 { class: "text-lg p-2",
+                            "{show_type()} ({search_result().len()}/{refs().len()})"
+                        }
+                        if !search_result().is_empty() {
+                            for reference in search_result() {
+                                ReferenceComponent { entry: reference }
+                            }
+                        } else {
+                            p { class: "p-2 text-lg text-red-500", "No results" }
+                        }
                     }
                 }
             }
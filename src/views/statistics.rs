@@ -0,0 +1,323 @@
+use crate::{CURRENT_BIB, STATE};
+use bibcitex_core::{
+    CoauthorGraph, Filter, GraphFilter, LibraryStats, bib::Reference, utils::read_bibliography_with,
+};
+use dioxus::prelude::*;
+use rfd::FileDialog;
+
+/// 作者、期刊等排行显示的条数
+const TOP: usize = 10;
+
+/// 合作网络的导出格式：名称、扩展名
+const GRAPH_FORMATS: [(&str, &str); 3] = [("GraphML", "graphml"), ("DOT", "dot"), ("JSON", "json")];
+
+/// 读取文献库
+fn read_library(name: &str) -> Result<Vec<Reference>, String> {
+    let bib = STATE.read().parse(name).map_err(|e| e.to_string())?;
+    Ok(read_bibliography_with(bib, &STATE.read().tag_separators))
+}
+
+/// 按年份范围筛选文献，未设置范围时包括无年份的文献
+fn year_filter(from_year: Option<i32>, to_year: Option<i32>) -> Filter {
+    if from_year.is_none() && to_year.is_none() {
+        Filter::All
+    } else {
+        Filter::years(from_year, to_year)
+    }
+}
+
+/// 读取文献库并统计符合筛选条件的文献
+fn compute(name: &str, filter: &Filter) -> Result<LibraryStats, String> {
+    Ok(LibraryStats::new(&read_library(name)?, filter, Some(name)))
+}
+
+/// 将文献库的合作网络导出为 GraphML、DOT 或 JSON，返回导出结果的说明
+fn export_graph(name: &str, filter: &GraphFilter, extension: &str) -> Result<String, String> {
+    let graph = CoauthorGraph::new(&read_library(name)?, filter, Some(name));
+    let Some(output) = FileDialog::new()
+        .set_title("导出合作网络")
+        .set_file_name(format!("{name}-合作网络.{extension}"))
+        .add_filter(extension, &[extension])
+        .save_file()
+    else {
+        return Ok(String::new());
+    };
+    let content = match extension {
+        "graphml" => graph.to_graphml(),
+        "dot" => graph.to_dot(),
+        _ => graph.to_json().map_err(|e| e.to_string())?,
+    };
+    std::fs::write(&output, content).map_err(|e| format!("导出失败: {e}"))?;
+    Ok(format!(
+        "已导出 {} 位作者、{} 条合作关系到 {}",
+        graph.nodes.len(),
+        graph.edges.len(),
+        output.display()
+    ))
+}
+
+/// 将统计结果导出为 CSV 或 Markdown，返回导出结果的说明
+fn export(name: &str, stats: &LibraryStats, markdown: bool) -> Result<String, String> {
+    let (file_name, filter, extension) = if markdown {
+        (format!("{name}-统计.md"), "Markdown", "md")
+    } else {
+        (format!("{name}-统计.csv"), "CSV", "csv")
+    };
+    let Some(output) = FileDialog::new()
+        .set_title("导出统计")
+        .set_file_name(&file_name)
+        .add_filter(filter, &[extension])
+        .save_file()
+    else {
+        return Ok(String::new());
+    };
+    let content = if markdown {
+        stats.to_markdown(name, TOP)
+    } else {
+        stats.to_csv()
+    };
+    std::fs::write(&output, content).map_err(|e| format!("导出失败: {e}"))?;
+    Ok(format!("已导出到 {}", output.display()))
+}
+
+/// 文献库统计：条目类型、逐年发表数、高产作者、合作者、期刊和出版社，以及 DOI、URL、PDF 的比例
+#[component]
+pub fn Statistics() -> Element {
+    let libraries = use_memo(|| {
+        let mut names = STATE
+            .read()
+            .bibliographies
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>();
+        names.sort();
+        names
+    });
+    let mut library = use_signal(|| {
+        CURRENT_BIB()
+            .or_else(|| libraries().first().cloned())
+            .unwrap_or_default()
+    });
+    let mut from_year = use_signal(|| None::<i32>);
+    let mut to_year = use_signal(|| None::<i32>);
+    let filter = use_memo(move || year_filter(from_year(), to_year()));
+    let stats = use_memo(move || (!library().is_empty()).then(|| compute(&library(), &filter())));
+    let mut message = use_signal(|| None::<Result<String, String>>);
+
+    let mut save = move |markdown: bool| {
+        if let Some(Ok(stats)) = stats() {
+            match export(&library(), &stats, markdown) {
+                // 取消了保存对话框
+                Ok(text) if text.is_empty() => {}
+                result => message.set(Some(result)),
+            }
+        }
+    };
+
+    rsx! {
+        div { class: "h-full overflow-y-auto p-6 flex flex-col gap-4",
+            div { class: "flex flex-wrap items-center gap-2",
+                h2 { class: "text-xl font-bold mr-4", "文献统计" }
+                select {
+                    class: "select select-sm",
+                    onchange: move |e| {
+                        library.set(e.value());
+                        message.set(None);
+                    },
+                    option { value: "", selected: library().is_empty(), disabled: true, "选择文献库" }
+                    for name in libraries() {
+                        option { value: "{name}", selected: library() == name, "{name}" }
+                    }
+                }
+                span { class: "text-sm ml-2", "年份" }
+                input {
+                    r#type: "number",
+                    class: "input input-bordered input-sm w-24",
+                    placeholder: "起始",
+                    value: from_year().map(|year| year.to_string()).unwrap_or_default(),
+                    oninput: move |e| from_year.set(e.value().parse().ok()),
+                }
+                span { "—" }
+                input {
+                    r#type: "number",
+                    class: "input input-bordered input-sm w-24",
+                    placeholder: "截止",
+                    value: to_year().map(|year| year.to_string()).unwrap_or_default(),
+                    oninput: move |e| to_year.set(e.value().parse().ok()),
+                }
+                button {
+                    class: "btn btn-sm btn-outline",
+                    disabled: !matches!(stats(), Some(Ok(_))),
+                    onclick: move |_| save(false),
+                    "导出 CSV"
+                }
+                button {
+                    class: "btn btn-sm btn-outline",
+                    disabled: !matches!(stats(), Some(Ok(_))),
+                    onclick: move |_| save(true),
+                    "导出 Markdown"
+                }
+            }
+            match message() {
+                Some(Ok(text)) => rsx! {
+                    p { class: "text-sm text-success", "{text}" }
+                },
+                Some(Err(error)) => rsx! {
+                    p { class: "text-sm text-error", "{error}" }
+                },
+                None => rsx! {},
+            }
+            match stats() {
+                None => rsx! {
+                    p { class: "text-base-content/50", "选择文献库以查看统计" }
+                },
+                Some(Err(error)) => rsx! {
+                    div { class: "alert alert-error", "{error}" }
+                },
+                Some(Ok(stats)) => rsx! {
+                    StatisticsView { stats }
+                    CoauthorExport { library: library(), filter: filter() }
+                },
+            }
+        }
+    }
+}
+
+#[component]
+fn StatisticsView(stats: LibraryStats) -> Element {
+    let busiest = stats
+        .years
+        .iter()
+        .map(|(_, n)| *n)
+        .max()
+        .unwrap_or(1)
+        .max(1);
+    let pairs = stats
+        .coauthors
+        .iter()
+        .map(|((first, second), n)| (format!("{first} & {second}"), *n))
+        .collect::<Vec<_>>();
+    rsx! {
+        div { class: "stats stats-vertical lg:stats-horizontal shadow bg-base-100",
+            div { class: "stat",
+                div { class: "stat-title", "文献" }
+                div { class: "stat-value", "{stats.total}" }
+                if stats.undated > 0 {
+                    div { class: "stat-desc", "{stats.undated} 条无年份" }
+                }
+            }
+            for (name , count) in [("DOI", stats.with_doi), ("URL", stats.with_url), ("PDF", stats.with_file)] {
+                div { class: "stat",
+                    div { class: "stat-title", "含 {name}" }
+                    div { class: "stat-value", "{stats.share(count):.0}%" }
+                    div { class: "stat-desc", "{count} 条" }
+                }
+            }
+        }
+        if !stats.years.is_empty() {
+            div { class: "card bg-base-100 shadow p-4",
+                h3 { class: "font-semibold mb-3", "逐年发表数" }
+                div { class: "flex items-end gap-1 h-40",
+                    for (year , count) in stats.years.iter().copied() {
+                        div {
+                            key: "{year}",
+                            class: "flex-1 min-w-1 bg-primary/70 hover:bg-primary rounded-t tooltip",
+                            "data-tip": "{year}: {count}",
+                            style: "height: {count * 100 / busiest}%",
+                        }
+                    }
+                }
+                div { class: "flex justify-between text-xs text-base-content/50 mt-1",
+                    span { "{stats.years[0].0}" }
+                    span { "{stats.years[stats.years.len() - 1].0}" }
+                }
+            }
+        }
+        div { class: "grid grid-cols-1 lg:grid-cols-2 gap-4",
+            CountTable { title: "条目类型", counts: stats.entry_types.clone() }
+            CountTable { title: "高产作者", counts: stats.authors.iter().take(TOP).cloned().collect() }
+            CountTable { title: "合作者", counts: pairs.into_iter().take(TOP).collect() }
+            CountTable { title: "期刊", counts: stats.journals.iter().take(TOP).cloned().collect() }
+            CountTable { title: "出版社", counts: stats.publishers.iter().take(TOP).cloned().collect() }
+        }
+    }
+}
+
+/// 导出合作网络，文献按统计页的年份范围筛选，合作关系按最少合作次数筛选
+#[component]
+fn CoauthorExport(library: String, filter: Filter) -> Element {
+    let mut min_weight = use_signal(|| 1usize);
+    let mut message = use_signal(|| None::<Result<String, String>>);
+
+    rsx! {
+        div { class: "card bg-base-100 shadow p-4 flex flex-col gap-3",
+            h3 { class: "font-semibold", "合作网络" }
+            p { class: "text-sm text-base-content/60",
+                "作者为节点，按文献数加权；合作者之间连边，按合作文献数加权。可导入 Gephi 等工具"
+            }
+            div { class: "flex flex-wrap items-center gap-2 text-sm",
+                span { "最少合作次数" }
+                input {
+                    r#type: "number",
+                    class: "input input-bordered input-sm w-20",
+                    min: 1,
+                    value: "{min_weight}",
+                    oninput: move |e| min_weight.set(e.value().parse().unwrap_or(1).max(1)),
+                }
+                for (name , extension) in GRAPH_FORMATS {
+                    button {
+                        class: "btn btn-sm btn-outline",
+                        onclick: {
+                            let library = library.clone();
+                            let filter = filter.clone();
+                            move |_| {
+                                let filter = GraphFilter {
+                                    references: filter.clone(),
+                                    min_weight: min_weight(),
+                                };
+                                match export_graph(&library, &filter, extension) {
+                                    // 取消了保存对话框
+                                    Ok(text) if text.is_empty() => {}
+                                    result => message.set(Some(result)),
+                                }
+                            }
+                        },
+                        "导出 {name}"
+                    }
+                }
+            }
+            match message() {
+                Some(Ok(text)) => rsx! {
+                    p { class: "text-sm text-success", "{text}" }
+                },
+                Some(Err(error)) => rsx! {
+                    p { class: "text-sm text-error", "{error}" }
+                },
+                None => rsx! {},
+            }
+        }
+    }
+}
+
+#[component]
+fn CountTable(title: String, counts: Vec<(String, usize)>) -> Element {
+    rsx! {
+        div { class: "card bg-base-100 shadow p-4",
+            h3 { class: "font-semibold mb-2", "{title}" }
+            if counts.is_empty() {
+                p { class: "text-sm text-base-content/50", "暂无" }
+            } else {
+                table { class: "table table-sm",
+                    tbody {
+                        for (name , count) in counts {
+                            tr { key: "{name}",
+                                td { class: "truncate max-w-xs", title: "{name}", "{name}" }
+                                td { class: "text-right font-mono", "{count}" }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}